@@ -2,6 +2,7 @@ use log::{warn, Level};
 use wasm_bindgen::prelude::wasm_bindgen;
 
 mod server;
+mod stream;
 mod target;
 mod utils;
 
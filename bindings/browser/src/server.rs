@@ -1,18 +1,83 @@
-use std::iter::FromIterator;
+use std::time::Duration;
 
-use erpc::protocol;
+use erpc::{
+    codec::Codec,
+    heartbeat::HeartbeatOptions,
+    protocol::{self, SendableError},
+    schema::EndpointSchema,
+};
+use futures::{stream, StreamExt};
+use http_client_wasm::ReconnectOptions;
 use log::error;
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use serde::Deserialize;
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
 
 #[wasm_bindgen(typescript_custom_section)]
 const SERVER_OPTIONS: &'static str = r#"
-interface ServerOptions {}
+interface ServerOptions {
+    reconnectDelay?: number;
+    maxReconnectDelay?: number;
+    heartbeatInterval?: number;
+    maxMissedPongs?: number;
+    codec?: Codec;
+}
+
+type ConnectionState = "connecting" | "open" | "closed" | "rejected";
 "#;
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "ServerOptions")]
     pub type ServerOptions;
+
+    #[wasm_bindgen(typescript_type = "(state: ConnectionState, address: string) => void")]
+    pub type ConnectionStateListener;
+}
+
+#[derive(Deserialize)]
+struct InternalServerOptions {
+    /**
+       Milliseconds to wait before the first reconnect of a closed websocket
+    */
+    #[serde(default, rename = "reconnectDelay")]
+    reconnect_delay: Option<u32>,
+    /**
+       Upper limit in milliseconds for the exponentially growing reconnect delay
+    */
+    #[serde(default, rename = "maxReconnectDelay")]
+    max_reconnect_delay: Option<u32>,
+    /**
+       Milliseconds between the pings which check that the websockets to targets are alive, no pings are sent if not set
+    */
+    #[serde(default, rename = "heartbeatInterval")]
+    heartbeat_interval: Option<u32>,
+    /**
+       Websockets which did not answer this many pings in a row are connected again, 2 if not set
+    */
+    #[serde(default, rename = "maxMissedPongs")]
+    max_missed_pongs: Option<u32>,
+    /**
+       The format of the websocket connections to targets, json if not set
+    */
+    #[serde(default)]
+    codec: Option<Codec>,
+}
+
+impl From<InternalServerOptions> for ReconnectOptions {
+    fn from(options: InternalServerOptions) -> Self {
+        let default = ReconnectOptions::default();
+        ReconnectOptions {
+            initial_delay: options
+                .reconnect_delay
+                .map(|v| Duration::from_millis(v.into()))
+                .unwrap_or(default.initial_delay),
+            max_delay: options
+                .max_reconnect_delay
+                .map(|v| Duration::from_millis(v.into()))
+                .unwrap_or(default.max_delay),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -33,33 +98,172 @@ impl ERPCServer {
         enable_sockets: bool,
         role: String,
     ) -> Self {
-        Self {
-            server: http_client_wasm::Server::new(role),
+        let mut server = http_client_wasm::Server::new(role);
+
+        let js_value: JsValue = options.into();
+        if !js_value.is_undefined() {
+            match serde_wasm_bindgen::from_value::<InternalServerOptions>(js_value) {
+                Ok(v) => {
+                    if let Some(codec) = v.codec {
+                        server.set_codec(codec);
+                    }
+                    server.set_heartbeat(v.heartbeat_interval.map(|interval| {
+                        HeartbeatOptions {
+                            interval: Duration::from_millis(interval.into()),
+                            max_missed_pongs: v
+                                .max_missed_pongs
+                                .unwrap_or(HeartbeatOptions::default().max_missed_pongs),
+                        }
+                    }));
+                    server.set_reconnect_options(v.into());
+                }
+                Err(err) => error!("Invalid server options, using defaults: {}", err),
+            }
         }
+
+        Self { server }
+    }
+
+    /**
+       Calls the listener whenever the websocket connection to a target is connecting, opened, closed or rejected
+    */
+    #[wasm_bindgen(js_name = "onConnectionStateChange")]
+    pub fn on_connection_state_change(&mut self, listener: ConnectionStateListener) {
+        let listener: js_sys::Function = listener.unchecked_into();
+        self.server
+            .on_connection_state_change(Box::new(move |state, address| {
+                if let Err(err) = listener.call2(
+                    &JsValue::null(),
+                    &JsValue::from_str(state.as_str()),
+                    &JsValue::from_str(address),
+                ) {
+                    error!("Connection state listener failed: {:#?}", err);
+                }
+            }));
     }
 
     #[wasm_bindgen(skip_typescript, js_name = "registerERPCHandler")]
-    pub fn register_erpc_handler(&mut self, handler: js_sys::Function, identifier: String) {
+    pub fn register_erpc_handler(
+        &mut self,
+        handler: js_sys::Function,
+        identifier: String,
+        returns_result: Option<bool>,
+        middleware: Option<Vec<JsValue>>,
+        schema: JsValue,
+    ) {
+        // the transpiler rejects middleware on browser roles, so this only happens with outdated generated code
+        if !middleware.unwrap_or_default().is_empty() {
+            error!("Middleware is not supported by browsers, it is ignored for {identifier}");
+        }
+
+        if !schema.is_undefined() && !schema.is_null() {
+            match serde_wasm_bindgen::from_value::<EndpointSchema>(schema) {
+                Ok(v) => self.server.set_endpoint_schema(identifier.clone(), v),
+                Err(err) => error!("Invalid schema for {identifier}: {err}"),
+            }
+        }
+
+        // handlers of endpoints which declare an error type return a result object
+        let returns_result = returns_result.unwrap_or(false);
         self.server.register_raw_handler(
             Box::new(move |input| {
-                let parameters = js_sys::Array::from_iter(
-                    input
-                        .parameters
-                        .iter()
-                        .map(|param| serde_wasm_bindgen::to_value(param).unwrap()),
-                );
-
-                let result = handler
+                let parameters = match parameters_array(input.parameters.into_arguments()) {
+                    Ok(v) => v,
+                    Err(err) => return Box::pin(async move { err.into() }),
+                };
+                let returned = match handler.apply(&JsValue::null(), &parameters) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        error!("Apply call failed: {:#?}", err);
+                        return Box::pin(async { SendableError::HandlerPanicked.into() });
+                    }
+                };
+
+                Box::pin(async move {
+                    // async handlers return a promise, resolving other values returns them unchanged
+                    let value = match JsFuture::from(js_sys::Promise::resolve(&returned)).await {
+                        Ok(v) => v,
+                        Err(err) => {
+                            error!("Handler failed: {:#?}", err);
+                            return SendableError::HandlerPanicked.into();
+                        }
+                    };
+                    let value = match serde_wasm_bindgen::from_value(value) {
+                        Ok(v) => v,
+                        Err(err) => {
+                            return SendableError::from(format!(
+                                "Could not serialize the value returned by the handler: {err}"
+                            ))
+                            .into()
+                        }
+                    };
+
+                    if returns_result {
+                        protocol::Response::from_result_object(value)
+                    } else {
+                        value.into()
+                    }
+                })
+            }),
+            identifier,
+        );
+    }
+
+    /**
+       Registers the handler of a streaming endpoint. The handler returns an async iterable, e.g. by being an async generator.
+       The next item is only requested from the iterator when the caller consumes the items.
+    */
+    #[wasm_bindgen(skip_typescript, js_name = "registerERPCStreamHandler")]
+    pub fn register_erpc_stream_handler(
+        &mut self,
+        handler: js_sys::Function,
+        identifier: String,
+        middleware: Option<Vec<JsValue>>,
+        schema: JsValue,
+    ) {
+        if !middleware.unwrap_or_default().is_empty() {
+            error!("Middleware is not supported by browsers, it is ignored for {identifier}");
+        }
+
+        if !schema.is_undefined() && !schema.is_null() {
+            match serde_wasm_bindgen::from_value::<EndpointSchema>(schema) {
+                Ok(v) => self.server.set_endpoint_schema(identifier.clone(), v),
+                Err(err) => error!("Invalid schema for {identifier}: {err}"),
+            }
+        }
+
+        self.server.register_raw_stream_handler(
+            Box::new(move |input| {
+                let parameters = match parameters_array(input.parameters.into_arguments()) {
+                    Ok(v) => v,
+                    Err(err) => return stream::once(async { Err(err) }).boxed_local(),
+                };
+
+                let iterator = match handler
                     .apply(&JsValue::null(), &parameters)
-                    .map_err(|err| {
-                        protocol::error::SendableError::from(format!(
-                            "Apply call failed: {:#?}",
-                            err
-                        ))
-                    })
-                    .map(|v| serde_wasm_bindgen::from_value(v).unwrap());
-
-                protocol::Response { body: result }
+                    .and_then(|iterable| async_iterator(&iterable))
+                {
+                    Ok(v) => v,
+                    Err(err) => {
+                        error!("Apply call failed: {:#?}", err);
+                        return stream::once(async { Err(SendableError::HandlerPanicked) })
+                            .boxed_local();
+                    }
+                };
+
+                // the iterator is dropped after it is done or failed
+                stream::unfold(Some(iterator), |iterator| async move {
+                    let iterator = iterator?;
+                    match next_item(&iterator).await {
+                        Ok(Some(v)) => Some((Ok(v), Some(iterator))),
+                        Ok(None) => None,
+                        Err(err) => {
+                            error!("Stream handler failed: {:#?}", err);
+                            Some((Err(SendableError::HandlerPanicked), None))
+                        }
+                    }
+                })
+                .boxed_local()
             }),
             identifier,
         );
@@ -75,3 +279,45 @@ impl ERPCServer {
     //     self.server.stop();
     // }
 }
+
+/**
+   Converts the parameters of a call to the arguments of a handler
+*/
+fn parameters_array(parameters: Vec<serde_json::Value>) -> Result<js_sys::Array, SendableError> {
+    parameters
+        .iter()
+        .map(|param| {
+            serde_wasm_bindgen::to_value(param).map_err(|err| {
+                SendableError::from(format!(
+                    "Could not pass the parameters to the handler: {err}"
+                ))
+            })
+        })
+        .collect()
+}
+
+/**
+   Gets the async iterator of an async iterable. Objects without Symbol.asyncIterator are used as iterator directly
+*/
+fn async_iterator(iterable: &JsValue) -> Result<JsValue, JsValue> {
+    match js_sys::Reflect::get(iterable, &js_sys::Symbol::async_iterator()) {
+        Ok(v) if v.is_function() => v.unchecked_into::<js_sys::Function>().call0(iterable),
+        _ => Ok(iterable.clone()),
+    }
+}
+
+/**
+   Awaits the next item of an async iterator, None if the iterator is done
+*/
+async fn next_item(iterator: &JsValue) -> Result<Option<serde_json::Value>, JsValue> {
+    let next: js_sys::Function =
+        js_sys::Reflect::get(iterator, &JsValue::from_str("next"))?.dyn_into()?;
+    let result = JsFuture::from(js_sys::Promise::resolve(&next.call0(iterator)?)).await?;
+
+    if js_sys::Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+        return Ok(None);
+    }
+
+    let value = js_sys::Reflect::get(&result, &JsValue::from_str("value"))?;
+    Ok(Some(serde_wasm_bindgen::from_value(value)?))
+}
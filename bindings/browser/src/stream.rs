@@ -0,0 +1,77 @@
+use std::rc::Rc;
+
+use erpc::stream::StreamReceiver;
+use futures::{lock::Mutex, StreamExt};
+use serde::Serialize;
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::target::sendable_error_to_js;
+
+/**
+   The items of a streaming endpoint, it implements the async iterator protocol.
+   Generated code wraps it in an object with Symbol.asyncIterator so it can be used in for await loops.
+*/
+#[wasm_bindgen]
+pub struct ERPCStream {
+    // None once the stream ended or was returned early
+    items: Rc<Mutex<Option<StreamReceiver>>>,
+}
+
+impl ERPCStream {
+    pub fn new(items: StreamReceiver) -> Self {
+        Self {
+            items: Rc::new(Mutex::new(Some(items))),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ERPCStream {
+    /**
+       Resolves to the next item of the stream, rejects with an ERPCError if the stream failed
+    */
+    pub async fn next(&self) -> Result<JsValue, JsValue> {
+        let mut items = self.items.lock().await;
+        let item = match items.as_mut() {
+            Some(v) => v.next().await,
+            None => None,
+        };
+
+        match item {
+            // json compatible serialization creates plain objects instead of maps
+            Some(Ok(v)) => Ok(iterator_result(
+                v.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?,
+                false,
+            )),
+            Some(Err(err)) => {
+                items.take();
+                Err(sendable_error_to_js(err))
+            }
+            None => {
+                items.take();
+                Ok(iterator_result(JsValue::undefined(), true))
+            }
+        }
+    }
+
+    /**
+       Stops the stream, the endpoint on the other side is cancelled if it still produces items
+    */
+    #[wasm_bindgen(js_name = "return")]
+    pub async fn return_(&self) -> JsValue {
+        // dropping the receiver cancels the stream
+        self.items.lock().await.take();
+        iterator_result(JsValue::undefined(), true)
+    }
+}
+
+fn iterator_result(value: JsValue, done: bool) -> JsValue {
+    let result = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&result, &JsValue::from_str("value"), &value);
+    let _ = js_sys::Reflect::set(
+        &result,
+        &JsValue::from_str("done"),
+        &JsValue::from_bool(done),
+    );
+    result.into()
+}
@@ -1,10 +1,36 @@
-use serde::Deserialize;
+use erpc::codec::Codec;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
 
+use crate::stream::ERPCStream;
+
 #[wasm_bindgen(typescript_custom_section)]
 const TARGET_OPTIONS: &'static str = r#"
 interface TargetOptions {
     address: string;
+    timeout?: number;
+    replayRequests?: boolean;
+    codec?: Codec;
+}
+
+type Codec = "json" | "messagePack";
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const ERPC_ERROR: &'static str = r#"
+interface ERPCError {
+    type:
+        | "NotFound"
+        | "Internal"
+        | "BadRequest"
+        | "Timeout"
+        | "Unauthorized"
+        | "ConnectionClosed"
+        | "HandlerPanicked"
+        | "Overloaded"
+        | "Application";
+    message: string;
+    path?: string;
 }
 "#;
 
@@ -17,6 +43,21 @@ extern "C" {
 #[derive(Deserialize)]
 struct InternalTargetOptions {
     address: String,
+    /**
+       Milliseconds after which calls fail with a Timeout error, calls wait forever if not set
+    */
+    #[serde(default)]
+    timeout: Option<u32>,
+    /**
+       Sends calls again which were still open when the websocket closed instead of failing them
+    */
+    #[serde(default, rename = "replayRequests")]
+    replay_requests: bool,
+    /**
+       The format of http requests to the target, json if not set
+    */
+    #[serde(default)]
+    codec: Codec,
 }
 
 #[wasm_bindgen]
@@ -27,21 +68,33 @@ pub struct ERPCTarget {
 #[wasm_bindgen]
 impl ERPCTarget {
     #[wasm_bindgen(constructor)]
-    pub fn new(options: TargetOptions, target_type: &str) -> Result<ERPCTarget, JsValue> {
+    pub fn new(
+        options: TargetOptions,
+        target_type: &str,
+        schema_hash: Option<String>,
+    ) -> Result<ERPCTarget, JsValue> {
         let js_value: JsValue = options.into();
 
         let options: InternalTargetOptions = serde_wasm_bindgen::from_value(js_value)?;
 
-        Ok(ERPCTarget {
-            target: http_client_wasm::Target::new(
-                options.address,
-                match target_type {
-                    "http-server" => erpc::target::TargetType::HttpServer,
-                    "browser" => erpc::target::TargetType::Browser,
-                    _ => return Err(JsError::new("Invalid value for target type").into()),
-                },
-            ),
-        })
+        let target = http_client_wasm::Target::new(
+            options.address,
+            match target_type {
+                "http-server" => erpc::target::TargetType::HttpServer,
+                "browser" => erpc::target::TargetType::Browser,
+                _ => return Err(JsError::new("Invalid value for target type").into()),
+            },
+        );
+        target.set_timeout(
+            options
+                .timeout
+                .map(|v| std::time::Duration::from_millis(v.into())),
+        );
+        target.set_replay_requests(options.replay_requests);
+        target.set_codec(options.codec);
+        target.set_schema_hash(schema_hash);
+
+        Ok(ERPCTarget { target })
     }
 
     //TODO remove unwraps
@@ -49,12 +102,11 @@ impl ERPCTarget {
     pub async fn call(
         &self,
         identifier: String,
-        parameters: Vec<JsValue>,
+        parameters: JsValue,
+        returns_result: Option<bool>,
     ) -> Result<JsValue, JsValue> {
-        let parameters = parameters
-            .into_iter()
-            .map(|param| serde_wasm_bindgen::from_value(param).unwrap())
-            .collect();
+        // a list of parameters, or an object for endpoints with named parameters
+        let parameters = serde_wasm_bindgen::from_value(parameters)?;
 
         let result = self
             .target
@@ -64,9 +116,67 @@ impl ERPCTarget {
             })
             .await;
 
-        match result.body {
-            Ok(v) => Ok(serde_wasm_bindgen::to_value(&v).unwrap()),
-            Err(err) => Err(JsError::new(&err.to_string()).into()),
+        // endpoints which declare an error type resolve to a result object
+        let body = if returns_result.unwrap_or(false) {
+            result.into_result_object()
+        } else {
+            result.body
+        };
+
+        match body {
+            // json compatible serialization creates plain objects instead of maps
+            Ok(v) => Ok(v
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+                .unwrap()),
+            Err(err) => Err(sendable_error_to_js(err)),
+        }
+    }
+
+    /**
+       Sets the browser which is called by a browser target, the server at the address of the target relays the calls to it.
+       The socket id is the one the server assigned to the websocket connection of the destination.
+    */
+    #[wasm_bindgen(js_name = "setRelayDestination")]
+    pub fn set_relay_destination(&self, role: String, socket_id: String) {
+        self.target.set_relay_destination(role, socket_id);
+    }
+
+    /**
+       Calls a streaming endpoint, the items are requested over the websocket connection to the target
+    */
+    #[wasm_bindgen(skip_typescript)]
+    pub fn stream(&self, identifier: String, parameters: JsValue) -> Result<ERPCStream, JsValue> {
+        let parameters = serde_wasm_bindgen::from_value(parameters)?;
+
+        match self.target.call_stream(erpc::protocol::Request {
+            identifier,
+            parameters,
+        }) {
+            Ok(v) => Ok(ERPCStream::new(v)),
+            Err(err) => Err(sendable_error_to_js(err)),
         }
     }
 }
+
+/**
+   Turns a SendableError into a js Error which carries the fields of the ERPCError interface
+*/
+pub(crate) fn sendable_error_to_js(err: erpc::protocol::SendableError) -> JsValue {
+    let js_error = js_sys::Error::new(&err.message());
+
+    let details = match serde_json::to_value(&err) {
+        Ok(serde_json::Value::Object(v)) => v,
+        _ => return js_error.into(),
+    };
+    for (key, value) in details {
+        // the message of the js error is the safe message of the whole error
+        if key == "message" || value.is_null() {
+            continue;
+        }
+        if let Ok(value) = value.serialize(&serde_wasm_bindgen::Serializer::json_compatible()) {
+            let _ = js_sys::Reflect::set(&js_error, &JsValue::from_str(&key), &value);
+        }
+    }
+
+    js_error.into()
+}
@@ -6,21 +6,186 @@
 export interface ServerOptions {
   port: number
   allowedCorsOrigins: Array<string>
+  /**
+   * The address the server binds to, all interfaces (0.0.0.0) if not set.
+   * IPv6 addresses like ::1 work as well, unix:/path/to.sock listens on a unix domain socket instead of a port.
+   */
+  bindAddress?: string
+  /** Serves https instead of http if set, it is not applied to unix domain sockets */
+  tls?: TlsOptions
+  /** Prefix of the routes of the server, e.g. rpc serves the handlers below /rpc/handlers */
+  routePrefix?: string
+  /** Milliseconds between the pings which check that websockets are still alive, no pings are sent if not set */
+  heartbeatInterval?: number
+  /** Websockets which did not answer this many pings in a row are closed, 2 if not set */
+  maxMissedPongs?: number
+  /** Milliseconds after which websockets without any calls are closed, they stay open if not set */
+  idleTimeout?: number
+  /** How many messages can wait to be sent to a single websocket, calls to a full socket fail with an Overloaded error. 1024 if not set */
+  socketQueueSize?: number
+  /** How many calls can wait for the response of a single websocket at once, further calls fail with an Overloaded error. 256 if not set */
+  maxInFlightRequests?: number
+  /** How many calls of a single websocket are handled at once, further calls wait until one of them finished. 64 if not set */
+  maxConcurrentSocketRequests?: number
+  /** Rejects calls and websockets of clients which were generated from other .erpc sources than the server, they are only reported if not set */
+  strictSchema?: boolean
+  /** Rejects websockets whose first message is not a handshake, clients from before the handshake existed are served if not set */
+  requireHandshake?: boolean
+  /** Milliseconds a websocket has to send its handshake if requireHandshake is set, 10 seconds if not set */
+  handshakeTimeout?: number
+  /** The largest request body in bytes the handler route reads, larger calls fail with a BadRequest error. 64 KiB if not set */
+  maxBodySize?: number
+}
+export interface TlsOptions {
+  /** The PEM encoded certificate chain */
+  certificate: string
+  /** The PEM encoded private key of the certificate */
+  key: string
+  /** Additionally listens for HTTP/3 (QUIC) on the same port, this needs a fixed port */
+  http3?: boolean
 }
 export interface TargetOptions {
   address: string
+  /** Milliseconds after which calls fail with a Timeout error, calls wait forever if not set */
+  timeout?: number
+  /** The format of http requests to the target, json if not set */
+  codec?: "json" | "messagePack"
+}
+/** The shape of errors thrown by call, the type field names the SendableError variant */
+export interface ERPCError {
+  type: "NotFound" | "Internal" | "BadRequest" | "Timeout" | "Unauthorized" | "ConnectionClosed" | "HandlerPanicked" | "Overloaded" | "Application"
+  message: string
+  path?: string
+}
+/**
+Information about the caller, handlers receive it as this.
+Calls over a websocket share the context which was created when the socket connected.
+*/
+export interface ERPCRequestContext {
+  /** The headers of the http request or the websocket upgrade request, names are lowercase */
+  headers: Record<string, string>
+  remoteAddress?: string
+  /** Set for calls over a websocket, the id is unique per connection */
+  socketId?: string
+  /** The role the browser connected with, set for calls over a websocket */
+  role?: string
+  /** The value the authenticator returned */
+  auth: any
+  /** The values the middleware of the endpoint stored in the context of the call */
+  middleware: Record<string, any>
+}
+/** A request another http framework like express or fastify received for the handler route */
+export interface ERPCHttpRequest {
+  /** The path of the request, e.g. /rpc/handlers/add */
+  path: string
+  body: Buffer
+  /** The headers of the request, the content type decides the format of the body */
+  headers: Record<string, string>
+  remoteAddress?: string
+}
+export interface ERPCHttpResponse {
+  status: number
+  contentType: string
+  body: Buffer
+}
+/** A browser which is connected to the server via websocket */
+export interface ERPCSocketInfo {
+  /** The socketId of the ERPCRequestContext the browser connected with */
+  id: string
+  role: string
+}
+/** The queue depths of a connected browser, full queues mean that the browser or the server can't keep up */
+export interface ERPCSocketMetrics {
+  id: string
+  role: string
+  /** Messages which wait to be sent to the browser */
+  outgoingQueue: number
+  /** Responses of the browser which wait to be read */
+  incomingQueue: number
+  /** Calls which wait for a response of the browser */
+  inFlightRequests: number
+}
+/** Describes a websocket connection which ended, message is set for reasons other than closed */
+export interface ERPCSocketDisconnect {
+  id: string
+  role: string
+  reason: "closed" | "connectionLost" | "invalidMessage" | "heartbeatTimeout" | "idleTimeout" | "handshakeRejected"
+  message?: string
+}
+/** A client which was generated from other .erpc sources than the server called it or connected to it */
+export interface ERPCSchemaMismatch {
+  /** The schema hash of the server */
+  expected: string
+  /** The schema hash the client was generated with */
+  received: string
+  context: ERPCRequestContext
 }
 export class ERPCServer {
-  constructor(options: ServerOptions, serverType: string, enableSockets: boolean, role: string)
+  constructor(options: ServerOptions, serverType: string, enableSockets: boolean, role: string, schemaHash?: string | undefined | null)
+  /**
+  Sets the authenticator which is called with the ERPCRequestContext of every http request and websocket connection.
+  What it returns or resolves to is available to handlers as this.auth, throwing rejects the caller as unauthorized.
+  */
+  setAuthenticator(authenticator: (context: ERPCRequestContext) => unknown): void
+  /**
+  Calls the callback whenever a websocket connection ended.
+  Calls which still wait for a response of the socket fail with a ConnectionClosed error.
+  */
+  onSocketDisconnection(callback: (disconnect: ERPCSocketDisconnect) => void): void
+  /**
+  Calls the callback whenever a client which was generated from other .erpc sources calls the server or connects to it
+  */
+  onSchemaMismatch(callback: (mismatch: ERPCSchemaMismatch) => void): void
+  /**
+  The browsers which are connected right now, only those of the role if one is given
+  */
+  connectedSockets(role?: string | undefined | null): Array<ERPCSocketInfo>
+  /**
+  The connected browser with the socket id, undefined if it is not connected (anymore)
+  */
+  connectedSocket(id: string): ERPCSocketInfo | null
+  /**
+  The queue depths of all connected browsers
+  */
+  socketMetrics(): Array<ERPCSocketMetrics>
+  /**
+  Calls the endpoint a request of another http framework is for, e.g. from an express or fastify route.
+  Paths which are not below the handler route are answered with 404.
+  */
+  handleRequest(request: ERPCHttpRequest): Promise<ERPCHttpResponse>
   /**
   Starts the server as configured
   */
   run(): Promise<void>
   /**
+  Resolves to the address the server listens on once it runs, e.g. 127.0.0.1:8080 for port 0
+  */
+  listening(): Promise<string>
+  /**
+  The address the server listens on, undefined if it is not running
+  */
+  get localAddress(): string | null
+  /**
   * Stops the server
   */
   stop(): void
 }
+/** The items of a streaming endpoint, it implements the async iterator protocol */
+export class ERPCStream {
+  /** Resolves to the next item of the stream, rejects with an ERPCError if the stream failed */
+  next(): Promise<IteratorResult<any>>
+  /** Stops the stream, the endpoint on the other side is cancelled if it still produces items */
+  return(): Promise<IteratorResult<any>>
+}
 export class ERPCTarget {
-  constructor(options: TargetOptions, targetType: string)
+  constructor(options: TargetOptions, targetType: string, schemaHash?: string | undefined | null)
+  /**
+   * The id of the socket the target calls, it is set for targets which onConnection created
+   */
+  get socketId(): string | null
+  /**
+   * Calls the handlers of a server in the same process from now on instead of sending http requests, e.g. for server side rendering.
+   * Calls still pass the authenticator, schema validation and middleware of the server.
+   */
+  setLoopback(server: ERPCServer): void
 }
@@ -246,7 +246,8 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { ERPCServer, ERPCTarget } = nativeBinding
+const { ERPCServer, ERPCStream, ERPCTarget } = nativeBinding
 
 module.exports.ERPCServer = ERPCServer
+module.exports.ERPCStream = ERPCStream
 module.exports.ERPCTarget = ERPCTarget
@@ -4,6 +4,7 @@
 //TODO: maybe rework error handling? use custom error type to prevent .map_err calls
 
 mod server;
+mod stream;
 mod target;
 mod threadsafe_function;
 
@@ -3,13 +3,15 @@
 //TODO: remove unwraps
 //TODO: refactoring
 
-use std::convert::Infallible;
+use std::{collections::HashMap, convert::Infallible, time::Duration};
 
-use erpc::protocol;
-use http_server::Socket;
+use erpc::{heartbeat::HeartbeatOptions, protocol, schema::EndpointSchema};
+use futures::{stream, StreamExt};
+use http_server::{DisconnectReason, MiddlewareCall, Socket};
+use log::error;
 use napi::{
-    bindgen_prelude::{FromNapiValue, Promise},
-    Env, JsFunction, JsUnknown, NapiRaw,
+    bindgen_prelude::{Buffer, FromNapiValue, Promise},
+    Env, JsFunction, JsObject, JsUnknown, NapiRaw, ValueType,
 };
 use tokio::sync::oneshot;
 
@@ -19,11 +21,170 @@ use crate::INITIALIZED;
 pub struct ServerOptions {
     pub port: u16,
     pub allowed_cors_origins: Vec<String>,
+    /**
+       The address the server binds to, all interfaces (0.0.0.0) if not set.
+       IPv6 addresses like ::1 work as well, unix:/path/to.sock listens on a unix domain socket instead of a port.
+    */
+    pub bind_address: Option<String>,
+    /**
+       Serves https instead of http if set, it is not applied to unix domain sockets
+    */
+    pub tls: Option<TlsOptions>,
+    /**
+       Prefix of the routes of the server, e.g. rpc serves the handlers below /rpc/handlers
+    */
+    pub route_prefix: Option<String>,
+    /**
+       Milliseconds between the pings which check that websockets are still alive, no pings are sent if not set
+    */
+    pub heartbeat_interval: Option<u32>,
+    /**
+       Websockets which did not answer this many pings in a row are closed, 2 if not set
+    */
+    pub max_missed_pongs: Option<u32>,
+    /**
+       Milliseconds after which websockets without any calls are closed, they stay open if not set
+    */
+    pub idle_timeout: Option<u32>,
+    /**
+       How many messages can wait to be sent to a single websocket, calls to a full socket fail with an Overloaded error. 1024 if not set
+    */
+    pub socket_queue_size: Option<u32>,
+    /**
+       How many calls can wait for the response of a single websocket at once, further calls fail with an Overloaded error. 256 if not set
+    */
+    pub max_in_flight_requests: Option<u32>,
+    /**
+       How many calls of a single websocket are handled at once, further calls wait until one of them finished. 64 if not set
+    */
+    pub max_concurrent_socket_requests: Option<u32>,
+    /**
+       Rejects calls and websockets of clients which were generated from other .erpc sources than the server, they are only reported if not set
+    */
+    pub strict_schema: Option<bool>,
+    /**
+       Rejects websockets whose first message is not a handshake, clients from before the handshake existed are served if not set
+    */
+    pub require_handshake: Option<bool>,
+    /**
+       Milliseconds a websocket has to send its handshake if requireHandshake is set, 10 seconds if not set
+    */
+    pub handshake_timeout: Option<u32>,
+    /**
+       The largest request body in bytes the handler route reads, larger calls fail with a BadRequest error. 64 KiB if not set
+    */
+    pub max_body_size: Option<u32>,
+}
+
+#[napi(object)]
+pub struct TlsOptions {
+    /**
+       The PEM encoded certificate chain
+    */
+    pub certificate: String,
+    /**
+       The PEM encoded private key of the certificate
+    */
+    pub key: String,
+    /**
+       Additionally listens for HTTP/3 (QUIC) on the same port, this needs a fixed port
+    */
+    pub http3: Option<bool>,
+}
+
+/**
+   Information about the caller, handlers receive it as this.
+   Calls over a websocket share the context which was created when the socket connected.
+*/
+#[napi(object, js_name = "ERPCRequestContext")]
+pub struct ERPCRequestContext {
+    /** The headers of the http request or the websocket upgrade request, names are lowercase */
+    pub headers: HashMap<String, String>,
+    pub remote_address: Option<String>,
+    /** Set for calls over a websocket, the id is unique per connection */
+    pub socket_id: Option<String>,
+    /** The role the browser connected with, set for calls over a websocket */
+    pub role: Option<String>,
+    /** The value the authenticator returned */
+    pub auth: serde_json::Value,
+    /** The values the middleware of the endpoint stored in the context of the call */
+    pub middleware: serde_json::Map<String, serde_json::Value>,
+}
+
+/**
+   A request another http framework like express or fastify received for the handler route
+*/
+#[napi(object, js_name = "ERPCHttpRequest")]
+pub struct ERPCHttpRequest {
+    /** The path of the request, e.g. /rpc/handlers/add */
+    pub path: String,
+    pub body: Buffer,
+    /** The headers of the request, the content type decides the format of the body */
+    pub headers: HashMap<String, String>,
+    pub remote_address: Option<String>,
+}
+
+#[napi(object, js_name = "ERPCHttpResponse")]
+pub struct ERPCHttpResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Buffer,
+}
+
+/**
+   A browser which is connected to the server via websocket
+*/
+#[napi(object, js_name = "ERPCSocketInfo")]
+pub struct ERPCSocketInfo {
+    /** The socketId of the ERPCRequestContext the browser connected with */
+    pub id: String,
+    pub role: String,
+}
+
+/**
+   The queue depths of a connected browser, full queues mean that the browser or the server can't keep up
+*/
+#[napi(object, js_name = "ERPCSocketMetrics")]
+pub struct ERPCSocketMetrics {
+    pub id: String,
+    pub role: String,
+    /** Messages which wait to be sent to the browser */
+    pub outgoing_queue: u32,
+    /** Responses of the browser which wait to be read */
+    pub incoming_queue: u32,
+    /** Calls which wait for a response of the browser */
+    pub in_flight_requests: u32,
+}
+
+/**
+   Describes a websocket connection which ended, message is set for reasons other than closed
+*/
+#[napi(object, js_name = "ERPCSocketDisconnect")]
+pub struct ERPCSocketDisconnect {
+    pub id: String,
+    pub role: String,
+    #[napi(
+        ts_type = "\"closed\" | \"connectionLost\" | \"invalidMessage\" | \"heartbeatTimeout\" | \"idleTimeout\" | \"handshakeRejected\""
+    )]
+    pub reason: String,
+    pub message: Option<String>,
+}
+
+/**
+   A client which was generated from other .erpc sources than the server called it or connected to it
+*/
+#[napi(object, js_name = "ERPCSchemaMismatch")]
+pub struct ERPCSchemaMismatch {
+    /** The schema hash of the server */
+    pub expected: String,
+    /** The schema hash the client was generated with */
+    pub received: String,
+    pub context: ERPCRequestContext,
 }
 
 #[napi(js_name = "ERPCServer")]
 pub struct ERPCServer {
-    server: http_server::Server,
+    pub(crate) server: http_server::Server,
 }
 
 #[napi]
@@ -35,16 +196,64 @@ impl ERPCServer {
         _server_type: String, // exists for consistency reasons but isn't actually needed
         enable_sockets: bool,
         _role: String, // might become handy in the future
+        schema_hash: Option<String>,
     ) -> Self {
         if *INITIALIZED {}
 
-        ERPCServer {
-            server: http_server::Server::new(
-                options.port,
-                options.allowed_cors_origins,
-                enable_sockets,
-            ),
+        let defaults = http_server::ServerOptions::default();
+
+        let server = http_server::Server::new(
+            http_server::ServerOptions {
+                port: options.port,
+                allowed_cors_origins: options.allowed_cors_origins,
+                bind_address: options.bind_address,
+                tls: options.tls.map(|tls| http_server::TlsOptions {
+                    certificate: tls.certificate.into_bytes(),
+                    key: tls.key.into_bytes(),
+                    http3: tls.http3.unwrap_or(false),
+                }),
+                route_prefix: options.route_prefix,
+                heartbeat: options.heartbeat_interval.map(|v| HeartbeatOptions {
+                    interval: Duration::from_millis(v.into()),
+                    max_missed_pongs: options
+                        .max_missed_pongs
+                        .unwrap_or(HeartbeatOptions::default().max_missed_pongs),
+                }),
+                idle_timeout: options
+                    .idle_timeout
+                    .map(|v| Duration::from_millis(v.into())),
+                socket_queue_size: options
+                    .socket_queue_size
+                    .map(|v| v as usize)
+                    .unwrap_or(defaults.socket_queue_size),
+                max_in_flight_requests: options
+                    .max_in_flight_requests
+                    .map(|v| v as usize)
+                    .unwrap_or(defaults.max_in_flight_requests),
+                max_concurrent_socket_requests: options
+                    .max_concurrent_socket_requests
+                    .map(|v| v as usize)
+                    .unwrap_or(defaults.max_concurrent_socket_requests),
+                strict_schema: options.strict_schema.unwrap_or(defaults.strict_schema),
+                require_handshake: options
+                    .require_handshake
+                    .unwrap_or(defaults.require_handshake),
+                handshake_timeout: options
+                    .handshake_timeout
+                    .map(|v| Duration::from_millis(v.into()))
+                    .unwrap_or(defaults.handshake_timeout),
+                max_body_size: options
+                    .max_body_size
+                    .map(|v| v as usize)
+                    .unwrap_or(defaults.max_body_size),
+            },
+            enable_sockets,
+        );
+        if let Some(schema_hash) = schema_hash {
+            server.set_schema_hash(schema_hash);
         }
+
+        ERPCServer { server }
     }
 
     #[napi(skip_typescript, js_name = "registerERPCHandler")]
@@ -53,14 +262,107 @@ impl ERPCServer {
         env: Env,
         func: JsFunction,
         identifier: String,
+        returns_result: Option<bool>,
+        middleware: Option<Vec<String>>,
+        schema: Option<serde_json::Value>,
     ) -> Result<(), napi::Error> {
+        // handlers of endpoints which declare an error type return a result object
+        let returns_result = returns_result.unwrap_or(false);
+
+        if let Some(middleware) = middleware {
+            self.server.set_endpoint_middleware(&identifier, middleware);
+        }
+
+        // js handlers get their parameters untyped, the schema makes sure they match the .erpc definition
+        if let Some(schema) = schema {
+            let schema = serde_json::from_value::<EndpointSchema>(schema).map_err(|err| {
+                napi::Error::from_reason(format!("Invalid schema for {identifier}: {err}"))
+            })?;
+            self.server.set_endpoint_schema(&identifier, schema);
+        }
+
+        let tsf = create_js_caller(env, func)?;
+
+        self.server.register_raw_handler(
+            Box::new(move |input, context| {
+                let (sender, reciever) = oneshot::channel::<serde_json::Value>();
+                let r = tsf.call(
+                    (
+                        input.parameters.into_arguments(),
+                        serde_json::to_value(context).ok(),
+                        sender,
+                    ),
+                    crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+                );
+
+                Box::pin(async move {
+                    match r {
+                        napi::Status::Ok => {}
+                        _ => {
+                            return erpc::protocol::Response {
+                                body: Err(protocol::error::SendableError::from(format!(
+                                    "Threadsafe function status not ok: {r}"
+                                ))),
+                            }
+                        }
+                    };
+
+                    let v = match reciever.await {
+                        Ok(v) => v,
+                        Err(err) => {
+                            // the sender is dropped without a response when the js handler throws
+                            error!("Could not receive response: {err}");
+                            return erpc::protocol::Response {
+                                body: Err(protocol::error::SendableError::HandlerPanicked),
+                            };
+                        }
+                    };
+
+                    if returns_result {
+                        erpc::protocol::Response::from_result_object(v)
+                    } else {
+                        erpc::protocol::Response { body: Ok(v) }
+                    }
+                })
+            }),
+            identifier,
+        );
+        Ok(())
+    }
+
+    /**
+       Registers the handler of a streaming endpoint. The handler returns an async iterable, e.g. by being an async generator.
+       The next item is only requested from the iterator when the caller consumes the items.
+    */
+    #[napi(skip_typescript, js_name = "registerERPCStreamHandler")]
+    pub fn register_erpc_stream_handler(
+        &self,
+        env: Env,
+        func: JsFunction,
+        identifier: String,
+        middleware: Option<Vec<String>>,
+        schema: Option<serde_json::Value>,
+    ) -> Result<(), napi::Error> {
+        if let Some(middleware) = middleware {
+            self.server.set_endpoint_middleware(&identifier, middleware);
+        }
+
+        if let Some(schema) = schema {
+            let schema = serde_json::from_value::<EndpointSchema>(schema).map_err(|err| {
+                napi::Error::from_reason(format!("Invalid schema for {identifier}: {err}"))
+            })?;
+            self.server.set_endpoint_schema(&identifier, schema);
+        }
+
+        // calls the handler and sends back a caller for the next method of the iterator it returned
         let tsf = crate::threadsafe_function::ThreadsafeFunction::create(
             env.raw(),
             unsafe { func.raw() },
             0,
             |ctx: crate::threadsafe_function::ThreadSafeCallContext<(
                 Vec<serde_json::Value>,
-                oneshot::Sender<serde_json::Value>,
+                serde_json::Value,
+                oneshot::Sender<JsCaller>,
             )>| {
                 let args = ctx
                     .value
@@ -68,86 +370,110 @@ impl ERPCServer {
                     .iter()
                     .map(|v| ctx.env.to_js_value(v))
                     .collect::<Result<Vec<JsUnknown>, napi::Error>>()?;
+                let this = ctx.env.to_js_value(&ctx.value.1)?.coerce_to_object()?;
 
-                let response = ctx.callback.call(None, args.as_slice())?;
-                let response_channel = ctx.value.1;
-
-                if !response.is_promise()? {
-                    let response: serde_json::Value = ctx.env.from_js_value(response)?;
-                    ctx.env
-                        .execute_tokio_future(
-                            async move {
-                                match response_channel.send(serde_json::to_value(&response)?) {
-                                    Ok(_) => {}
-                                    Err(err) => {
-                                        return Err(napi::Error::from_reason(format!(
-                                            "Could not send response: {err}"
-                                        )))
-                                    }
-                                };
-                                Ok(())
-                            },
-                            |_, _| Ok(()),
-                        )
-                        .unwrap();
-                } else {
-                    unsafe {
-                        let prm: Promise<serde_json::Value> =
-                            Promise::from_napi_value(ctx.env.raw(), response.raw())?;
-                        ctx.env.execute_tokio_future(
-                            async move {
-                                let result = prm.await?;
-                                match response_channel.send(serde_json::to_value(result)?) {
-                                    Ok(_) => {}
-                                    Err(err) => {
-                                        return Err(napi::Error::from_reason(format!(
-                                            "Could not send response: {err}"
-                                        )))
-                                    }
-                                };
-                                Ok(())
-                            },
-                            |_, _| Ok(()),
-                        )?;
-                    }
-                };
+                let iterable = ctx.callback.call(Some(&this), args.as_slice())?;
+                let next = async_iterator_next(&ctx.env, iterable)?;
 
+                // the receiver is gone if the stream was cancelled in the meantime
+                let _ = ctx.value.2.send(create_js_caller(ctx.env, next)?);
                 Ok(())
             },
         )?;
 
-        self.server.register_raw_handler(
-            Box::new(move |input| {
-                let (sender, reciever) = oneshot::channel::<serde_json::Value>();
+        self.server.register_raw_stream_handler(
+            Box::new(move |input, context| {
+                let (sender, reciever) = oneshot::channel::<JsCaller>();
                 let r = tsf.call(
-                    (input.parameters, sender),
+                    (
+                        input.parameters.into_arguments(),
+                        serde_json::to_value(context).unwrap_or_default(),
+                        sender,
+                    ),
                     crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
                 );
 
-                Box::pin(async move {
+                let next = async move {
                     match r {
                         napi::Status::Ok => {}
                         _ => {
-                            return erpc::protocol::Response {
-                                body: Err(protocol::error::SendableError::from(format!(
-                                    "Threadsafe function status not ok: {r}"
-                                ))),
-                            }
+                            return Err(protocol::error::SendableError::from(format!(
+                                "Threadsafe function status not ok: {r}"
+                            )))
+                        }
+                    };
+
+                    reciever.await.map_err(|err| {
+                        // the sender is dropped without a caller when the js handler throws
+                        error!("Could not receive stream: {err}");
+                        protocol::error::SendableError::HandlerPanicked
+                    })
+                };
+
+                stream::once(next)
+                    .map(|next| match next {
+                        Ok(next) => stream::unfold(Some(next), next_item).boxed(),
+                        Err(err) => stream::once(async move { Err(err) }).boxed(),
+                    })
+                    .flatten()
+                    .boxed()
+            }),
+            identifier,
+        );
+        Ok(())
+    }
+
+    /**
+       Registers a js function as middleware. It is called with the MiddlewareCall and can return a changed call.
+       Like handlers it receives the ERPCRequestContext of the call as this.
+       Returning nothing passes the call on unchanged, throwing rejects the call as unauthorized.
+    */
+    #[napi(skip_typescript, js_name = "registerERPCMiddleware")]
+    pub fn register_erpc_middleware(
+        &self,
+        env: Env,
+        func: JsFunction,
+        identifier: String,
+    ) -> Result<(), napi::Error> {
+        let tsf = create_js_caller(env, func)?;
+
+        self.server.register_raw_middleware(
+            Box::new(move |call| {
+                let (sender, reciever) = oneshot::channel::<serde_json::Value>();
+                let status = serde_json::to_value(&call)
+                    .and_then(|v| Ok((v, serde_json::to_value(&call.caller)?)))
+                    .map(|(v, context)| {
+                        tsf.call(
+                            (vec![v], Some(context), sender),
+                            crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+                        )
+                    })
+                    .map_err(protocol::SendableError::from);
+
+                Box::pin(async move {
+                    match status? {
+                        napi::Status::Ok => {}
+                        r => {
+                            return Err(protocol::SendableError::from(format!(
+                                "Threadsafe function status not ok: {r}"
+                            )))
                         }
                     };
 
                     let v = match reciever.await {
                         Ok(v) => v,
                         Err(err) => {
-                            return erpc::protocol::Response {
-                                body: Err(protocol::error::SendableError::from(format!(
-                                    "Could not receive response: {err}"
-                                ))),
-                            }
+                            // the sender is dropped without a response when the js middleware throws
+                            error!("Middleware rejected call: {err}");
+                            return Err(protocol::SendableError::Unauthorized);
                         }
                     };
 
-                    erpc::protocol::Response { body: Ok(v) }
+                    match v {
+                        serde_json::Value::Null => Ok(call),
+                        v => serde_json::from_value::<MiddlewareCall>(v)
+                            .map_err(protocol::SendableError::from),
+                    }
                 })
             }),
             identifier,
@@ -155,6 +481,48 @@ impl ERPCServer {
         Ok(())
     }
 
+    /**
+       Sets the authenticator which is called with the ERPCRequestContext of every http request and websocket connection.
+       What it returns or resolves to is available to handlers as this.auth, throwing rejects the caller as unauthorized.
+    */
+    #[napi(
+        js_name = "setAuthenticator",
+        ts_args_type = "authenticator: (context: ERPCRequestContext) => unknown"
+    )]
+    pub fn set_authenticator(&self, env: Env, func: JsFunction) -> Result<(), napi::Error> {
+        let tsf = create_js_caller(env, func)?;
+
+        self.server.set_raw_authenticator(Box::new(move |context| {
+            let (sender, reciever) = oneshot::channel::<serde_json::Value>();
+            let status = serde_json::to_value(&context)
+                .map(|v| {
+                    tsf.call(
+                        (vec![v], None, sender),
+                        crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+                    )
+                })
+                .map_err(protocol::SendableError::from);
+
+            Box::pin(async move {
+                match status? {
+                    napi::Status::Ok => {}
+                    r => {
+                        return Err(protocol::SendableError::from(format!(
+                            "Threadsafe function status not ok: {r}"
+                        )))
+                    }
+                };
+
+                // the sender is dropped without a value when the js authenticator throws
+                reciever.await.map_err(|err| {
+                    error!("Authenticator rejected caller: {err}");
+                    protocol::SendableError::Unauthorized
+                })
+            })
+        }));
+        Ok(())
+    }
+
     #[napi(skip_typescript)]
     pub fn on_socket_connection(&mut self, env: Env, func: JsFunction) -> Result<(), napi::Error> {
         let tsf = crate::threadsafe_function::ThreadsafeFunction::create(
@@ -207,6 +575,170 @@ impl ERPCServer {
         Ok(())
     }
 
+    /**
+       Calls the callback whenever a websocket connection ended.
+       Calls which still wait for a response of the socket fail with a ConnectionClosed error.
+    */
+    #[napi(
+        js_name = "onSocketDisconnection",
+        ts_args_type = "callback: (disconnect: ERPCSocketDisconnect) => void"
+    )]
+    pub fn on_socket_disconnection(&self, env: Env, func: JsFunction) -> Result<(), napi::Error> {
+        let tsf = crate::threadsafe_function::ThreadsafeFunction::create(
+            env.raw(),
+            unsafe { func.raw() },
+            0,
+            |ctx: crate::threadsafe_function::ThreadSafeCallContext<serde_json::Value>| {
+                let disconnect = ctx.env.to_js_value(&ctx.value)?;
+                ctx.callback.call(None, &[disconnect])?;
+                Ok(())
+            },
+        )?;
+
+        self.server.on_disconnect(move |socket, reason| {
+            let (reason, message) = match reason {
+                DisconnectReason::Closed => ("closed", None),
+                DisconnectReason::ConnectionLost(v) => ("connectionLost", Some(v)),
+                DisconnectReason::InvalidMessage(v) => ("invalidMessage", Some(v)),
+                DisconnectReason::HeartbeatTimeout => ("heartbeatTimeout", None),
+                DisconnectReason::IdleTimeout => ("idleTimeout", None),
+                DisconnectReason::HandshakeRejected(v) => ("handshakeRejected", Some(v)),
+            };
+
+            let r = tsf.call(
+                serde_json::json!({
+                    "id": socket.id,
+                    "role": socket.role,
+                    "reason": reason,
+                    "message": message,
+                }),
+                crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+            );
+            match r {
+                napi::Status::Ok => {}
+                _ => error!("Threadsafe function status not ok: {r}"),
+            }
+        });
+        Ok(())
+    }
+
+    /**
+       Calls the callback whenever a client which was generated from other .erpc sources calls the server or connects to it
+    */
+    #[napi(
+        js_name = "onSchemaMismatch",
+        ts_args_type = "callback: (mismatch: ERPCSchemaMismatch) => void"
+    )]
+    pub fn on_schema_mismatch(&self, env: Env, func: JsFunction) -> Result<(), napi::Error> {
+        let tsf = crate::threadsafe_function::ThreadsafeFunction::create(
+            env.raw(),
+            unsafe { func.raw() },
+            0,
+            |ctx: crate::threadsafe_function::ThreadSafeCallContext<serde_json::Value>| {
+                let mismatch = ctx.env.to_js_value(&ctx.value)?;
+                ctx.callback.call(None, &[mismatch])?;
+                Ok(())
+            },
+        )?;
+
+        self.server.on_schema_mismatch(move |mismatch| {
+            let r = tsf.call(
+                serde_json::json!({
+                    "expected": mismatch.expected,
+                    "received": mismatch.received,
+                    "context": mismatch.context,
+                }),
+                crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+            );
+            match r {
+                napi::Status::Ok => {}
+                _ => error!("Threadsafe function status not ok: {r}"),
+            }
+        });
+        Ok(())
+    }
+
+    /**
+       The browsers which are connected right now, only those of the role if one is given
+    */
+    #[napi]
+    pub fn connected_sockets(&self, role: Option<String>) -> Vec<ERPCSocketInfo> {
+        self.server
+            .connected_sockets(role.as_deref())
+            .into_iter()
+            .map(|v| ERPCSocketInfo {
+                id: v.id,
+                role: v.role,
+            })
+            .collect()
+    }
+
+    /**
+       The connected browser with the socket id, undefined if it is not connected (anymore)
+    */
+    #[napi]
+    pub fn connected_socket(&self, id: String) -> Option<ERPCSocketInfo> {
+        self.server.connected_socket(&id).map(|v| ERPCSocketInfo {
+            id: v.id,
+            role: v.role,
+        })
+    }
+
+    /**
+       The queue depths of all connected browsers
+    */
+    #[napi]
+    pub fn socket_metrics(&self) -> Vec<ERPCSocketMetrics> {
+        self.server
+            .socket_metrics()
+            .into_iter()
+            .map(|v| ERPCSocketMetrics {
+                id: v.id,
+                role: v.role,
+                outgoing_queue: v.outgoing_queue as u32,
+                incoming_queue: v.incoming_queue as u32,
+                in_flight_requests: v.in_flight_requests as u32,
+            })
+            .collect()
+    }
+
+    /**
+      Calls the endpoint a request of another http framework is for, e.g. from an express or fastify route.
+      Paths which are not below the handler route are answered with 404.
+    */
+    #[napi]
+    pub async fn handle_request(&self, request: ERPCHttpRequest) -> ERPCHttpResponse {
+        let content_type = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone());
+        let context = http_server::RequestContext {
+            headers: request
+                .headers
+                .into_iter()
+                .map(|(name, value)| (name.to_lowercase(), value))
+                .collect(),
+            remote_address: request.remote_address,
+            ..http_server::RequestContext::default()
+        };
+
+        let response = match self.server.identifier_from_path(&request.path) {
+            Some(identifier) => {
+                self.server
+                    .dispatch(identifier, content_type.as_deref(), &request.body, context)
+                    .await
+            }
+            None => protocol::SendableError::NotFound.into(),
+        };
+
+        ERPCHttpResponse {
+            status: response.status,
+            content_type: response.content_type.to_string(),
+            body: response.body.into(),
+        }
+    }
+
     /**
       Starts the server as configured
     */
@@ -215,6 +747,22 @@ impl ERPCServer {
         self.server.run().await.await;
     }
 
+    /**
+      Resolves to the address the server listens on once it runs, e.g. 127.0.0.1:8080 for port 0
+    */
+    #[napi]
+    pub async fn listening(&self) -> String {
+        self.server.listening().await
+    }
+
+    /**
+      The address the server listens on, undefined if it is not running
+    */
+    #[napi(getter)]
+    pub fn local_address(&self) -> Option<String> {
+        self.server.local_address()
+    }
+
     /**
      * Stops the server
      */
@@ -228,3 +776,152 @@ impl ERPCServer {
         }
     }
 }
+
+/**
+   Calls a js function with the parameters and optionally a value which is passed as this
+*/
+type JsCaller = crate::threadsafe_function::ThreadsafeFunction<(
+    Vec<serde_json::Value>,
+    Option<serde_json::Value>,
+    oneshot::Sender<serde_json::Value>,
+)>;
+
+/**
+   Gets the next method of the async iterator of an async iterable, bound to the iterator.
+   Objects without Symbol.asyncIterator are used as iterator directly.
+*/
+fn async_iterator_next(env: &Env, iterable: JsUnknown) -> Result<JsFunction, napi::Error> {
+    let iterable = iterable.coerce_to_object()?;
+    let symbol: JsObject = env.get_global()?.get_named_property("Symbol")?;
+    let async_iterator: JsUnknown = symbol.get_named_property("asyncIterator")?;
+
+    let get_iterator: JsUnknown = iterable.get_property(async_iterator)?;
+    let iterator = if get_iterator.get_type()? == ValueType::Function {
+        unsafe { get_iterator.cast::<JsFunction>() }
+            .call_without_args(Some(&iterable))?
+            .coerce_to_object()?
+    } else {
+        iterable
+    };
+
+    let next: JsObject = iterator.get_named_property("next")?;
+    let bind: JsFunction = next.get_named_property("bind")?;
+    let bound = bind.call(Some(&next), &[iterator])?;
+    Ok(unsafe { bound.cast::<JsFunction>() })
+}
+
+/**
+   Awaits the next item of the iterator behind the caller, the stream ends when the iterator is done or failed
+*/
+async fn next_item(
+    next: Option<JsCaller>,
+) -> Option<(
+    Result<serde_json::Value, protocol::SendableError>,
+    Option<JsCaller>,
+)> {
+    let next = next?;
+
+    let (sender, reciever) = oneshot::channel::<serde_json::Value>();
+    let r = next.call(
+        (vec![], None, sender),
+        crate::threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+    );
+    match r {
+        napi::Status::Ok => {}
+        _ => {
+            return Some((
+                Err(protocol::SendableError::from(format!(
+                    "Threadsafe function status not ok: {r}"
+                ))),
+                None,
+            ))
+        }
+    };
+
+    let result = match reciever.await {
+        Ok(v) => v,
+        Err(err) => {
+            // the sender is dropped without a result when the iterator throws
+            error!("Stream handler failed: {err}");
+            return Some((Err(protocol::SendableError::HandlerPanicked), None));
+        }
+    };
+
+    if result["done"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    Some((Ok(result["value"].clone()), Some(next)))
+}
+
+/**
+   Creates a threadsafe function which calls the js function with the parameters and sends back what it returns or resolves to.
+   The sender is dropped without a value if the js function throws.
+*/
+fn create_js_caller(env: Env, func: JsFunction) -> Result<JsCaller, napi::Error> {
+    crate::threadsafe_function::ThreadsafeFunction::create(
+        env.raw(),
+        unsafe { func.raw() },
+        0,
+        |ctx: crate::threadsafe_function::ThreadSafeCallContext<(
+            Vec<serde_json::Value>,
+            Option<serde_json::Value>,
+            oneshot::Sender<serde_json::Value>,
+        )>| {
+            let args = ctx
+                .value
+                .0
+                .iter()
+                .map(|v| ctx.env.to_js_value(v))
+                .collect::<Result<Vec<JsUnknown>, napi::Error>>()?;
+            let this = match &ctx.value.1 {
+                Some(v) => Some(ctx.env.to_js_value(v)?.coerce_to_object()?),
+                None => None,
+            };
+
+            let response = ctx.callback.call(this.as_ref(), args.as_slice())?;
+            let response_channel = ctx.value.2;
+
+            if !response.is_promise()? {
+                let response: serde_json::Value = ctx.env.from_js_value(response)?;
+                ctx.env
+                    .execute_tokio_future(
+                        async move {
+                            match response_channel.send(serde_json::to_value(&response)?) {
+                                Ok(_) => {}
+                                Err(err) => {
+                                    return Err(napi::Error::from_reason(format!(
+                                        "Could not send response: {err}"
+                                    )))
+                                }
+                            };
+                            Ok(())
+                        },
+                        |_, _| Ok(()),
+                    )
+                    .unwrap();
+            } else {
+                unsafe {
+                    let prm: Promise<serde_json::Value> =
+                        Promise::from_napi_value(ctx.env.raw(), response.raw())?;
+                    ctx.env.execute_tokio_future(
+                        async move {
+                            let result = prm.await?;
+                            match response_channel.send(serde_json::to_value(result)?) {
+                                Ok(_) => {}
+                                Err(err) => {
+                                    return Err(napi::Error::from_reason(format!(
+                                        "Could not send response: {err}"
+                                    )))
+                                }
+                            };
+                            Ok(())
+                        },
+                        |_, _| Ok(()),
+                    )?;
+                }
+            };
+
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use erpc::stream::StreamReceiver;
+use futures::StreamExt;
+use napi::{Env, JsObject, JsUnknown};
+use tokio::sync::Mutex;
+
+use crate::target::sendable_error_to_js;
+
+/**
+   The items of a streaming endpoint, it implements the async iterator protocol
+*/
+#[napi(js_name = "ERPCStream")]
+pub struct ERPCStream {
+    // None once the stream ended or was returned early
+    items: Arc<Mutex<Option<StreamReceiver>>>,
+}
+
+impl ERPCStream {
+    pub fn new(items: StreamReceiver) -> Self {
+        Self {
+            items: Arc::new(Mutex::new(Some(items))),
+        }
+    }
+}
+
+#[napi]
+impl ERPCStream {
+    /**
+       Resolves to the next item of the stream, rejects with an ERPCError if the stream failed
+    */
+    #[napi(ts_return_type = "Promise<IteratorResult<any>>")]
+    pub fn next(&self, env: Env) -> Result<JsObject, napi::Error> {
+        let items = self.items.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let mut items = items.lock().await;
+                let item = match items.as_mut() {
+                    Some(v) => v.next().await,
+                    None => None,
+                };
+
+                if !matches!(item, Some(Ok(_))) {
+                    items.take();
+                }
+                Ok(item)
+            },
+            |env, item| match item {
+                Some(Ok(v)) => iterator_result(env, env.to_js_value(&v)?, false),
+                Some(Err(err)) => Err(sendable_error_to_js(env, err)?),
+                None => iterator_result(env, env.get_undefined()?.into_unknown(), true),
+            },
+        )
+    }
+
+    /**
+       Stops the stream, the endpoint on the other side is cancelled if it still produces items
+    */
+    #[napi(js_name = "return", ts_return_type = "Promise<IteratorResult<any>>")]
+    pub fn return_(&self, env: Env) -> Result<JsObject, napi::Error> {
+        let items = self.items.clone();
+
+        env.execute_tokio_future(
+            async move {
+                // dropping the receiver cancels the stream
+                items.lock().await.take();
+                Ok(())
+            },
+            |env, _| iterator_result(env, env.get_undefined()?.into_unknown(), true),
+        )
+    }
+}
+
+fn iterator_result(env: &Env, value: JsUnknown, done: bool) -> Result<JsObject, napi::Error> {
+    let mut result = env.create_object()?;
+    result.set_named_property("value", value)?;
+    result.set_named_property("done", env.get_boolean(done)?)?;
+    Ok(result)
+}
@@ -1,30 +1,90 @@
-use erpc::target::TargetType;
+use std::time::Duration;
+
+use erpc::{
+    codec::Codec,
+    protocol::{self, SendableError},
+    target::TargetType,
+};
 use http_server::Socket;
 use napi::{Env, JsObject, JsUnknown};
 
+use crate::{server::ERPCServer, stream::ERPCStream};
+
+/**
+   The shape of errors thrown by call, the type field names the SendableError variant
+*/
+#[napi(object, js_name = "ERPCError")]
+pub struct ERPCError {
+    #[napi(
+        js_name = "type",
+        ts_type = "\"NotFound\" | \"Internal\" | \"BadRequest\" | \"Timeout\" | \"Unauthorized\" | \"ConnectionClosed\" | \"HandlerPanicked\" | \"Overloaded\" | \"Application\""
+    )]
+    pub error_type: String,
+    pub message: String,
+    pub path: Option<String>,
+}
+
 #[napi(object)]
 pub struct TargetOptions {
     pub address: String,
+    /**
+       Milliseconds after which calls fail with a Timeout error, calls wait forever if not set
+    */
+    pub timeout: Option<u32>,
+    /**
+       The format of http requests to the target, json if not set
+    */
+    #[napi(ts_type = "\"json\" | \"messagePack\"")]
+    pub codec: Option<String>,
 }
 
 #[napi(js_name = "ERPCTarget")]
 pub struct ERPCTarget {
     target: http_server::Target,
+    /**
+       Set if calls of this target go to all connected browsers of a role instead
+    */
+    broadcast: Option<Broadcast>,
+}
+
+#[derive(Clone)]
+struct Broadcast {
+    server: http_server::Server,
+    role: String,
+    /**
+       Only these sockets are called if set
+    */
+    socket_ids: Option<Vec<String>>,
 }
 
 #[napi]
 impl ERPCTarget {
     #[napi(constructor)]
-    pub fn new(options: TargetOptions, target_type: String) -> Self {
+    pub fn new(
+        options: TargetOptions,
+        target_type: String,
+        schema_hash: Option<String>,
+    ) -> Result<Self, napi::Error> {
         let target_type = match target_type.as_str() {
             "browser" => TargetType::Browser,
             "http-server" => TargetType::HttpServer,
             _ => panic!("Unsupported target type {}", target_type),
         };
 
-        ERPCTarget {
-            target: http_server::Target::new(options.address, target_type),
+        let target = http_server::Target::new(options.address, target_type);
+        target.set_timeout(options.timeout.map(|v| Duration::from_millis(v.into())));
+        target.set_schema_hash(schema_hash);
+
+        if let Some(codec) = options.codec {
+            let codec = serde_json::from_value::<Codec>(serde_json::Value::String(codec))
+                .map_err(|err| napi::Error::from_reason(format!("Invalid codec: {err}")))?;
+            target.set_codec(codec);
         }
+
+        Ok(ERPCTarget {
+            target,
+            broadcast: None,
+        })
     }
 
     #[napi(skip_typescript)]
@@ -32,8 +92,48 @@ impl ERPCTarget {
         &self,
         env: Env,
         method_identifier: String,
-        parameters: Option<Vec<serde_json::Value>>,
+        parameters: Option<serde_json::Value>,
+        returns_result: Option<bool>,
     ) -> Result<JsObject, napi::Error> {
+        let parameters = parse_parameters(parameters)?;
+        if let Some(broadcast) = self.broadcast.clone() {
+            let timeout = self.target.timeout();
+            return env.execute_tokio_future(
+                async move {
+                    let responses = broadcast
+                        .server
+                        .broadcast(
+                            &broadcast.role,
+                            erpc::protocol::Request {
+                                identifier: method_identifier,
+                                parameters,
+                            },
+                            |context| match (&broadcast.socket_ids, &context.socket_id) {
+                                (Some(ids), Some(id)) => ids.contains(id),
+                                (Some(_), None) => false,
+                                (None, _) => true,
+                            },
+                            timeout,
+                        )
+                        .await;
+
+                    Ok(responses
+                        .into_iter()
+                        .map(|(socket_id, response)| {
+                            (
+                                socket_id,
+                                broadcast_result(response, returns_result.unwrap_or(false)),
+                            )
+                        })
+                        .collect::<serde_json::Map<_, _>>())
+                },
+                |env, data| {
+                    let ret: JsUnknown = env.to_js_value(&data)?;
+                    Ok(ret)
+                },
+            );
+        }
+
         let t = self.target.clone();
 
         env.execute_tokio_future(
@@ -41,26 +141,81 @@ impl ERPCTarget {
                 let v = t
                     .call(erpc::protocol::Request {
                         identifier: method_identifier,
-                        parameters: parameters.unwrap_or_default(),
+                        parameters,
                     })
                     .await;
 
-                let res = match v.body {
-                    Ok(v) => v,
-                    Err(err) => {
-                        return Err(napi::Error::from_reason(err.to_string()));
-                    }
-                };
-
-                Ok(res)
+                // endpoints which declare an error type resolve to a result object
+                if returns_result.unwrap_or(false) {
+                    Ok(v.into_result_object())
+                } else {
+                    Ok(v.body)
+                }
             },
-            |env, data| {
-                let ret: JsUnknown = env.to_js_value(&data)?;
-                Ok(ret)
+            |env, data| match data {
+                Ok(v) => {
+                    let ret: JsUnknown = env.to_js_value(&v)?;
+                    Ok(ret)
+                }
+                Err(err) => Err(sendable_error_to_js(env, err)?),
             },
         )
     }
 
+    /**
+       Calls a streaming endpoint, the items are requested over the websocket connection to the target
+    */
+    #[napi(skip_typescript)]
+    pub fn stream(
+        &self,
+        env: Env,
+        method_identifier: String,
+        parameters: Option<serde_json::Value>,
+    ) -> Result<ERPCStream, napi::Error> {
+        match self.target.call_stream(erpc::protocol::Request {
+            identifier: method_identifier,
+            parameters: parse_parameters(parameters)?,
+        }) {
+            Ok(v) => Ok(ERPCStream::new(v)),
+            Err(err) => Err(sendable_error_to_js(&env, err)?),
+        }
+    }
+
+    /**
+       The id of the socket the target calls, it is set for targets which onConnection created
+    */
+    #[napi(getter)]
+    pub fn socket_id(&self) -> Option<String> {
+        self.target.socket_id()
+    }
+
+    /**
+       Calls the handlers of a server in the same process from now on instead of sending http requests, e.g. for server side rendering.
+       Calls still pass the authenticator, schema validation and middleware of the server.
+    */
+    #[napi(js_name = "setLoopback")]
+    pub fn set_loopback(&mut self, server: &ERPCServer) {
+        self.target.set_loopback(&server.server);
+    }
+
+    /**
+       Calls all connected browsers of the role from now on, or only the sockets with the ids if given.
+       Calls resolve to an object with the result of each socket by its id.
+    */
+    #[napi(skip_typescript, js_name = "setERPCBroadcast")]
+    pub fn set_erpc_broadcast(
+        &mut self,
+        server: &ERPCServer,
+        role: String,
+        socket_ids: Option<Vec<String>>,
+    ) {
+        self.broadcast = Some(Broadcast {
+            server: server.server.clone(),
+            role,
+            socket_ids,
+        });
+    }
+
     #[napi(skip_typescript, js_name = "setERPCSocket")]
     pub fn set_erpc_socket(&self, env: Env, socket: JsObject) -> Result<(), napi::Error> {
         let mut t = self.target.clone();
@@ -76,3 +231,63 @@ impl ERPCTarget {
         Ok(())
     }
 }
+
+/**
+   Reads the parameters js passed to a call, a list or an object for endpoints with named parameters
+*/
+fn parse_parameters(
+    parameters: Option<serde_json::Value>,
+) -> Result<protocol::Parameters, napi::Error> {
+    match parameters {
+        None | Some(serde_json::Value::Null) => Ok(protocol::Parameters::default()),
+        Some(v) => serde_json::from_value(v)
+            .map_err(|err| napi::Error::from_reason(format!("Invalid parameters: {err}"))),
+    }
+}
+
+/**
+   The result of a single socket of a broadcast, { ok: true, value } or { ok: false, error } with an ERPCError
+*/
+fn broadcast_result(response: protocol::Response, returns_result: bool) -> serde_json::Value {
+    let body = if returns_result {
+        response.into_result_object()
+    } else {
+        response.body
+    };
+
+    match body {
+        Ok(value) => serde_json::json!({ "ok": true, "value": value }),
+        Err(err) => {
+            let mut error = serde_json::to_value(&err).unwrap_or_default();
+            if let serde_json::Value::Object(v) = &mut error {
+                // the message is the safe message of the whole error like for thrown errors
+                v.insert("message".to_string(), err.message().into());
+            }
+            serde_json::json!({ "ok": false, "error": error })
+        }
+    }
+}
+
+/**
+   Turns a SendableError into a js Error which carries the fields of the ERPCError interface
+*/
+pub(crate) fn sendable_error_to_js(
+    env: &Env,
+    err: SendableError,
+) -> Result<napi::Error, napi::Error> {
+    let mut js_error = env.create_error(napi::Error::from_reason(err.message()))?;
+
+    let details = match serde_json::to_value(&err)? {
+        serde_json::Value::Object(v) => v,
+        _ => return Ok(napi::Error::from_reason(err.message())),
+    };
+    for (key, value) in details {
+        // the message of the js error is the safe message of the whole error
+        if key == "message" || value.is_null() {
+            continue;
+        }
+        js_error.set_named_property(&key, env.to_js_value(&value)?)?;
+    }
+
+    Ok(napi::Error::from(js_error.into_unknown()))
+}
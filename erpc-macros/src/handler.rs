@@ -0,0 +1,348 @@
+use std::{
+    env,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use erpc_transpiler::transpiler::{config::parse_config, find_rust_handler, HandlerSignature};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    FnArg, GenericArgument, Ident, ItemFn, LitStr, PathArguments, ReturnType, Token, Type,
+    TypeParamBound, Visibility,
+};
+
+/**
+   The arguments of the handler attribute, the identifier of the endpoint and optionally the path of the erpc.json
+*/
+pub struct HandlerArgs {
+    pub identifier: LitStr,
+    pub config: Option<LitStr>,
+}
+
+impl Parse for HandlerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let identifier = input.parse()?;
+        let mut config = None;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key != "config" {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "Expected config = \"path/to/erpc.json\"",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            config = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(Self { identifier, config })
+    }
+}
+
+pub fn expand(args: HandlerArgs, item: ItemFn) -> syn::Result<TokenStream> {
+    let manifest_dir = match env::var("CARGO_MANIFEST_DIR") {
+        Ok(v) => PathBuf::from(v),
+        Err(err) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Could not read CARGO_MANIFEST_DIR: {err}"),
+            ))
+        }
+    };
+    let config_path = match &args.config {
+        Some(v) => manifest_dir.join(v.value()),
+        None => manifest_dir.join("erpc.json"),
+    };
+
+    expand_with_config(&config_path, args, item)
+}
+
+/**
+   Checks the handler against the sources of the config and generates its registration
+*/
+pub(crate) fn expand_with_config(
+    config_path: &Path,
+    args: HandlerArgs,
+    item: ItemFn,
+) -> syn::Result<TokenStream> {
+    let identifier = args.identifier.value();
+    let (source, signature) = match find_handler(config_path, &identifier) {
+        Ok(v) => v,
+        Err(err) => return Err(syn::Error::new_spanned(&args.identifier, err)),
+    };
+
+    check_signature(&item, &signature, &identifier)?;
+
+    Ok(registration(&args.identifier, &source, &signature, item))
+}
+
+/**
+   Looks the endpoint up in all source directories of the config, the role of the config is the one which handles it
+*/
+pub(crate) fn find_handler(
+    config_path: &Path,
+    identifier: &str,
+) -> Result<(PathBuf, HandlerSignature), String> {
+    let config = match File::open(config_path) {
+        Ok(v) => match parse_config(v) {
+            Ok(v) => v,
+            Err(err) => {
+                return Err(format!(
+                    "Could not parse {}: {err}",
+                    config_path.to_str().unwrap_or("<could not unwrap path>")
+                ))
+            }
+        },
+        Err(err) => {
+            return Err(format!(
+                "Could not open {}: {err}",
+                config_path.to_str().unwrap_or("<could not unwrap path>")
+            ))
+        }
+    };
+
+    // the sources are relative to the erpc.json
+    let config_directory = config_path.parent().unwrap_or(Path::new(""));
+
+    let mut errors = vec![];
+    for source in &config.sources {
+        match find_rust_handler(&config_directory.join(source), &config.role, identifier) {
+            Ok(v) => return Ok(v),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+
+    if errors.is_empty() {
+        return Err(format!(
+            "No sources are configured in {}",
+            config_path.to_str().unwrap_or("<could not unwrap path>")
+        ));
+    }
+    Err(errors.join("\n"))
+}
+
+/**
+   Compares the handler with the signature the .erpc source declares, errors point at the mismatching part of the handler
+*/
+pub(crate) fn check_signature(
+    item: &ItemFn,
+    signature: &HandlerSignature,
+    identifier: &str,
+) -> syn::Result<()> {
+    if item.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            item.sig.fn_token,
+            "Handlers have to be async",
+        ));
+    }
+    if !item.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.sig.generics,
+            "Handlers can't be generic",
+        ));
+    }
+
+    let mut parameters = vec![];
+    for input in &item.sig.inputs {
+        match input {
+            FnArg::Typed(v) => parameters.push(&*v.ty),
+            FnArg::Receiver(v) => {
+                return Err(syn::Error::new_spanned(v, "Handlers can't take self"))
+            }
+        }
+    }
+
+    if parameters.len() != signature.parameters.len() {
+        return Err(syn::Error::new_spanned(
+            &item.sig,
+            format!(
+                "{identifier} takes {} parameters ({}), the handler takes {}",
+                signature.parameters.len(),
+                signature.parameters.join(", "),
+                parameters.len()
+            ),
+        ));
+    }
+
+    for (parameter, expected) in parameters.into_iter().zip(&signature.parameters) {
+        check_type(parameter, expected, identifier)?;
+    }
+
+    let output = match &item.sig.output {
+        ReturnType::Type(_, v) => Some(&**v),
+        ReturnType::Default => None,
+    };
+
+    if signature.streaming {
+        return match output.and_then(stream_item) {
+            Some(v) => check_type(v, &signature.output, identifier),
+            None => Err(syn::Error::new_spanned(
+                &item.sig,
+                format!(
+                    "{identifier} is a stream, the handler has to return impl Stream<Item = {}>",
+                    signature.output
+                ),
+            )),
+        };
+    }
+
+    match output {
+        Some(v) => check_type(v, &signature.output, identifier),
+        None if signature.output == "()" => Ok(()),
+        None => Err(syn::Error::new_spanned(
+            &item.sig,
+            format!(
+                "{identifier} returns {}, the handler returns nothing",
+                signature.output
+            ),
+        )),
+    }
+}
+
+fn check_type(actual: &Type, expected: &str, identifier: &str) -> syn::Result<()> {
+    if type_name(actual) == expected.replace(' ', "") {
+        return Ok(());
+    }
+
+    Err(syn::Error::new_spanned(
+        actual,
+        format!(
+            "{identifier} expects {expected} here, found {}",
+            actual.to_token_stream()
+        ),
+    ))
+}
+
+/**
+   Stringifies a type like the transpiler does. Paths are shortened to their last segment,
+   since the generated types can be imported from anywhere.
+*/
+pub(crate) fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(v) if v.qself.is_none() => match v.path.segments.last() {
+            Some(segment) => {
+                let mut name = segment.ident.unraw().to_string();
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    let arguments = arguments
+                        .args
+                        .iter()
+                        .map(|argument| match argument {
+                            GenericArgument::Type(v) => type_name(v),
+                            argument => compact(argument),
+                        })
+                        .collect::<Vec<_>>();
+                    name.push_str(&format!("<{}>", arguments.join(",")));
+                }
+                name
+            }
+            None => compact(ty),
+        },
+        Type::Paren(v) => type_name(&v.elem),
+        Type::Group(v) => type_name(&v.elem),
+        ty => compact(ty),
+    }
+}
+
+fn compact(tokens: &impl ToTokens) -> String {
+    tokens.to_token_stream().to_string().replace(' ', "")
+}
+
+/**
+   The item type of a handler which returns impl Stream<Item = T>
+*/
+fn stream_item(ty: &Type) -> Option<&Type> {
+    let bounds = match ty {
+        Type::ImplTrait(v) => &v.bounds,
+        _ => return None,
+    };
+
+    bounds.iter().find_map(|bound| {
+        let segment = match bound {
+            TypeParamBound::Trait(v) => v.path.segments.last()?,
+            _ => return None,
+        };
+        if segment.ident != "Stream" {
+            return None;
+        }
+
+        match &segment.arguments {
+            PathArguments::AngleBracketed(arguments) => {
+                arguments.args.iter().find_map(|argument| match argument {
+                    GenericArgument::AssocType(v) if v.ident == "Item" => Some(&v.ty),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+/**
+   Turns the fn into a unit struct of the same name which registers it on a server
+*/
+fn registration(
+    identifier: &LitStr,
+    source: &Path,
+    signature: &HandlerSignature,
+    mut item: ItemFn,
+) -> TokenStream {
+    let name = item.sig.ident.clone();
+    let vis = item.vis.clone();
+    let docs = item
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("doc"))
+        .cloned()
+        .collect::<Vec<_>>();
+    // the fn is moved into the register method, where it shadows the struct
+    item.vis = Visibility::Inherited;
+
+    let register = if signature.fallible {
+        quote!(register_fallible_handler)
+    } else if signature.streaming {
+        quote!(register_stream_handler)
+    } else {
+        quote!(register_handler)
+    };
+
+    let middleware = if signature.middleware.is_empty() {
+        TokenStream::new()
+    } else {
+        let middleware = &signature.middleware;
+        quote! {
+            server.set_endpoint_middleware(#identifier, vec![#(#middleware.to_string()),*]);
+        }
+    };
+
+    let schema = &signature.schema;
+    let source = source.to_string_lossy().to_string();
+
+    quote! {
+        #(#docs)*
+        #[allow(non_camel_case_types)]
+        #vis struct #name;
+
+        impl http_server::EndpointHandler for #name {
+            const IDENTIFIER: &'static str = #identifier;
+
+            fn register(self, server: &mut http_server::Server) {
+                // the handler is checked again whenever its .erpc source changes
+                const _: &[u8] = include_bytes!(#source);
+
+                #item
+
+                server.#register(#name, #identifier);
+                server.set_endpoint_schema(
+                    #identifier,
+                    http_server::serde_json::from_str(#schema).expect("the generated schema is valid"),
+                );
+                #middleware
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, ItemFn};
+
+use crate::handler::HandlerArgs;
+
+mod handler;
+mod tests;
+
+/**
+   Registers an async fn as handler of an endpoint, e.g. #[handler("api/ping")].
+   The identifier and the types of the parameters and the result are checked against the .erpc sources
+   of the erpc.json next to the Cargo.toml, another config can be given with config = "path/to/erpc.json".
+
+   The fn becomes a unit struct of the same name, which is registered with Server::register.
+*/
+#[proc_macro_attribute]
+pub fn handler(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as HandlerArgs);
+    let item = parse_macro_input!(item as ItemFn);
+
+    match handler::expand(args, item) {
+        Ok(v) => v.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,28 @@
+import { ERPCServer, ServerOptions } from "@easy-rpc/node"
+import middleware from "./Backend/middleware"
+
+export default class Backend extends ERPCServer {
+    private _middleware = undefined as any
+    set middleware(value: middleware) {
+        this._middleware = value;
+        (value as any).setERPCServer(this)
+    }
+    get middleware() {
+        return this._middleware
+    }
+    /**
+        @param options The options to set for the easy-rpc object
+        @param callbacks Callbacks to register for this server
+    */
+    constructor(options: ServerOptions, callbacks?: {
+        middleware: middleware
+    }) {
+        super(options, "http-server", false, "Backend", "1e6a3c80fa88ea32")
+        if (callbacks?.middleware) {
+            this.middleware = callbacks.middleware
+        } else {
+            this.middleware = new middleware()
+        }
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,95 @@
+
+export interface User {
+    name: string
+    age?: number
+}
+
+export interface RenameError {
+    reason: string
+}
+
+export default class users {
+    private server: any
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It can be used to set the server of the object.
+    */
+    private setERPCServer(server: any) {
+        this.server = server
+
+        // trigger the setters to set the handlers on the server object
+        if (this.find) {
+            this.find = this.find
+        }
+        if (this.rename) {
+            this.rename = this.rename
+        }
+        if (this.watch) {
+            this.watch = this.watch
+        }
+        if (this.clear) {
+            this.clear = this.clear
+        }
+    }
+
+    constructor(callbacks?: {
+        find: (name: string, limit?: number) => Promise<User[]>
+        rename: (user: User, name: string) => Promise<{ ok: true, value: "renamed" | "taken" } | { ok: false, error: RenameError }>
+        watch: (names: string[]) => AsyncIterable<User>
+        clear: () => Promise<void>
+    }) {
+        if (callbacks?.find) {
+            this.find = callbacks.find
+        }
+
+        if (callbacks?.rename) {
+            this.rename = callbacks.rename
+        }
+
+        if (callbacks?.watch) {
+            this.watch = callbacks.watch
+        }
+
+        if (callbacks?.clear) {
+            this.clear = callbacks.clear
+        }
+
+    }
+
+    private _find: (name: string, limit?: number) => Promise<User[]> = undefined as any
+    set find(value: (name: string, limit?: number) => Promise<User[]>) {
+        this._find = value
+        this.server?.registerERPCHandler(value, "api/users/find", false, ["auth"], {"parameters":[{"name":"name","optional":false,"schema":{"kind":"string"}},{"name":"limit","optional":true,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}}],"types":{}})
+    }
+    get find() {
+        return this._find
+    }
+
+    private _rename: (user: User, name: string) => Promise<{ ok: true, value: "renamed" | "taken" } | { ok: false, error: RenameError }> = undefined as any
+    set rename(value: (user: User, name: string) => Promise<{ ok: true, value: "renamed" | "taken" } | { ok: false, error: RenameError }>) {
+        this._rename = value
+        this.server?.registerERPCHandler(value, "api/users/rename", true, [], {"parameters":[{"name":"user","optional":false,"schema":{"kind":"custom","name":"User"}},{"name":"name","optional":false,"schema":{"kind":"string"}}],"types":{"User":[{"name":"name","optional":false,"schema":{"kind":"string"}},{"name":"age","optional":true,"schema":{"kind":"integer","max":32767,"min":-32768}}]}})
+    }
+    get rename() {
+        return this._rename
+    }
+
+    private _watch: (names: string[]) => AsyncIterable<User> = undefined as any
+    set watch(value: (names: string[]) => AsyncIterable<User>) {
+        this._watch = value
+        this.server?.registerERPCStreamHandler(value, "api/users/watch", [], {"parameters":[{"name":"names","optional":false,"schema":{"items":{"kind":"string"},"kind":"array"}}],"types":{}})
+    }
+    get watch() {
+        return this._watch
+    }
+
+    private _clear: () => Promise<void> = undefined as any
+    set clear(value: () => Promise<void>) {
+        this._clear = value
+        this.server?.registerERPCHandler(value, "api/users/clear", false, [], {"parameters":[],"types":{}})
+    }
+    get clear() {
+        return this._clear
+    }
+
+
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+
+export interface ERPCMiddlewareCall {
+    identifier: string
+    /** An object keyed by parameter name for endpoints with named parameters */
+    parameters: any[] | Record<string, any>
+    context: Record<string, any>
+    /** Who made the call, auth is the value the authenticator returned */
+    caller: {
+        headers: Record<string, string>
+        remoteAddress?: string
+        socketId?: string
+        role?: string
+        auth: any
+    }
+}
+
+export default class middleware {
+    private server: any
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It can be used to set the server of the object.
+    */
+    private setERPCServer(server: any) {
+        this.server = server
+
+        // trigger the setters to set the handlers on the server object
+        if (this.auth) {
+            this.auth = this.auth
+        }
+    }
+
+    constructor(callbacks?: {
+        auth: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>
+    }) {
+        if (callbacks?.auth) {
+            this.auth = callbacks.auth
+        }
+
+    }
+
+    private _auth: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void> = undefined as any
+    set auth(value: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>) {
+        this._auth = value
+        this.server?.registerERPCMiddleware(value, "auth")
+    }
+    get auth() {
+        return this._auth
+    }
+
+
+}
\ No newline at end of file
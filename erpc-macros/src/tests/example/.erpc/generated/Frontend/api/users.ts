@@ -0,0 +1,23 @@
+
+export interface User {
+    name: string
+    age?: number
+}
+
+export interface RenameError {
+    reason: string
+}
+
+export default class users {
+
+    private server: any
+
+    constructor(server: any) {
+        this.server = server
+    }
+
+    notify(message: string): Promise<void> {
+        return this.server.call("api/users/notify", [message])
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use syn::{parse_quote, ItemFn, Type};
+
+    use crate::handler::{
+        check_signature, expand_with_config, find_handler, type_name, HandlerArgs,
+    };
+
+    fn config() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("tests")
+            .join("example")
+            .join("erpc.json")
+    }
+
+    fn check(identifier: &str, item: ItemFn) -> syn::Result<()> {
+        let (_, signature) = find_handler(&config(), identifier).unwrap();
+        check_signature(&item, &signature, identifier)
+    }
+
+    #[test]
+    fn type_names() {
+        let ty: Type = parse_quote!(std::vec::Vec<crate::erpc::api::User>);
+        assert_eq!(type_name(&ty), "Vec<User>");
+
+        let ty: Type = parse_quote!(Result<(), r#type::Error>);
+        assert_eq!(type_name(&ty), "Result<(),Error>");
+
+        let ty: Type = parse_quote!(Option<i32>);
+        assert_eq!(type_name(&ty), "Option<i32>");
+    }
+
+    #[test]
+    fn matching_handlers() {
+        check(
+            "api/users/find",
+            parse_quote! {
+                async fn find(name: String, limit: Option<i32>) -> Vec<models::User> {
+                    vec![]
+                }
+            },
+        )
+        .unwrap();
+
+        check(
+            "api/users/rename",
+            parse_quote! {
+                async fn rename(user: User, name: String) -> Result<RenameReturn, RenameError> {
+                    Err(name)
+                }
+            },
+        )
+        .unwrap();
+
+        check(
+            "api/users/watch",
+            parse_quote! {
+                async fn watch(names: Vec<String>) -> impl futures::Stream<Item = User> + Send {
+                    futures::stream::empty()
+                }
+            },
+        )
+        .unwrap();
+
+        check("api/users/clear", parse_quote! { async fn clear() {} }).unwrap();
+    }
+
+    #[test]
+    fn mismatching_handlers() {
+        let err = check(
+            "api/users/find",
+            parse_quote! { async fn find(name: String, limit: i64) -> Vec<User> { vec![] } },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "api/users/find expects Option<i32> here, found i64"
+        );
+
+        let err = check(
+            "api/users/find",
+            parse_quote! { async fn find(name: String) -> Vec<User> { vec![] } },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "api/users/find takes 2 parameters (String, Option<i32>), the handler takes 1"
+        );
+
+        // the error type has to be part of the result
+        assert!(check(
+            "api/users/rename",
+            parse_quote! { async fn rename(user: User, name: String) -> RenameReturn { todo!() } },
+        )
+        .is_err());
+
+        assert!(check(
+            "api/users/watch",
+            parse_quote! { async fn watch(names: Vec<String>) -> Vec<User> { vec![] } },
+        )
+        .is_err());
+
+        assert!(check("api/users/clear", parse_quote! { fn clear() {} }).is_err());
+    }
+
+    #[test]
+    fn unknown_endpoints() {
+        // endpoints of other roles can't be handled
+        assert!(find_handler(&config(), "api/users/notify").is_err());
+        assert!(find_handler(&config(), "api/users/missing").is_err());
+        assert!(find_handler(Path::new("missing.json"), "api/users/find").is_err());
+    }
+
+    #[test]
+    fn registration() {
+        let args: HandlerArgs = parse_quote!("api/users/find");
+        let item: ItemFn = parse_quote! {
+            /** Finds users by name */
+            pub async fn find(name: String, limit: Option<i32>) -> Vec<User> {
+                vec![]
+            }
+        };
+
+        let expanded = expand_with_config(&config(), args, item)
+            .unwrap()
+            .to_string();
+
+        assert!(expanded.contains("pub struct find ;"));
+        assert!(expanded.contains("impl http_server :: EndpointHandler for find"));
+        assert!(expanded.contains("server . register_handler (find , \"api/users/find\")"));
+        assert!(expanded.contains(
+            "server . set_endpoint_middleware (\"api/users/find\" , vec ! [\"auth\" . to_string ()])"
+        ));
+    }
+}
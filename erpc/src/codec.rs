@@ -0,0 +1,144 @@
+#[cfg(not(target_arch = "wasm32"))]
+use log::error;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::protocol::SendableError;
+
+/**
+   The format messages are serialized with on the wire.
+   Http requests name it in the Content-Type header, websockets negotiate it as subprotocol when connecting.
+   Requests without a format use json, so clients which don't know about codecs keep working.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Codec {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl Codec {
+    /**
+       All supported codecs, in the order the server prefers them
+    */
+    pub const ALL: [Codec; 2] = [Codec::MessagePack, Codec::Json];
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Codec::Json => "application/json",
+            Codec::MessagePack => "application/msgpack",
+        }
+    }
+
+    pub fn subprotocol(&self) -> &'static str {
+        match self {
+            Codec::Json => "erpc.json",
+            Codec::MessagePack => "erpc.msgpack",
+        }
+    }
+
+    /**
+       Parses the value of a Content-Type header, parameters like the charset are ignored
+    */
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match mime.as_str() {
+            "application/json" => Some(Codec::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Codec::MessagePack)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn from_subprotocol(subprotocol: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|codec| codec.subprotocol() == subprotocol.trim())
+    }
+
+    /**
+       Picks the preferred codec out of the comma separated subprotocols a websocket client offers
+    */
+    pub fn negotiate_subprotocol(offered: &str) -> Option<Self> {
+        let offered: Vec<Codec> = offered
+            .split(',')
+            .filter_map(Self::from_subprotocol)
+            .collect();
+        Self::ALL.into_iter().find(|codec| offered.contains(codec))
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, SendableError> {
+        match self {
+            Codec::Json => Ok(serde_json::to_vec(value)?),
+            // structs are written as maps, otherwise tagged enums like SendableError can't be read back
+            Codec::MessagePack => Ok(rmp_serde::to_vec_named(value)?),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SendableError> {
+        self.try_decode(bytes).map_err(SendableError::from)
+    }
+
+    /**
+       Like decode, but the error is a BadRequest since the bytes were sent by the caller
+    */
+    pub fn decode_request<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SendableError> {
+        self.try_decode(bytes)
+            .map_err(|err| SendableError::BadRequest {
+                message: format!("Could not parse request: {}", err),
+                path: None,
+            })
+    }
+
+    fn try_decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Codec::Json => serde_json::from_slice(bytes).map_err(|err| err.to_string()),
+            Codec::MessagePack => rmp_serde::from_slice(bytes).map_err(|err| err.to_string()),
+        }
+    }
+
+    /**
+       Reads the codec of the request body, requests without a Content-Type use json
+    */
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_salvo_request(req: &salvo::Request) -> Result<Self, SendableError> {
+        let content_type = match req.headers().get(salvo::http::header::CONTENT_TYPE) {
+            Some(v) => v.to_str().unwrap_or_default(),
+            None => return Ok(Codec::Json),
+        };
+
+        Self::from_content_type(content_type).ok_or_else(|| SendableError::BadRequest {
+            message: format!("Unsupported content type {}", content_type),
+            path: None,
+        })
+    }
+
+    /**
+       Writes the encoded value as body of the response
+    */
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render<T: Serialize + ?Sized>(&self, value: &T, res: &mut salvo::Response) {
+        let bytes = match self.encode(value) {
+            Ok(v) => v,
+            Err(_) => {
+                res.status_code(salvo::http::StatusCode::INTERNAL_SERVER_ERROR);
+                return;
+            }
+        };
+
+        res.headers_mut().insert(
+            salvo::http::header::CONTENT_TYPE,
+            salvo::http::header::HeaderValue::from_static(self.content_type()),
+        );
+        if let Err(err) = res.write_body(bytes) {
+            error!("Could not write response body: {}", err);
+        }
+    }
+}
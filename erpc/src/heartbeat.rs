@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use crate::protocol::socket::{Heartbeat, SocketMessage};
+
+/**
+   Controls how often a websocket connection is checked with pings.
+   A connection is considered dead if max_missed_pongs pings in a row were not answered before the next ping was due.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatOptions {
+    pub interval: Duration,
+    pub max_missed_pongs: u32,
+}
+
+impl Default for HeartbeatOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            max_missed_pongs: 2,
+        }
+    }
+}
+
+/**
+   Tracks the pings of one connection which were not answered yet.
+   The side which sends pings calls ping every interval and pong for every pong which arrives.
+*/
+#[derive(Debug)]
+pub struct HeartbeatMonitor {
+    options: HeartbeatOptions,
+    missed_pongs: u32,
+    sent: u64,
+}
+
+impl HeartbeatMonitor {
+    pub fn new(options: HeartbeatOptions) -> Self {
+        Self {
+            options,
+            missed_pongs: 0,
+            sent: 0,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.options.interval
+    }
+
+    /**
+       The ping to send now, None if too many pings were missed and the connection should be closed
+    */
+    pub fn ping(&mut self) -> Option<SocketMessage> {
+        if self.missed_pongs >= self.options.max_missed_pongs {
+            return None;
+        }
+
+        // counted as missed until the pong arrives
+        self.missed_pongs += 1;
+        self.sent += 1;
+        Some(SocketMessage::Ping(Heartbeat {
+            id: self.sent.to_string(),
+        }))
+    }
+
+    /**
+       Any pong proves that the connection is alive, so older pings don't have to be answered anymore
+    */
+    pub fn pong(&mut self) {
+        self.missed_pongs = 0;
+    }
+}
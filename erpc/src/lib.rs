@@ -1,2 +1,7 @@
+pub mod codec;
+pub mod heartbeat;
 pub mod protocol;
-pub mod target;
\ No newline at end of file
+pub mod schema;
+pub mod stream;
+pub mod target;
+mod tests;
@@ -4,17 +4,102 @@ use log::error;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-//TODO: add more error types, to give the user more information about what went wrong
-/// Error type that can be sent over the wire, does not contain any sensitive information
-#[derive(Debug, Serialize, Deserialize, Error)]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::codec::Codec;
+
+/**
+   Error type that can be sent over the wire, does not contain any sensitive information.
+   It is serialized with a "type" field so clients can tell the variants apart.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
+#[serde(tag = "type")]
 pub enum SendableError {
+    /**
+       No handler is registered for the requested identifier
+    */
     NotFound,
+    /**
+       Something went wrong on the remote side, details are only logged and never sent
+    */
     Internal,
+    /**
+       The request could not be deserialized into the parameters of the handler.
+       The path points to the parameter which failed, e.g. "[1].name"
+    */
+    BadRequest {
+        message: String,
+        path: Option<String>,
+    },
+    /**
+       The remote side did not respond in time
+    */
+    Timeout,
+    /**
+       The request was rejected because it is not authorized
+    */
+    Unauthorized,
+    /**
+       The connection to the remote side was closed before a response arrived
+    */
+    ConnectionClosed,
+    /**
+       The handler panicked while processing the request
+    */
+    HandlerPanicked,
+    /**
+       The request was rejected because too many requests are queued or in flight, it can be tried again later
+    */
+    Overloaded,
+    /**
+       The handler returned the error type it declared with throws.
+       Unlike all other variants this is not a system error but part of the result of the endpoint
+    */
+    Application { error: serde_json::Value },
+}
+
+impl SendableError {
+    /**
+       A message describing the error which is safe to show to the caller
+    */
+    pub fn message(&self) -> String {
+        match self {
+            Self::NotFound => "No handler found for this request".to_string(),
+            Self::Internal => "Internal error".to_string(),
+            Self::BadRequest { message, path } => match path {
+                Some(path) => format!("Bad request at {path}: {message}"),
+                None => format!("Bad request: {message}"),
+            },
+            Self::Timeout => "The request timed out".to_string(),
+            Self::Unauthorized => "The request is not authorized".to_string(),
+            Self::ConnectionClosed => "The connection was closed".to_string(),
+            Self::HandlerPanicked => "The handler panicked".to_string(),
+            Self::Overloaded => "Too many requests are pending, try again later".to_string(),
+            Self::Application { .. } => "The handler returned an application error".to_string(),
+        }
+    }
+
+    /**
+       Reconstructs an error from a http status code, used when the response body does not contain an error
+    */
+    pub fn from_status_code(status_code: u16) -> Self {
+        match status_code {
+            400 => Self::BadRequest {
+                message: "Invalid request".to_string(),
+                path: None,
+            },
+            401 | 403 => Self::Unauthorized,
+            404 => Self::NotFound,
+            408 | 504 => Self::Timeout,
+            502 => Self::ConnectionClosed,
+            429 | 503 => Self::Overloaded,
+            _ => Self::Internal,
+        }
+    }
 }
 
 impl Display for SendableError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:#?}", self)
+        write!(f, "{}", self.message())
     }
 }
 
@@ -25,11 +110,39 @@ impl From<serde_json::Error> for SendableError {
     }
 }
 
+impl From<rmp_serde::encode::Error> for SendableError {
+    fn from(value: rmp_serde::encode::Error) -> Self {
+        error!("{}", value);
+        Self::Internal
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for SendableError {
+    fn from(value: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        // the root path is displayed as "." and does not help to find the faulty parameter
+        let path = match value.path().to_string().as_str() {
+            "." => None,
+            v => Some(v.to_string()),
+        };
+
+        Self::BadRequest {
+            message: value.inner().to_string(),
+            path,
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<reqwest::Error> for SendableError {
     fn from(value: reqwest::Error) -> Self {
         error!("{}", value);
-        Self::Internal
+        if value.is_timeout() {
+            Self::Timeout
+        } else if value.is_connect() {
+            Self::ConnectionClosed
+        } else {
+            Self::Internal
+        }
     }
 }
 
@@ -78,15 +191,34 @@ impl From<&str> for SendableError {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl salvo::Piece for SendableError {
-    fn render(self, res: &mut salvo::Response) {
+impl SendableError {
+    pub fn status_code(&self) -> salvo::http::StatusCode {
         match self {
-            Self::NotFound => {
-                res.status_code(salvo::http::StatusCode::NOT_FOUND);
-            }
-            Self::Internal => {
-                res.status_code(salvo::http::StatusCode::INTERNAL_SERVER_ERROR);
-            }
+            Self::NotFound => salvo::http::StatusCode::NOT_FOUND,
+            Self::Internal => salvo::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Self::BadRequest { .. } => salvo::http::StatusCode::BAD_REQUEST,
+            Self::Timeout => salvo::http::StatusCode::GATEWAY_TIMEOUT,
+            Self::Unauthorized => salvo::http::StatusCode::UNAUTHORIZED,
+            Self::ConnectionClosed => salvo::http::StatusCode::BAD_GATEWAY,
+            Self::HandlerPanicked => salvo::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Overloaded => salvo::http::StatusCode::SERVICE_UNAVAILABLE,
+            Self::Application { .. } => salvo::http::StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SendableError {
+    pub fn render_with_codec(self, res: &mut salvo::Response, codec: Codec) {
+        res.status_code(self.status_code());
+        // the body carries the variant so clients can reconstruct the error
+        codec.render(&self, res);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl salvo::Piece for SendableError {
+    fn render(self, res: &mut salvo::Response) {
+        self.render_with_codec(res, Codec::Json);
+    }
+}
@@ -6,29 +6,110 @@ pub use self::error::SendableError;
 use serde::{Deserialize, Serialize};
 
 #[cfg(not(target_arch = "wasm32"))]
-use salvo::writing::Json;
+use crate::codec::Codec;
 
 /**
    The most basic kind of request. Used to pass around request info internally, e.g. to pass into the handlers
 */
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Request {
     pub identifier: String,
-    pub parameters: Vec<serde_json::Value>,
+    pub parameters: Parameters,
+}
+
+/**
+   The parameters of a call. Endpoints declared with named parameters, e.g. login({user string}), are called
+   with an object keyed by parameter name, so the order of their parameters can change without breaking callers.
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Parameters {
+    Positional(Vec<serde_json::Value>),
+    Named(serde_json::Map<String, serde_json::Value>),
+}
+
+impl Parameters {
+    pub fn len(&self) -> usize {
+        match self {
+            Parameters::Positional(v) => v.len(),
+            Parameters::Named(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+       Serializes the parameter struct of an endpoint with named parameters
+    */
+    pub fn named<T: Serialize>(parameters: &T) -> Result<Self, SendableError> {
+        match serde_json::to_value(parameters)? {
+            serde_json::Value::Object(v) => Ok(Parameters::Named(v)),
+            _ => Err(SendableError::from(
+                "Named parameters have to serialize to an object",
+            )),
+        }
+    }
+
+    /**
+       The arguments a js handler is called with, named parameters are passed as a single options object
+    */
+    pub fn into_arguments(self) -> Vec<serde_json::Value> {
+        match self {
+            Parameters::Positional(v) => v,
+            Parameters::Named(v) => vec![serde_json::Value::Object(v)],
+        }
+    }
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters::Positional(vec![])
+    }
+}
+
+impl From<Vec<serde_json::Value>> for Parameters {
+    fn from(value: Vec<serde_json::Value>) -> Self {
+        Parameters::Positional(value)
+    }
+}
+
+impl FromIterator<serde_json::Value> for Parameters {
+    fn from_iter<T: IntoIterator<Item = serde_json::Value>>(iter: T) -> Self {
+        Parameters::Positional(iter.into_iter().collect())
+    }
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for Parameters {
+    fn from(value: serde_json::Map<String, serde_json::Value>) -> Self {
+        Parameters::Named(value)
+    }
 }
 
 impl Request {
+    /**
+       Reads the parameters from the body of the request, they are decoded with the codec named in its Content-Type.
+       Bodies larger than max_size bytes are rejected
+    */
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn try_from_salvo_request(
         req: &mut salvo::Request,
         identifier: String,
+        codec: Codec,
+        max_size: usize,
     ) -> Result<Self, SendableError> {
+        let body = req
+            .payload_with_max_size(max_size)
+            .await
+            .map_err(|err| SendableError::BadRequest {
+                message: format!("Could not read request: {}", err),
+                path: None,
+            })?;
+
         Ok(Request {
             identifier,
-            parameters: req
-                .parse_json()
-                .await
-                .map_err(|err| format!("Could not parse request: {}", err))?,
+            parameters: codec.decode_request(body)?,
         })
     }
 }
@@ -39,12 +120,51 @@ impl Request {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Response {
     /**
-       An error in the result indicates an actual system error and not a user defined error.
-       E.g. "connection closed" but not "wrong password"
+       An error in the result indicates an actual system error, e.g. "connection closed".
+       The only exception is SendableError::Application which carries the user defined error
+       of endpoints declaring an error type with throws, e.g. "wrong password"
     */
     pub body: Result<serde_json::Value, SendableError>,
 }
 
+impl Response {
+    /**
+       Converts the body into the result object which is passed to and from js for endpoints declaring an error type.
+       The object is either { ok: true, value } or { ok: false, error }, system errors are still returned as error.
+    */
+    pub fn into_result_object(self) -> Result<serde_json::Value, SendableError> {
+        match self.body {
+            Ok(value) => Ok(serde_json::json!({ "ok": true, "value": value })),
+            Err(SendableError::Application { error }) => {
+                Ok(serde_json::json!({ "ok": false, "error": error }))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /**
+       Creates a response out of a result object as described in into_result_object
+    */
+    pub fn from_result_object(value: serde_json::Value) -> Self {
+        let mut object = match value {
+            serde_json::Value::Object(v) => v,
+            _ => return SendableError::from("Handler did not return a result object").into(),
+        };
+
+        match object.get("ok") {
+            Some(serde_json::Value::Bool(true)) => object
+                .remove("value")
+                .unwrap_or(serde_json::Value::Null)
+                .into(),
+            Some(serde_json::Value::Bool(false)) => SendableError::Application {
+                error: object.remove("error").unwrap_or(serde_json::Value::Null),
+            }
+            .into(),
+            _ => SendableError::from("Handler returned a result object without ok field").into(),
+        }
+    }
+}
+
 impl From<SendableError> for Response {
     fn from(value: SendableError) -> Self {
         Response { body: Err(value) }
@@ -58,11 +178,18 @@ impl From<serde_json::Value> for Response {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl salvo::Piece for Response {
-    fn render(self, res: &mut salvo::Response) {
+impl Response {
+    pub fn render_with_codec(self, res: &mut salvo::Response, codec: Codec) {
         match self.body {
-            Ok(v) => res.render(Json(v)),
-            Err(err) => err.render(res),
+            Ok(v) => codec.render(&v, res),
+            Err(err) => err.render_with_codec(res, codec),
         }
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+impl salvo::Piece for Response {
+    fn render(self, res: &mut salvo::Response) {
+        self.render_with_codec(res, Codec::Json);
+    }
+}
@@ -3,3 +3,6 @@ pub static  HANDLERS_ROUTE: &str = "handlers";
 
 // a const indicating the route to use for establishing a websocket connection
 pub static  WEBSOCKETS_ROUTE: &str = "ws";
+
+// a const indicating the header clients send the schema hash they were generated with in
+pub static  SCHEMA_HASH_HEADER: &str = "erpc-schema-hash";
@@ -2,6 +2,24 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsCast;
 
 use super::SendableError;
+use crate::codec::Codec;
+
+/**
+   The version of the socket protocol, it is increased whenever the messages change in a way older peers can't read
+*/
+pub const PROTOCOL_VERSION: u32 = 1;
+/**
+   The oldest version of the socket protocol which is still understood
+*/
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+/**
+   The optional parts of the protocol this build understands, peers only use the ones both of them support
+*/
+pub const FEATURES: &[&str] = &["streams", "relay", "heartbeat", "namedParameters"];
+/**
+   The code of the close frame the server sends after rejecting a handshake
+*/
+pub const HANDSHAKE_REJECTED_CLOSE_CODE: u16 = 1002;
 
 /**
    A socket message
@@ -10,52 +28,77 @@ use super::SendableError;
 pub enum SocketMessage {
     Request(Request),
     Response(Response),
+    Cancel(Cancel),
+    /**
+       Calls a streaming endpoint, the remote side answers with stream items instead of a response
+    */
+    StreamRequest(Request),
+    StreamItem(StreamItem),
+    StreamEnd(StreamEnd),
+    StreamError(StreamError),
+    StreamDemand(StreamDemand),
+    /**
+       Calls an endpoint of another browser, the server forwards the request to its socket and routes the response back
+    */
+    RelayRequest(RelayRequest),
+    /**
+       Checks that the connection is alive, the remote side answers with a pong with the same id
+    */
+    Ping(Heartbeat),
+    Pong(Heartbeat),
+    /**
+       The first message of a client after the connection opened, the server answers with HandshakeAccepted or HandshakeRejected
+    */
+    Handshake(Handshake),
+    HandshakeAccepted(HandshakeAccepted),
+    /**
+       The server closes the connection after sending it, connecting again won't help
+    */
+    HandshakeRejected(HandshakeRejected),
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl TryFrom<salvo::websocket::Message> for SocketMessage {
-    type Error = SendableError;
-
-    fn try_from(value: salvo::websocket::Message) -> Result<Self, Self::Error> {
-        serde_json::from_slice(&value.into_bytes()).map_err(|e| e.into())
+impl SocketMessage {
+    pub fn encode(&self, codec: Codec) -> Result<Vec<u8>, SendableError> {
+        codec.encode(self)
     }
-}
 
-#[cfg(not(target_arch = "wasm32"))]
-impl TryInto<salvo::websocket::Message> for SocketMessage {
-    type Error = SendableError;
-
-    fn try_into(self) -> Result<salvo::websocket::Message, Self::Error> {
-        Ok(salvo::websocket::Message::binary(serde_json::to_vec(
-            &self,
-        )?))
+    pub fn decode(bytes: &[u8], codec: Codec) -> Result<Self, SendableError> {
+        codec.decode(bytes)
     }
-}
 
-impl TryInto<Vec<u8>> for SocketMessage {
-    type Error = SendableError;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn try_from_salvo_message(
+        value: salvo::websocket::Message,
+        codec: Codec,
+    ) -> Result<Self, SendableError> {
+        Self::decode(&value.into_bytes(), codec)
+    }
 
-    fn try_into(self) -> Result<Vec<u8>, Self::Error> {
-        Ok(serde_json::to_vec(&self)?)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn try_into_salvo_message(
+        &self,
+        codec: Codec,
+    ) -> Result<salvo::websocket::Message, SendableError> {
+        Ok(salvo::websocket::Message::binary(self.encode(codec)?))
     }
-}
 
-// #[cfg(target_arch = "wasm32")]
-impl SocketMessage {
     pub async fn try_from_wasm_socket_message_event(
         value: web_sys::MessageEvent,
+        codec: Codec,
     ) -> Result<Self, SendableError> {
         if let Ok(abuf) = value.data().dyn_into::<js_sys::ArrayBuffer>() {
             let array = js_sys::Uint8Array::new(&abuf);
-            Ok(serde_json::from_slice(array.to_vec().as_slice())?)
+            Self::decode(array.to_vec().as_slice(), codec)
         } else if let Ok(blob) = value.data().dyn_into::<web_sys::Blob>() {
             let b = gloo_file::futures::read_as_bytes(&gloo_file::Blob::from(blob)).await?;
-            Ok(serde_json::from_slice(b.as_slice())?)
+            Self::decode(b.as_slice(), codec)
         } else if let Ok(txt) = value.data().dyn_into::<js_sys::JsString>() {
-            Ok(serde_json::from_str(
-                &txt.as_string()
-                    .ok_or("Could not convert ws string to rs string")?,
-            )?)
+            Self::decode(
+                txt.as_string()
+                    .ok_or("Could not convert ws string to rs string")?
+                    .as_bytes(),
+                codec,
+            )
         } else {
             Err(SendableError::from("Unknown message type"))
         }
@@ -67,6 +110,19 @@ impl SocketMessage {
         match self {
             SocketMessage::Request(r) => &r.id,
             SocketMessage::Response(r) => &r.id,
+            SocketMessage::Cancel(c) => &c.id,
+            SocketMessage::StreamRequest(r) => &r.id,
+            SocketMessage::StreamItem(i) => &i.id,
+            SocketMessage::StreamEnd(e) => &e.id,
+            SocketMessage::StreamError(e) => &e.id,
+            SocketMessage::StreamDemand(d) => &d.id,
+            SocketMessage::RelayRequest(r) => &r.id,
+            SocketMessage::Ping(h) => &h.id,
+            SocketMessage::Pong(h) => &h.id,
+            // handshakes don't belong to a call
+            SocketMessage::Handshake(_)
+            | SocketMessage::HandshakeAccepted(_)
+            | SocketMessage::HandshakeRejected(_) => "",
         }
     }
 }
@@ -95,6 +151,24 @@ impl Request {
     }
 }
 
+/**
+    A request for another browser which is connected to the same server.
+    The caller receives the response with the id of this request like for any other request
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RelayRequest {
+    pub id: String,
+    /**
+        The role the destination connected with
+    */
+    pub role: String,
+    /**
+        The id of the websocket connection of the destination, see RequestContext.socket_id on the server
+    */
+    pub socket_id: String,
+    pub request: super::Request,
+}
+
 /*
     A response to a websocket request. Wraps around the basic response to add an ID to assign this response to a request
 */
@@ -114,4 +188,155 @@ impl Response {
             response,
         }
     }
-}
\ No newline at end of file
+}
+
+/**
+    Tells the remote side that the caller is no longer waiting for the response of a request, e.g. because it timed out.
+    The remote side can abort the work for this request and does not need to send a response.
+    For streams this stops the producer, no further items are sent
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cancel {
+    /**
+        The id of the request which is cancelled
+    */
+    pub id: String,
+}
+
+/**
+    An item of a stream, sent by the side which received the stream request
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StreamItem {
+    /**
+        The id of the stream request this item belongs to
+    */
+    pub id: String,
+    pub value: serde_json::Value,
+}
+
+/**
+    Tells the consumer that the stream is complete, no further items follow
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StreamEnd {
+    pub id: String,
+}
+
+/**
+    Ends a stream with an error, no further items follow
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StreamError {
+    pub id: String,
+    pub error: SendableError,
+}
+
+/**
+    Allows the producer of a stream to send the given amount of additional items.
+    Producers may send crate::stream::STREAM_WINDOW items before the first demand arrives
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StreamDemand {
+    pub id: String,
+    pub amount: u32,
+}
+
+/**
+    A ping or the pong answering it
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Heartbeat {
+    pub id: String,
+}
+
+/**
+    Tells the server which protocol the client speaks
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Handshake {
+    pub version: u32,
+    pub codec: Codec,
+    /**
+        The hash of the schema the client was generated from, None if it is unknown
+    */
+    #[serde(default)]
+    pub schema_hash: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl Handshake {
+    pub fn new(codec: Codec, schema_hash: Option<String>) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            codec,
+            schema_hash,
+            features: FEATURES.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    /**
+        Decides whether the server can talk to the client, the codec is the one the connection was opened with.
+        Newer clients are downgraded to the version of the server, they have to support it down to their MIN_PROTOCOL_VERSION
+    */
+    pub fn negotiate(&self, codec: Codec) -> Result<HandshakeAccepted, HandshakeRejected> {
+        if self.version < MIN_PROTOCOL_VERSION {
+            return Err(HandshakeRejected::new(format!(
+                "Protocol version {} is not supported anymore, the server needs at least version {MIN_PROTOCOL_VERSION}",
+                self.version
+            )));
+        }
+        if self.codec != codec {
+            return Err(HandshakeRejected::new(format!(
+                "The client uses the codec {:?} but the connection was opened with {:?}",
+                self.codec, codec
+            )));
+        }
+
+        Ok(HandshakeAccepted {
+            version: self.version.min(PROTOCOL_VERSION),
+            features: self
+                .features
+                .iter()
+                .filter(|v| FEATURES.contains(&v.as_str()))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+/**
+    The protocol both sides use from now on
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HandshakeAccepted {
+    pub version: u32,
+    /**
+        The features both sides support
+    */
+    pub features: Vec<String>,
+}
+
+/**
+    Why the server does not talk to the client, e.g. because the client is too old
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HandshakeRejected {
+    pub reason: String,
+    /**
+        The protocol versions the server supports, so clients can tell whether they have to update
+    */
+    pub version: u32,
+    pub min_version: u32,
+}
+
+impl HandshakeRejected {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+        }
+    }
+}
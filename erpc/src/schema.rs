@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::protocol::{Parameters, SendableError};
+
+/**
+   Describes which values are allowed for a parameter or field. It is generated by the transpiler from the .erpc sources
+   and used to check calls before they reach handlers which receive their parameters untyped.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Schema {
+    Boolean,
+    /**
+       An integer in the range of its declared type, e.g. -128 to 127 for int8
+    */
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Float,
+    String,
+    /**
+       Exactly the given value, used for the literals of enums
+    */
+    Literal {
+        value: Value,
+    },
+    /**
+       Any of the variants, used for enums
+    */
+    Union {
+        variants: Vec<Schema>,
+    },
+    Array {
+        items: Box<Schema>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        length: Option<usize>,
+    },
+    /**
+       A custom type, its fields are defined in the types of the EndpointSchema
+    */
+    Custom {
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    #[serde(default)]
+    pub optional: bool,
+    pub schema: Schema,
+}
+
+/**
+   The schema of all parameters of an endpoint. Custom types are referenced by name to allow recursive types.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointSchema {
+    pub parameters: Vec<FieldSchema>,
+    #[serde(default)]
+    pub types: HashMap<String, Vec<FieldSchema>>,
+    /**
+       The endpoint is called with an object keyed by parameter name instead of a list of parameters
+    */
+    #[serde(default)]
+    pub named: bool,
+}
+
+impl EndpointSchema {
+    /**
+       Checks the parameters of a call. The error points to the first invalid value with a path like "[1].name",
+       or "user.name" for named parameters, the same format deserialization errors of typed handlers use.
+    */
+    pub fn validate(&self, parameters: &Parameters) -> Result<(), SendableError> {
+        match (parameters, self.named) {
+            (Parameters::Positional(v), false) => self.validate_positional(v),
+            (Parameters::Named(v), true) => self.validate_named(v),
+            (Parameters::Positional(_), true) => Err(SendableError::BadRequest {
+                message: "Expected named parameters".to_string(),
+                path: None,
+            }),
+            (Parameters::Named(_), false) => Err(SendableError::BadRequest {
+                message: "Expected a list of parameters".to_string(),
+                path: None,
+            }),
+        }
+    }
+
+    fn validate_positional(&self, parameters: &[Value]) -> Result<(), SendableError> {
+        if parameters.len() > self.parameters.len() {
+            return Err(SendableError::BadRequest {
+                message: format!(
+                    "Expected at most {} parameters, got {}",
+                    self.parameters.len(),
+                    parameters.len()
+                ),
+                path: None,
+            });
+        }
+
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            let path = format!("[{i}]");
+            match parameters.get(i) {
+                None | Some(Value::Null) if parameter.optional => {}
+                None => {
+                    return Err(bad_request(
+                        format!("Missing parameter {}", parameter.name),
+                        path,
+                    ))
+                }
+                Some(value) => self.validate_value(&parameter.schema, value, path)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_named(&self, parameters: &Map<String, Value>) -> Result<(), SendableError> {
+        if let Some(name) = parameters
+            .keys()
+            .find(|name| !self.parameters.iter().any(|v| &v.name == *name))
+        {
+            return Err(bad_request(
+                format!("Unknown parameter {name}"),
+                name.to_string(),
+            ));
+        }
+
+        for parameter in &self.parameters {
+            let path = parameter.name.clone();
+            match parameters.get(&parameter.name) {
+                None | Some(Value::Null) if parameter.optional => {}
+                None => {
+                    return Err(bad_request(
+                        format!("Missing parameter {}", parameter.name),
+                        path,
+                    ))
+                }
+                Some(value) => self.validate_value(&parameter.schema, value, path)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_value(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        path: String,
+    ) -> Result<(), SendableError> {
+        match schema {
+            Schema::Boolean => match value {
+                Value::Bool(_) => Ok(()),
+                _ => Err(bad_request(expected("a boolean", value), path)),
+            },
+            Schema::Integer { min, max } => match value.as_i64() {
+                Some(v) if v >= *min && v <= *max => Ok(()),
+                Some(v) => Err(bad_request(
+                    format!("Expected an integer between {min} and {max}, got {v}"),
+                    path,
+                )),
+                None => Err(bad_request(expected("an integer", value), path)),
+            },
+            Schema::Float => match value {
+                Value::Number(_) => Ok(()),
+                _ => Err(bad_request(expected("a number", value), path)),
+            },
+            Schema::String => match value {
+                Value::String(_) => Ok(()),
+                _ => Err(bad_request(expected("a string", value), path)),
+            },
+            Schema::Literal { value: literal } => {
+                if literal == value || numbers_equal(literal, value) {
+                    Ok(())
+                } else {
+                    Err(bad_request(
+                        format!("Expected {literal}, got {value}"),
+                        path,
+                    ))
+                }
+            }
+            Schema::Union { variants } => {
+                if variants
+                    .iter()
+                    .any(|variant| self.validate_value(variant, value, path.clone()).is_ok())
+                {
+                    Ok(())
+                } else {
+                    Err(bad_request(
+                        format!("{value} does not match any of the allowed values"),
+                        path,
+                    ))
+                }
+            }
+            Schema::Array { items, length } => {
+                let values = match value {
+                    Value::Array(v) => v,
+                    _ => return Err(bad_request(expected("an array", value), path)),
+                };
+
+                match length {
+                    Some(length) if values.len() != *length => {
+                        return Err(bad_request(
+                            format!(
+                                "Expected an array of length {length}, got length {}",
+                                values.len()
+                            ),
+                            path,
+                        ))
+                    }
+                    _ => {}
+                }
+
+                for (i, item) in values.iter().enumerate() {
+                    self.validate_value(items, item, format!("{path}[{i}]"))?;
+                }
+
+                Ok(())
+            }
+            Schema::Custom { name } => {
+                let fields = match self.types.get(name) {
+                    Some(v) => v,
+                    None => {
+                        return Err(SendableError::from(format!(
+                            "Schema does not define the type {name}"
+                        )))
+                    }
+                };
+
+                let object = match value {
+                    Value::Object(v) => v,
+                    _ => return Err(bad_request(expected("an object", value), path)),
+                };
+
+                for field in fields {
+                    let field_path = format!("{path}.{}", field.name);
+                    match object.get(&field.name) {
+                        None | Some(Value::Null) if field.optional => {}
+                        None => {
+                            return Err(bad_request(
+                                format!("Missing field {}", field.name),
+                                field_path,
+                            ))
+                        }
+                        Some(value) => self.validate_value(&field.schema, value, field_path)?,
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn bad_request(message: String, path: String) -> SendableError {
+    SendableError::BadRequest {
+        message,
+        path: Some(path),
+    }
+}
+
+fn expected(kind: &str, value: &Value) -> String {
+    format!("Expected {kind}, got {value}")
+}
+
+/**
+   Float literals are compared by value since 1 and 1.0 are different json numbers
+*/
+fn numbers_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::{
+    future::{select, Either},
+    stream::Map,
+    Future, FutureExt, StreamExt,
+};
+use log::error;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::protocol::{
+    socket::{Cancel, SocketMessage, StreamDemand, StreamEnd, StreamError, StreamItem},
+    SendableError,
+};
+
+/**
+   The amount of items a producer may send before the consumer granted more.
+   Consumers grant half of the window again whenever they consumed that many items.
+*/
+pub const STREAM_WINDOW: u32 = 16;
+
+// re-exported so generated code can name streams without depending on futures
+pub use futures::Stream;
+
+/**
+   The items a streaming handler produces
+*/
+pub type ItemStream = Pin<Box<dyn Stream<Item = Result<Value, SendableError>> + Send>>;
+
+/**
+   The streams one side of a socket produces for the other side.
+   Demand and cancellations arriving on the socket are passed to the producers through this.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct StreamProducers {
+    demand: Arc<Mutex<HashMap<String, flume::Sender<u32>>>>,
+}
+
+impl StreamProducers {
+    /**
+       Creates the future which sends the items to the consumer, it has to be spawned by the caller.
+       It completes when the stream ended, the consumer cancelled it or the socket closed.
+    */
+    pub fn start<S>(
+        &self,
+        id: String,
+        items: S,
+        outgoing: flume::Sender<SocketMessage>,
+    ) -> impl Future<Output = ()>
+    where
+        S: Stream<Item = Result<Value, SendableError>> + Unpin,
+    {
+        let (demand_sender, demand_reciever) = flume::unbounded();
+        lock(&self.demand).insert(id.clone(), demand_sender);
+
+        let demand = self.demand.clone();
+        async move {
+            produce(&id, items, demand_reciever, outgoing).await;
+            lock(&demand).remove(&id);
+        }
+    }
+
+    pub fn demand(&self, demand: StreamDemand) {
+        let mut producers = lock(&self.demand);
+        if let Some(sender) = producers.get(&demand.id) {
+            if sender.send(demand.amount).is_err() {
+                producers.remove(&demand.id);
+            }
+        }
+    }
+
+    /**
+       Stops the producer of the stream, nothing happens if there is none
+    */
+    pub fn cancel(&self, id: &str) {
+        lock(&self.demand).remove(id);
+    }
+
+    /**
+       Stops all producers, used when the socket closed
+    */
+    pub fn clear(&self) {
+        lock(&self.demand).clear();
+    }
+}
+
+/**
+   Sends the items to the consumer, but never more than the consumer granted.
+   Stops as soon as the demand channel is closed, which means the stream was cancelled.
+*/
+async fn produce<S>(
+    id: &str,
+    mut items: S,
+    demand: flume::Receiver<u32>,
+    outgoing: flume::Sender<SocketMessage>,
+) where
+    S: Stream<Item = Result<Value, SendableError>> + Unpin,
+{
+    let mut credits = STREAM_WINDOW;
+
+    loop {
+        // waiting for the next item must not delay noticing a cancellation
+        let item = loop {
+            match select(items.next(), demand.recv_async()).await {
+                Either::Left((item, _)) => break item,
+                Either::Right((Ok(amount), _)) => credits = credits.saturating_add(amount),
+                Either::Right((Err(_), _)) => return,
+            }
+        };
+
+        let message = match item {
+            Some(Ok(value)) => {
+                while credits == 0 {
+                    match demand.recv_async().await {
+                        Ok(amount) => credits = credits.saturating_add(amount),
+                        Err(_) => return,
+                    }
+                }
+                credits -= 1;
+
+                SocketMessage::StreamItem(StreamItem {
+                    id: id.to_string(),
+                    value,
+                })
+            }
+            Some(Err(error)) => SocketMessage::StreamError(StreamError {
+                id: id.to_string(),
+                error,
+            }),
+            None => SocketMessage::StreamEnd(StreamEnd { id: id.to_string() }),
+        };
+
+        let last = !matches!(message, SocketMessage::StreamItem(_));
+        // waits while the queue of the socket is full instead of growing it
+        if let Err(err) = outgoing.send_async(message).await {
+            error!("Could not send stream message: {err}");
+            return;
+        }
+        if last {
+            return;
+        }
+    }
+}
+
+/**
+   The streams one side of a socket consumes. Stream messages arriving on the socket are passed to their receivers through this.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct StreamConsumers {
+    items: Arc<Mutex<HashMap<String, flume::Sender<SocketMessage>>>>,
+}
+
+impl StreamConsumers {
+    /**
+       Creates the receiver for the stream request with the id, which has to be sent on outgoing afterwards
+    */
+    pub fn open(&self, id: String, outgoing: flume::Sender<SocketMessage>) -> StreamReceiver {
+        let (sender, reciever) = flume::unbounded();
+        lock(&self.items).insert(id.clone(), sender);
+
+        StreamReceiver {
+            id,
+            items: reciever.into_stream(),
+            outgoing,
+            consumers: self.clone(),
+            consumed: 0,
+            demand: None,
+            done: false,
+        }
+    }
+
+    /**
+       Passes a stream item, end or error to the receiver of its stream
+    */
+    pub fn dispatch(&self, message: SocketMessage) {
+        let id = message.id().to_string();
+        let last = !matches!(message, SocketMessage::StreamItem(_));
+
+        let mut consumers = lock(&self.items);
+        let sender = match consumers.get(&id) {
+            Some(v) => v,
+            None => {
+                error!("Could not find open stream for id {id}");
+                return;
+            }
+        };
+
+        if sender.send(message).is_err() || last {
+            consumers.remove(&id);
+        }
+    }
+
+    /**
+       Fails all open streams with ConnectionClosed, used when the socket closed
+    */
+    pub fn clear(&self) {
+        lock(&self.items).clear();
+    }
+}
+
+/**
+   The items of a stream which was requested from the other side of a socket.
+   It grants the producer new items while they are consumed and cancels the stream when dropped before it ended.
+*/
+pub struct StreamReceiver {
+    id: String,
+    items: flume::r#async::RecvStream<'static, SocketMessage>,
+    outgoing: flume::Sender<SocketMessage>,
+    consumers: StreamConsumers,
+    /**
+       Items consumed since the last demand was sent
+    */
+    consumed: u32,
+    /**
+       A demand which did not fit into the queue of the socket, it is sent as soon as there is room
+    */
+    demand: Option<flume::r#async::SendFut<'static, SocketMessage>>,
+    done: bool,
+}
+
+/**
+   The items of a stream deserialized into the item type of the endpoint
+*/
+pub type TypedStream<T> =
+    Map<StreamReceiver, fn(Result<Value, SendableError>) -> Result<T, SendableError>>;
+
+impl StreamReceiver {
+    /**
+       Sends the pending demand if the queue of the socket has room, otherwise the stream is woken once it has
+    */
+    fn poll_demand(&mut self, cx: &mut Context<'_>) {
+        let result = match self.demand.as_mut().map(|v| v.poll_unpin(cx)) {
+            Some(Poll::Ready(v)) => v,
+            _ => return,
+        };
+        self.demand = None;
+        if let Err(err) = result {
+            error!("Could not send stream demand: {err}");
+        }
+    }
+
+    /**
+       Deserializes the items into the item type of the endpoint, items which don't match it become errors
+    */
+    pub fn typed<T: DeserializeOwned>(self) -> TypedStream<T> {
+        self.map(deserialize_item::<T>)
+    }
+}
+
+fn deserialize_item<T: DeserializeOwned>(
+    item: Result<Value, SendableError>,
+) -> Result<T, SendableError> {
+    Ok(serde_json::from_value(item?)?)
+}
+
+impl Stream for StreamReceiver {
+    type Item = Result<Value, SendableError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            // polled before waiting for items, the producer might not send any until it got the demand
+            self.poll_demand(cx);
+
+            let message = match self.items.poll_next_unpin(cx) {
+                Poll::Ready(v) => v,
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match message {
+                Some(SocketMessage::StreamItem(item)) => {
+                    self.consumed += 1;
+                    // items consumed while a demand waits for room are granted with the next one
+                    if self.consumed >= STREAM_WINDOW / 2 && self.demand.is_none() {
+                        let demand = SocketMessage::StreamDemand(StreamDemand {
+                            id: self.id.clone(),
+                            amount: self.consumed,
+                        });
+                        self.consumed = 0;
+                        self.demand = Some(self.outgoing.clone().into_send_async(demand));
+                        self.poll_demand(cx);
+                    }
+
+                    return Poll::Ready(Some(Ok(item.value)));
+                }
+                Some(SocketMessage::StreamEnd(_)) => {
+                    self.done = true;
+                    return Poll::Ready(None);
+                }
+                Some(SocketMessage::StreamError(err)) => {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(err.error)));
+                }
+                Some(message) => error!("Unexpected message on stream {}: {:?}", self.id, message),
+                // the sender is dropped when the socket closed
+                None => {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(SendableError::ConnectionClosed)));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StreamReceiver {
+    fn drop(&mut self) {
+        lock(&self.consumers.items).remove(&self.id);
+
+        if !self.done {
+            // the socket might already be closed, then there is nothing left to cancel.
+            // If its queue is full the producer stops once it used up its window
+            let _ = self.outgoing.try_send(SocketMessage::Cancel(Cancel {
+                id: self.id.clone(),
+            }));
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // the maps stay consistent even if a thread panicked while holding the lock
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
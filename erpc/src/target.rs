@@ -1,6 +1,9 @@
-
 #[derive(Debug, Clone)]
 pub enum TargetType {
     HttpServer,
-    Browser
-}
\ No newline at end of file
+    Browser,
+    /**
+       A server running in the same process, calls are dispatched to its handlers without any network traffic
+    */
+    Loopback,
+}
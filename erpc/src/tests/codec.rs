@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        codec::Codec,
+        protocol::{self, socket::SocketMessage, SendableError},
+    };
+
+    #[test]
+    fn content_type() {
+        assert_eq!(
+            Codec::from_content_type("application/json; charset=utf-8"),
+            Some(Codec::Json)
+        );
+        assert_eq!(
+            Codec::from_content_type("Application/MsgPack"),
+            Some(Codec::MessagePack)
+        );
+        assert_eq!(
+            Codec::from_content_type("application/x-msgpack"),
+            Some(Codec::MessagePack)
+        );
+        assert_eq!(Codec::from_content_type("text/plain"), None);
+
+        for codec in Codec::ALL {
+            assert_eq!(Codec::from_content_type(codec.content_type()), Some(codec));
+        }
+    }
+
+    #[test]
+    fn subprotocol() {
+        assert_eq!(
+            Codec::negotiate_subprotocol("erpc.json, erpc.msgpack"),
+            Some(Codec::MessagePack)
+        );
+        assert_eq!(
+            Codec::negotiate_subprotocol("chat, erpc.json"),
+            Some(Codec::Json)
+        );
+        assert_eq!(Codec::negotiate_subprotocol("chat"), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let messages = vec![
+            SocketMessage::Request(protocol::socket::Request {
+                id: "1".to_string(),
+                request: protocol::Request {
+                    identifier: "api/echo".to_string(),
+                    parameters: vec![
+                        json!(1),
+                        json!(-2.5),
+                        json!("text"),
+                        json!({"a": [true, null]}),
+                    ]
+                    .into(),
+                },
+            }),
+            SocketMessage::Request(protocol::socket::Request {
+                id: "5".to_string(),
+                request: protocol::Request {
+                    identifier: "api/login".to_string(),
+                    parameters: protocol::Parameters::Named(serde_json::Map::from_iter([
+                        ("user".to_string(), json!("name")),
+                        ("remember".to_string(), json!(true)),
+                    ])),
+                },
+            }),
+            SocketMessage::Response(protocol::socket::Response {
+                id: "2".to_string(),
+                response: json!({"nested": {"value": 18446744073709551615u64}}).into(),
+            }),
+            SocketMessage::Response(protocol::socket::Response {
+                id: "3".to_string(),
+                response: SendableError::BadRequest {
+                    message: "Expected a string".to_string(),
+                    path: Some("[0]".to_string()),
+                }
+                .into(),
+            }),
+            SocketMessage::Cancel(protocol::socket::Cancel {
+                id: "4".to_string(),
+            }),
+        ];
+
+        for codec in Codec::ALL {
+            for message in &messages {
+                let encoded = message.encode(codec).unwrap();
+                let decoded = SocketMessage::decode(&encoded, codec).unwrap();
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+            }
+        }
+    }
+
+    #[test]
+    fn message_pack_is_not_json() {
+        let encoded = Codec::MessagePack.encode(&vec![json!("text")]).unwrap();
+
+        assert!(Codec::Json
+            .decode::<Vec<serde_json::Value>>(&encoded)
+            .is_err());
+        assert!(matches!(
+            Codec::MessagePack.decode_request::<Vec<serde_json::Value>>(b"not msgpack"),
+            Err(SendableError::BadRequest { path: None, .. })
+        ));
+    }
+}
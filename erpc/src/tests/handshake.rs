@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::Codec,
+        protocol::socket::{
+            Handshake, SocketMessage, FEATURES, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+        },
+    };
+
+    #[test]
+    fn accepted() {
+        let handshake = Handshake::new(Codec::Json, Some("abc".to_string()));
+
+        let accepted = handshake.negotiate(Codec::Json).unwrap();
+        assert_eq!(accepted.version, PROTOCOL_VERSION);
+        assert_eq!(accepted.features, FEATURES);
+    }
+
+    #[test]
+    fn newer_clients_are_downgraded() {
+        let handshake = Handshake {
+            version: PROTOCOL_VERSION + 1,
+            features: vec!["streams".to_string(), "somethingNew".to_string()],
+            ..Handshake::new(Codec::MessagePack, None)
+        };
+
+        let accepted = handshake.negotiate(Codec::MessagePack).unwrap();
+        assert_eq!(accepted.version, PROTOCOL_VERSION);
+        assert_eq!(accepted.features, vec!["streams".to_string()]);
+    }
+
+    #[test]
+    fn rejected() {
+        let outdated = Handshake {
+            version: MIN_PROTOCOL_VERSION - 1,
+            ..Handshake::new(Codec::Json, None)
+        };
+        let rejected = outdated.negotiate(Codec::Json).unwrap_err();
+        assert_eq!(rejected.version, PROTOCOL_VERSION);
+        assert_eq!(rejected.min_version, MIN_PROTOCOL_VERSION);
+
+        let handshake = Handshake::new(Codec::Json, None);
+        assert!(handshake.negotiate(Codec::MessagePack).is_err());
+    }
+
+    #[test]
+    fn optional_fields() {
+        // clients which know nothing about schema hashes and features can still connect
+        let message = SocketMessage::decode(
+            br#"{"Handshake":{"version":1,"codec":"json"}}"#,
+            Codec::Json,
+        )
+        .unwrap();
+
+        match message {
+            SocketMessage::Handshake(v) => {
+                assert_eq!(v.schema_hash, None);
+                assert!(v.features.is_empty());
+            }
+            v => panic!("Expected a handshake, got {:?}", v),
+        }
+    }
+}
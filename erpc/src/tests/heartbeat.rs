@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        heartbeat::{HeartbeatMonitor, HeartbeatOptions},
+        protocol::socket::SocketMessage,
+    };
+
+    fn monitor() -> HeartbeatMonitor {
+        HeartbeatMonitor::new(HeartbeatOptions {
+            interval: Duration::from_secs(1),
+            max_missed_pongs: 2,
+        })
+    }
+
+    #[test]
+    fn answered_pings() {
+        let mut monitor = monitor();
+
+        for _ in 0..5 {
+            assert!(matches!(monitor.ping(), Some(SocketMessage::Ping(_))));
+            monitor.pong();
+        }
+    }
+
+    #[test]
+    fn missed_pongs() {
+        let mut monitor = monitor();
+
+        let first = match monitor.ping() {
+            Some(SocketMessage::Ping(v)) => v.id,
+            v => panic!("Expected a ping, got {:?}", v),
+        };
+        let second = match monitor.ping() {
+            Some(SocketMessage::Ping(v)) => v.id,
+            v => panic!("Expected a ping, got {:?}", v),
+        };
+        assert_ne!(first, second);
+
+        // both pings are unanswered, so the connection is dead
+        assert!(monitor.ping().is_none());
+
+        // a late pong revives it
+        monitor.pong();
+        assert!(monitor.ping().is_some());
+    }
+}
@@ -0,0 +1,5 @@
+mod codec;
+mod handshake;
+mod heartbeat;
+mod schema;
+mod stream;
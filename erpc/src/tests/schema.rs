@@ -0,0 +1,258 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::{
+        protocol::{Parameters, SendableError},
+        schema::{EndpointSchema, FieldSchema, Schema},
+    };
+
+    fn field(name: &str, optional: bool, schema: Schema) -> FieldSchema {
+        FieldSchema {
+            name: name.to_string(),
+            optional,
+            schema,
+        }
+    }
+
+    fn path(result: Result<(), SendableError>) -> Option<String> {
+        match result {
+            Err(SendableError::BadRequest { path, .. }) => path,
+            v => panic!("Expected a bad request, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn parameter_count() {
+        let schema = EndpointSchema {
+            parameters: vec![
+                field("name", false, Schema::String),
+                field(
+                    "age",
+                    true,
+                    Schema::Integer {
+                        min: -128,
+                        max: 127,
+                    },
+                ),
+            ],
+            types: HashMap::new(),
+            named: false,
+        };
+
+        assert_eq!(schema.validate(&vec![json!("a"), json!(1)].into()), Ok(()));
+        assert_eq!(schema.validate(&vec![json!("a")].into()), Ok(()));
+        assert_eq!(
+            schema.validate(&vec![json!("a"), json!(null)].into()),
+            Ok(())
+        );
+        assert_eq!(
+            path(schema.validate(&Parameters::default())),
+            Some("[0]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!("a"), json!(1), json!(2)].into())),
+            None
+        );
+    }
+
+    #[test]
+    fn primitives() {
+        let schema = EndpointSchema {
+            parameters: vec![
+                field("flag", false, Schema::Boolean),
+                field(
+                    "small",
+                    false,
+                    Schema::Integer {
+                        min: -128,
+                        max: 127,
+                    },
+                ),
+                field("ratio", false, Schema::Float),
+            ],
+            types: HashMap::new(),
+            named: false,
+        };
+
+        assert_eq!(
+            schema.validate(&vec![json!(true), json!(-128), json!(0.5)].into()),
+            Ok(())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!("true"), json!(1), json!(1)].into())),
+            Some("[0]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!(true), json!(128), json!(1)].into())),
+            Some("[1]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!(true), json!(1.5), json!(1)].into())),
+            Some("[1]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!(true), json!(1), json!("1")].into())),
+            Some("[2]".to_string())
+        );
+    }
+
+    #[test]
+    fn arrays_and_enums() {
+        let schema = EndpointSchema {
+            parameters: vec![
+                field(
+                    "position",
+                    false,
+                    Schema::Array {
+                        items: Box::new(Schema::Float),
+                        length: Some(2),
+                    },
+                ),
+                field(
+                    "mode",
+                    false,
+                    Schema::Union {
+                        variants: vec![
+                            Schema::Literal { value: json!("on") },
+                            Schema::Literal { value: json!(1.5) },
+                        ],
+                    },
+                ),
+            ],
+            types: HashMap::new(),
+            named: false,
+        };
+
+        assert_eq!(
+            schema.validate(&vec![json!([1, 2]), json!("on")].into()),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate(&vec![json!([1, 2]), json!(1.5)].into()),
+            Ok(())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!([1]), json!("on")].into())),
+            Some("[0]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!([1, "2"]), json!("on")].into())),
+            Some("[0][1]".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!([1, 2]), json!("off")].into())),
+            Some("[1]".to_string())
+        );
+    }
+
+    #[test]
+    fn custom_types() {
+        let mut types = HashMap::new();
+        types.insert(
+            "Node".to_string(),
+            vec![
+                field("name", false, Schema::String),
+                field(
+                    "children",
+                    true,
+                    Schema::Array {
+                        items: Box::new(Schema::Custom {
+                            name: "Node".to_string(),
+                        }),
+                        length: None,
+                    },
+                ),
+            ],
+        );
+        let schema = EndpointSchema {
+            parameters: vec![field(
+                "tree",
+                false,
+                Schema::Custom {
+                    name: "Node".to_string(),
+                },
+            )],
+            types,
+            named: false,
+        };
+
+        assert_eq!(
+            schema.validate(
+                &vec![json!({ "name": "root", "children": [{ "name": "leaf" }] })].into()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            path(
+                schema
+                    .validate(&vec![json!({ "name": "root", "children": [{ "name": 1 }] })].into())
+            ),
+            Some("[0].children[0].name".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&vec![json!({ "children": [] })].into())),
+            Some("[0].name".to_string())
+        );
+    }
+
+    #[test]
+    fn named_parameters() {
+        let schema = EndpointSchema {
+            parameters: vec![
+                field("user", false, Schema::String),
+                field("remember", true, Schema::Boolean),
+            ],
+            types: HashMap::new(),
+            named: true,
+        };
+        let named = |value: serde_json::Value| match value {
+            serde_json::Value::Object(v) => Parameters::Named(v),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(schema.validate(&named(json!({ "user": "a" }))), Ok(()));
+        assert_eq!(
+            schema.validate(&named(json!({ "remember": true, "user": "a" }))),
+            Ok(())
+        );
+        assert_eq!(
+            path(schema.validate(&named(json!({ "remember": true })))),
+            Some("user".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&named(json!({ "user": 1 })))),
+            Some("user".to_string())
+        );
+        assert_eq!(
+            path(schema.validate(&named(json!({ "user": "a", "other": 1 })))),
+            Some("other".to_string())
+        );
+        // the parameters of named endpoints can't be passed by position and the other way around
+        assert_eq!(path(schema.validate(&vec![json!("a")].into())), None);
+        assert_eq!(
+            path(EndpointSchema::default().validate(&named(json!({ "user": "a" })))),
+            None
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        let schema: EndpointSchema = serde_json::from_value(json!({
+            "parameters": [
+                { "name": "ids", "schema": { "kind": "array", "items": { "kind": "integer", "min": 0, "max": 10 } } }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            schema.parameters[0].schema,
+            Schema::Array {
+                items: Box::new(Schema::Integer { min: 0, max: 10 }),
+                length: None
+            }
+        );
+    }
+}
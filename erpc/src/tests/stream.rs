@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream, FutureExt, StreamExt};
+    use serde_json::json;
+
+    use crate::{
+        protocol::{
+            socket::{SocketMessage, StreamEnd, StreamItem},
+            SendableError,
+        },
+        stream::{StreamConsumers, StreamProducers, STREAM_WINDOW},
+    };
+
+    fn numbers(
+        amount: u32,
+    ) -> stream::Iter<std::vec::IntoIter<Result<serde_json::Value, SendableError>>> {
+        stream::iter((0..amount).map(|i| Ok(json!(i))).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn window_limits_items() {
+        let producers = StreamProducers::default();
+        let (outgoing, sent) = flume::unbounded();
+
+        // nobody grants more items, so the producer stops after the window
+        let producer = producers.start("1".to_string(), numbers(100), outgoing);
+        assert!(producer.now_or_never().is_none());
+
+        assert_eq!(sent.drain().count(), STREAM_WINDOW as usize);
+    }
+
+    #[test]
+    fn full_queue_waits() {
+        let producers = StreamProducers::default();
+        let (outgoing, sent) = flume::bounded(2);
+
+        let mut producer = Box::pin(producers.start("1".to_string(), numbers(10), outgoing));
+        assert!(producer.as_mut().now_or_never().is_none());
+        assert!(sent.is_full());
+
+        // reading from the queue lets the producer continue
+        let mut messages = vec![];
+        while producer.as_mut().now_or_never().is_none() {
+            messages.extend(sent.drain());
+        }
+        messages.extend(sent.drain());
+
+        assert_eq!(messages.len(), 11);
+        assert!(matches!(messages[10], SocketMessage::StreamEnd(_)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let producers = StreamProducers::default();
+        let consumers = StreamConsumers::default();
+        let (producer_outgoing, producer_sent) = flume::unbounded();
+        let (consumer_outgoing, consumer_sent) = flume::unbounded();
+
+        let receiver = consumers.open("1".to_string(), consumer_outgoing);
+        let producer = producers.start("1".to_string(), numbers(100), producer_outgoing);
+
+        let items = block_on(async {
+            let to_consumer = async {
+                while let Ok(message) = producer_sent.recv_async().await {
+                    consumers.dispatch(message);
+                }
+            };
+            let to_producer = async {
+                while let Ok(message) = consumer_sent.recv_async().await {
+                    match message {
+                        SocketMessage::StreamDemand(demand) => producers.demand(demand),
+                        _ => panic!("Expected only demand"),
+                    }
+                }
+            };
+
+            let (items, ..) = futures::join!(
+                receiver.collect::<Vec<_>>(),
+                producer,
+                to_consumer,
+                to_producer
+            );
+            items
+        });
+
+        assert_eq!(items.len(), 100);
+        assert_eq!(items[99], Ok(json!(99)));
+    }
+
+    #[test]
+    fn errors_end_the_stream() {
+        let consumers = StreamConsumers::default();
+        let producers = StreamProducers::default();
+        let (outgoing, sent) = flume::unbounded();
+        let (consumer_outgoing, _consumer_sent) = flume::unbounded();
+
+        let items = stream::iter(vec![
+            Ok(json!(1)),
+            Err(SendableError::Unauthorized),
+            Ok(json!(2)),
+        ]);
+        block_on(producers.start("1".to_string(), items, outgoing));
+
+        let receiver = consumers.open("1".to_string(), consumer_outgoing);
+        for message in sent.drain() {
+            consumers.dispatch(message);
+        }
+
+        let items = block_on(receiver.collect::<Vec<_>>());
+        assert_eq!(items, vec![Ok(json!(1)), Err(SendableError::Unauthorized)]);
+    }
+
+    #[test]
+    fn demand_waits_for_room() {
+        let consumers = StreamConsumers::default();
+        let (outgoing, sent) = flume::bounded(1);
+
+        let mut receiver = consumers.open("1".to_string(), outgoing.clone());
+        for i in 0..STREAM_WINDOW / 2 {
+            consumers.dispatch(SocketMessage::StreamItem(StreamItem {
+                id: "1".to_string(),
+                value: json!(i),
+            }));
+        }
+
+        // the queue of the socket is full when the demand is due
+        outgoing
+            .try_send(SocketMessage::StreamEnd(StreamEnd {
+                id: "other".to_string(),
+            }))
+            .unwrap();
+        for _ in 0..STREAM_WINDOW / 2 {
+            assert!(block_on(receiver.next()).is_some());
+        }
+        assert!(receiver.next().now_or_never().is_none());
+
+        // the producer waits for the demand, so it has to be sent once there is room
+        assert!(matches!(sent.try_recv(), Ok(SocketMessage::StreamEnd(_))));
+        assert!(receiver.next().now_or_never().is_none());
+        match sent.try_recv() {
+            Ok(SocketMessage::StreamDemand(demand)) => assert_eq!(demand.amount, STREAM_WINDOW / 2),
+            v => panic!("Expected a demand, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn drop_cancels() {
+        let consumers = StreamConsumers::default();
+        let (outgoing, sent) = flume::unbounded();
+
+        let receiver = consumers.open("1".to_string(), outgoing);
+        drop(receiver);
+
+        match sent.try_recv().unwrap() {
+            SocketMessage::Cancel(cancel) => assert_eq!(cancel.id, "1"),
+            v => panic!("Expected a cancel, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn closed_socket_fails_stream() {
+        let consumers = StreamConsumers::default();
+        let (outgoing, _sent) = flume::unbounded();
+
+        let mut receiver = consumers.open("1".to_string(), outgoing);
+        consumers.clear();
+
+        assert_eq!(
+            block_on(receiver.next()),
+            Some(Err(SendableError::ConnectionClosed))
+        );
+        assert_eq!(block_on(receiver.next()), None);
+    }
+
+    #[test]
+    fn cancel_stops_producer() {
+        let producers = StreamProducers::default();
+        let (outgoing, sent) = flume::unbounded();
+
+        let producer = producers.start("1".to_string(), stream::pending(), outgoing);
+        producers.cancel("1");
+        block_on(producer);
+
+        assert!(sent.is_empty());
+    }
+
+    #[test]
+    fn typed_items() {
+        let consumers = StreamConsumers::default();
+        let (outgoing, _sent) = flume::unbounded();
+
+        let receiver = consumers.open("1".to_string(), outgoing);
+        for value in [json!(1), json!("two")] {
+            consumers.dispatch(SocketMessage::StreamItem(StreamItem {
+                id: "1".to_string(),
+                value,
+            }));
+        }
+        consumers.dispatch(SocketMessage::StreamEnd(StreamEnd {
+            id: "1".to_string(),
+        }));
+
+        let items = block_on(receiver.typed::<u32>().collect::<Vec<_>>());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0], Ok(1));
+        assert!(items[1].is_err());
+    }
+}
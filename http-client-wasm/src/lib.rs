@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::sync::Arc;
 
@@ -6,41 +7,59 @@ mod target;
 mod tests;
 
 use parking_lot::RwLock;
-pub use server::Server;
+pub use server::{ConnectionState, ReconnectOptions, Server};
 pub use target::Target;
 
 #[derive(Clone, Debug)]
 pub struct Socket {
-    pub requests: flume::Sender<erpc::protocol::socket::Request>,
-    pub responses: flume::Receiver<erpc::protocol::socket::Response>,
+    /**
+       Messages which are sent to the server, these are requests and cancellations of requests
+    */
+    pub requests: flume::Sender<erpc::protocol::socket::SocketMessage>,
+    /**
+       Messages from the server answering the requests, these are responses and stream messages
+    */
+    pub responses: flume::Receiver<erpc::protocol::socket::SocketMessage>,
 }
 
 lazy_static::lazy_static! {
     static ref CREATED_TARGETS: StorageChannel<Target> = StorageChannel::new();
 }
 
+/**
+   How many values a StorageChannel keeps for receivers which are created later, the oldest are dropped beyond it
+*/
+const MAX_STORED_VALUES: usize = 1024;
+
 //TODO make this its own crate
+/**
+   Sends every value to all receivers. Values are stored as well, so receivers which are created later
+   get the values which were sent before them. Only the latest values are kept instead of all of them forever
+*/
 struct StorageChannel<T: Clone + Debug> {
-    stored_values: Arc<RwLock<Vec<T>>>,
+    stored_values: Arc<RwLock<VecDeque<T>>>,
     senders: Arc<RwLock<Vec<flume::Sender<T>>>>,
 }
 
 impl<T: Clone + Debug> StorageChannel<T> {
     fn new() -> Self {
         Self {
-            stored_values: Arc::new(RwLock::new(Vec::new())),
+            stored_values: Arc::new(RwLock::new(VecDeque::new())),
             senders: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
     pub fn send(&self, value: T) -> Result<(), String> {
-        let lock = self.senders.read();
+        let mut senders = self.senders.write();
 
-        for sender in lock.iter() {
-            sender.send(value.to_owned()).unwrap();
-        }
+        // receivers which were dropped would otherwise be kept forever
+        senders.retain(|sender| sender.send(value.to_owned()).is_ok());
 
-        self.stored_values.write().push(value);
+        let mut stored_values = self.stored_values.write();
+        if stored_values.len() >= MAX_STORED_VALUES {
+            stored_values.pop_front();
+        }
+        stored_values.push_back(value);
 
         Ok(())
     }
@@ -48,12 +67,15 @@ impl<T: Clone + Debug> StorageChannel<T> {
     pub fn reciever(&self) -> Result<flume::Receiver<T>, String> {
         let (rx, tx) = flume::unbounded();
 
+        // the lock on the senders makes sure that no value is sent in between
+        let mut senders = self.senders.write();
         for stored_value in self.stored_values.read().iter() {
-            //TODO check if blocking send here is ok
-            rx.send(stored_value.to_owned()).unwrap();
+            if let Err(err) = rx.send(stored_value.to_owned()) {
+                return Err(format!("Could not pass stored value: {err}"));
+            }
         }
 
-        self.senders.write().push(rx);
+        senders.push(rx);
 
         Ok(tx)
     }
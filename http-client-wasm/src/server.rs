@@ -1,23 +1,115 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
 
 //TODO think of some clever error handling
 //TODO this could use some optimizations to improve performance
 //TODO ideally we recycle already existing ws connections to a backend when two frontend server are connecting to the
 // same machine
 
-use erpc::protocol::{self};
+use erpc::{
+    codec::Codec,
+    heartbeat::{HeartbeatMonitor, HeartbeatOptions},
+    protocol::{self, SendableError},
+    schema::EndpointSchema,
+    stream::StreamProducers,
+};
+use futures::{stream, Future, Stream, StreamExt};
 use log::error;
 use parking_lot::RwLock;
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
-use web_sys::{console, ErrorEvent, MessageEvent, WebSocket};
+use web_sys::{console, CloseEvent, ErrorEvent, Event, MessageEvent, WebSocket};
 
-use crate::CREATED_TARGETS;
+use crate::{Socket, Target, CREATED_TARGETS};
 
-type InternalHandler = Box<dyn Fn(protocol::Request) -> protocol::Response>;
+/**
+   Handles calls of endpoints, the response is awaited so handlers can be async
+*/
+type InternalHandler =
+    Box<dyn Fn(protocol::Request) -> Pin<Box<dyn Future<Output = protocol::Response>>>>;
+/**
+   Handles calls of streaming endpoints, errors of the call like invalid parameters are sent as the only item
+*/
+type InternalStreamHandler = Box<
+    dyn Fn(
+        protocol::Request,
+    ) -> Pin<Box<dyn Stream<Item = Result<serde_json::Value, SendableError>>>>,
+>;
+type HandlerMap = Arc<RwLock<HashMap<String, InternalHandler>>>;
+type StreamHandlerMap = Arc<RwLock<HashMap<String, InternalStreamHandler>>>;
+type SchemaMap = Arc<RwLock<HashMap<String, EndpointSchema>>>;
+type StateListeners = Arc<RwLock<Vec<Box<dyn Fn(ConnectionState, &str)>>>>;
+
+/**
+   The state of the websocket connection to a target
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Open,
+    Closed,
+    /**
+       The target refused to talk to this client, e.g. because it is outdated. It is not connected again
+    */
+    Rejected,
+}
+
+impl ConnectionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Open => "open",
+            ConnectionState::Closed => "closed",
+            ConnectionState::Rejected => "rejected",
+        }
+    }
+}
+
+/**
+   Controls how long to wait before a closed websocket is connected again.
+   The delay doubles with every failed attempt up to max_delay. A random jitter prevents all clients from reconnecting at once.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectOptions {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectOptions {
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        // wait at least half of the delay, the rest is random
+        delay / 2 + delay.mul_f64(js_sys::Math::random() / 2.0)
+    }
+}
 
 pub struct Server {
     role: String,
-    handlers: Arc<RwLock<HashMap<String, InternalHandler>>>,
+    handlers: HandlerMap,
+    stream_handlers: StreamHandlerMap,
+    schemas: SchemaMap,
+    reconnect_options: ReconnectOptions,
+    heartbeat: Option<HeartbeatOptions>,
+    state_listeners: StateListeners,
+    codec: Codec,
 }
 
 impl Server {
@@ -25,6 +117,12 @@ impl Server {
         Self {
             role,
             handlers: Arc::new(RwLock::new(HashMap::new())), // TODO what does this warning mean?
+            stream_handlers: Arc::new(RwLock::new(HashMap::new())),
+            schemas: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_options: ReconnectOptions::default(),
+            heartbeat: None,
+            state_listeners: Arc::new(RwLock::new(Vec::new())),
+            codec: Codec::default(),
         }
     }
 
@@ -32,9 +130,58 @@ impl Server {
         self.handlers.write().insert(identifier, handler);
     }
 
+    /**
+       Registers a handler for a streaming endpoint, its items are only pulled as fast as the caller consumes them
+    */
+    pub fn register_raw_stream_handler(
+        &mut self,
+        handler: InternalStreamHandler,
+        identifier: String,
+    ) {
+        self.stream_handlers.write().insert(identifier, handler);
+    }
+
+    /**
+       Sets the schema the parameters of calls to the endpoint are checked against before the handler runs
+    */
+    pub fn set_endpoint_schema(&mut self, identifier: String, schema: EndpointSchema) {
+        self.schemas.write().insert(identifier, schema);
+    }
+
+    pub fn set_reconnect_options(&mut self, reconnect_options: ReconnectOptions) {
+        self.reconnect_options = reconnect_options;
+    }
+
+    /**
+       Pings the targets in the interval and connects again if they stopped answering, no pings are sent if None
+    */
+    pub fn set_heartbeat(&mut self, heartbeat: Option<HeartbeatOptions>) {
+        self.heartbeat = heartbeat;
+    }
+
+    /**
+       Sets the format of the websocket connections, it is negotiated with the target as subprotocol
+    */
+    pub fn set_codec(&mut self, codec: Codec) {
+        self.codec = codec;
+    }
+
+    /**
+       Registers a listener which is called with the new state and the address of the target whenever a websocket connection changes its state
+    */
+    pub fn on_connection_state_change(&mut self, listener: Box<dyn Fn(ConnectionState, &str)>) {
+        self.state_listeners.write().push(listener);
+    }
+
     pub fn run(&self) {
         let role = self.role.clone();
         let handlers = self.handlers.clone();
+        let stream_handlers = self.stream_handlers.clone();
+        let schemas = self.schemas.clone();
+        let reconnect_options = self.reconnect_options;
+        let heartbeat = self.heartbeat;
+        let codec = self.codec;
+        let state_listeners = self.state_listeners.clone();
         wasm_bindgen_futures::spawn_local(async move {
             let reciever = match CREATED_TARGETS.reciever() {
                 Ok(v) => v,
@@ -59,87 +206,505 @@ impl Server {
                     .replace("https://", "wss://");
                 let address = format!("{address}/{}/{role}", protocol::routes::WEBSOCKETS_ROUTE);
 
-                let ws = match WebSocket::new(&address) {
+                connect(Rc::new(Connection {
+                    address,
+                    target,
+                    handlers: handlers.clone(),
+                    stream_handlers: stream_handlers.clone(),
+                    schemas: schemas.clone(),
+                    reconnect_options,
+                    heartbeat,
+                    codec,
+                    state_listeners: state_listeners.clone(),
+                    attempt: Cell::new(0),
+                    legacy: Cell::new(false),
+                    rejected: Cell::new(false),
+                    callbacks: RefCell::new(None),
+                }));
+            }
+        });
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        //TODO
+        Err("Stopping not supported yet".to_string())
+    }
+}
+
+/**
+   The websocket connection to a single target, it is established again whenever it closes
+*/
+struct Connection {
+    address: String,
+    target: Target,
+    handlers: HandlerMap,
+    stream_handlers: StreamHandlerMap,
+    schemas: SchemaMap,
+    reconnect_options: ReconnectOptions,
+    heartbeat: Option<HeartbeatOptions>,
+    codec: Codec,
+    state_listeners: StateListeners,
+    /**
+       Failed attempts since the last successful connection, used to calculate the reconnect delay
+    */
+    attempt: Cell<u32>,
+    /**
+       The last websocket closed before the handshake was answered, the target might predate it.
+       Only the next websocket is opened without a handshake, a close can have other reasons than an outdated target
+    */
+    legacy: Cell<bool>,
+    /**
+       The target rejected the handshake, connecting again won't help
+    */
+    rejected: Cell<bool>,
+    /**
+       The callbacks of the current websocket. They are kept until the next websocket replaces them instead of leaking them
+    */
+    callbacks: RefCell<Option<SocketCallbacks>>,
+}
+
+impl Connection {
+    fn notify(&self, state: ConnectionState) {
+        for listener in self.state_listeners.read().iter() {
+            listener(state, self.target.address());
+        }
+    }
+}
+
+/**
+   How far a websocket got with the handshake
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HandshakePhase {
+    Connecting,
+    Sent,
+    Done,
+}
+
+struct SocketCallbacks {
+    _onopen: Closure<dyn FnMut(Event)>,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut(ErrorEvent)>,
+    _onclose: Closure<dyn FnMut(CloseEvent)>,
+}
+
+fn connect(connection: Rc<Connection>) {
+    connection.notify(ConnectionState::Connecting);
+
+    let ws = match WebSocket::new_with_str(&connection.address, connection.codec.subprotocol()) {
+        Ok(v) => v,
+        Err(err) => {
+            console::error_2(&JsValue::from_str("Could not create WebSocket: "), &err);
+            reconnect(connection);
+            return;
+        }
+    };
+    ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
+    let codec = connection.codec;
+
+    // responses to calls of the target arrive on this socket and are passed to the target
+    let (responses_sender, responses_reciever) =
+        flume::unbounded::<protocol::socket::SocketMessage>();
+
+    // items of the streams the server requested from this socket
+    let producers = StreamProducers::default();
+    // the pings of this socket, a new socket starts without missed pongs
+    let heartbeat = connection
+        .heartbeat
+        .map(|v| Rc::new(RefCell::new(HeartbeatMonitor::new(v))));
+    let (stream_sender, stream_reciever) = flume::unbounded::<protocol::socket::SocketMessage>();
+    let cloned_ws = ws.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        while let Ok(message) = stream_reciever.recv_async().await {
+            let serialized = match message.encode(codec) {
+                Ok(v) => v,
+                Err(err) => {
+                    error!("Could not serialize socket message: {}", err);
+                    continue;
+                }
+            };
+
+            if let Err(err) = cloned_ws.send_with_u8_array(&serialized) {
+                console::error_2(&JsValue::from_str("Could not send on WebSocket: "), &err);
+                return;
+            }
+        }
+    });
+
+    // calls of the target are only sent once the handshake is done
+    let phase = Rc::new(Cell::new(HandshakePhase::Connecting));
+
+    let cloned_connection = connection.clone();
+    let cloned_ws = ws.clone();
+    let cloned_heartbeat = heartbeat.clone();
+    let cloned_producers = producers.clone();
+    let cloned_phase = phase.clone();
+    let cloned_responses_reciever = responses_reciever.clone();
+    let onopen_callback = Closure::<dyn FnMut(_)>::new(move |_: Event| {
+        cloned_connection.attempt.set(0);
+
+        if cloned_connection.legacy.replace(false) {
+            cloned_phase.set(HandshakePhase::Done);
+            open(
+                &cloned_connection,
+                &cloned_ws,
+                cloned_responses_reciever.clone(),
+            );
+        } else {
+            cloned_phase.set(HandshakePhase::Sent);
+            let handshake = protocol::socket::SocketMessage::Handshake(
+                protocol::socket::Handshake::new(codec, cloned_connection.target.schema_hash()),
+            );
+            match handshake.encode(codec) {
+                Ok(serialized) => {
+                    if let Err(err) = cloned_ws.send_with_u8_array(&serialized) {
+                        console::error_2(&JsValue::from_str("Could not send on WebSocket: "), &err);
+                    }
+                }
+                Err(err) => error!("Could not serialize socket message: {}", err),
+            }
+        }
+
+        if let Some(heartbeat) = cloned_heartbeat.clone() {
+            wasm_bindgen_futures::spawn_local(ping(
+                cloned_connection.clone(),
+                cloned_ws.clone(),
+                heartbeat,
+                cloned_producers.clone(),
+            ));
+        }
+    });
+    ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
+
+    let handlers = connection.handlers.clone();
+    let stream_handlers = connection.stream_handlers.clone();
+    let schemas = connection.schemas.clone();
+    let cloned_producers = producers.clone();
+    let cloned_ws = ws.clone();
+    let cloned_heartbeat = heartbeat.clone();
+    let cloned_connection = connection.clone();
+    let cloned_phase = phase.clone();
+    let onmessage_callback = Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
+        let heartbeat = cloned_heartbeat.clone();
+        let connection = cloned_connection.clone();
+        let phase = cloned_phase.clone();
+        let responses_reciever = responses_reciever.clone();
+        let handlers = handlers.clone();
+        let stream_handlers = stream_handlers.clone();
+        let schemas = schemas.clone();
+        let producers = cloned_producers.clone();
+        let stream_sender = stream_sender.clone();
+        let cloned_ws = cloned_ws.clone();
+        let responses_sender = responses_sender.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let message =
+                match protocol::socket::SocketMessage::try_from_wasm_socket_message_event(e, codec)
+                    .await
+                {
                     Ok(v) => v,
                     Err(err) => {
-                        console::error_2(&JsValue::from_str("Could not create WebSocket: "), &err);
+                        error!("Could not convert socket message: {}", err);
                         return;
                     }
                 };
-                ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
-                let cloned_ws = ws.clone();
-                let handlers = handlers.clone();
-                let onmessage_callback = Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
-                    let handlers = handlers.clone();
-                    let cloned_ws = cloned_ws.clone();
-                    wasm_bindgen_futures::spawn_local(async move {
-                        let message =
-                            match protocol::socket::SocketMessage::try_from_wasm_socket_message_event(e)
-                                .await
-                            {
-                                Ok(v) => v,
-                                Err(err) => {
-                                    error!("Could not convert socket message: {}", err);
-                                    return;
-                                }
-                            };
-
-                        let response: protocol::socket::SocketMessage = match message {
-                            protocol::socket::SocketMessage::Request(req) => {
-                                let handlers = handlers.read();
-                                match handlers.get(&req.request.identifier) {
-                                    Some(handler) => {
-                                        let response = handler(req.request);
-                                        protocol::socket::SocketMessage::Response(
-                                            protocol::socket::Response {
-                                                id: req.id,
-                                                response,
-                                            },
-                                        )
-                                    }
-                                    None => {
-                                        error!(
-                                            "Could not find handler for route {}",
-                                            req.request.identifier
-                                        );
-                                        protocol::socket::SocketMessage::Response(
-                                            protocol::socket::Response {
-                                                id: req.id,
-                                                response: protocol::error::SendableError::NotFound
-                                                    .into(),
-                                            },
-                                        )
-                                    }
+
+            let response: protocol::socket::SocketMessage = match message {
+                protocol::socket::SocketMessage::Request(req) => {
+                    let checked = match schemas.read().get(&req.request.identifier) {
+                        Some(schema) => schema.validate(&req.request.parameters),
+                        None => Ok(()),
+                    };
+                    if let Err(err) = checked {
+                        let response = protocol::socket::SocketMessage::Response(
+                            protocol::socket::Response {
+                                id: req.id,
+                                response: err.into(),
+                            },
+                        );
+                        match response.encode(codec) {
+                            Ok(serialized) => {
+                                if let Err(err) = cloned_ws.send_with_u8_array(&serialized) {
+                                    console::error_2(
+                                        &JsValue::from_str("Could not send on WebSocket: "),
+                                        &err,
+                                    );
                                 }
                             }
-                            protocol::socket::SocketMessage::Response(_) => todo!(), //TODO
-                        };
-
-                        println!("Sending response: {:?}", response);
-                        //TODO remove unwrap
-                        let serialized: Vec<u8> = response.try_into().unwrap();
-
-                        println!("serialized: {:?}", serialized);
-
-                        cloned_ws.send_with_u8_array(&serialized).unwrap();
-                    });
-                });
-                // set message event handler on WebSocket
-                ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
-                // forget the callback to keep it alive
-                onmessage_callback.forget();
-
-                let onerror_callback = Closure::<dyn FnMut(_)>::new(move |e: ErrorEvent| {
-                    console::error_2(&JsValue::from_str("websocket error event: "), &e.error());
-                });
-                ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
-                onerror_callback.forget();
+                            Err(err) => error!("Could not serialize socket message: {}", err),
+                        }
+                        return;
+                    }
+
+                    let identifier = req.request.identifier.clone();
+                    // the lock must not be held while the handler runs
+                    let call = handlers
+                        .read()
+                        .get(&identifier)
+                        .map(|handler| handler(req.request));
+                    match call {
+                        Some(call) => {
+                            protocol::socket::SocketMessage::Response(protocol::socket::Response {
+                                id: req.id,
+                                response: call.await,
+                            })
+                        }
+                        None => {
+                            error!("Could not find handler for route {identifier}");
+                            protocol::socket::SocketMessage::Response(protocol::socket::Response {
+                                id: req.id,
+                                response: protocol::error::SendableError::NotFound.into(),
+                            })
+                        }
+                    }
+                }
+                protocol::socket::SocketMessage::StreamRequest(req) => {
+                    let checked = match schemas.read().get(&req.request.identifier) {
+                        Some(schema) => schema.validate(&req.request.parameters),
+                        None => Ok(()),
+                    };
+                    let items = match checked {
+                        Ok(_) => match stream_handlers.read().get(&req.request.identifier) {
+                            Some(handler) => handler(req.request),
+                            None => {
+                                error!(
+                                    "Could not find stream handler for route {}",
+                                    req.request.identifier
+                                );
+                                stream::once(async { Err(SendableError::NotFound) }).boxed_local()
+                            }
+                        },
+                        Err(err) => stream::once(async move { Err(err) }).boxed_local(),
+                    };
+
+                    producers.start(req.id, items, stream_sender).await;
+                    return;
+                }
+                protocol::socket::SocketMessage::StreamDemand(demand) => {
+                    producers.demand(demand);
+                    return;
+                }
+                // calls are answered once their handler finished, only streams can be stopped early
+                protocol::socket::SocketMessage::Cancel(cancel) => {
+                    producers.cancel(&cancel.id);
+                    return;
+                }
+                protocol::socket::SocketMessage::Ping(ping) => {
+                    protocol::socket::SocketMessage::Pong(ping)
+                }
+                protocol::socket::SocketMessage::Pong(_) => {
+                    if let Some(heartbeat) = heartbeat {
+                        heartbeat.borrow_mut().pong();
+                    }
+                    return;
+                }
+                protocol::socket::SocketMessage::HandshakeAccepted(accepted) => {
+                    phase.set(HandshakePhase::Done);
+                    if accepted.version < protocol::socket::MIN_PROTOCOL_VERSION {
+                        error!(
+                            "{} only speaks protocol version {}, this client needs at least version {}",
+                            connection.address,
+                            accepted.version,
+                            protocol::socket::MIN_PROTOCOL_VERSION
+                        );
+                        connection.rejected.set(true);
+                        if let Err(err) = cloned_ws
+                            .close_with_code_and_reason(1000, "Unsupported protocol version")
+                        {
+                            console::error_2(
+                                &JsValue::from_str("Could not close WebSocket: "),
+                                &err,
+                            );
+                        }
+                        return;
+                    }
+
+                    open(&connection, &cloned_ws, responses_reciever);
+                    return;
+                }
+                // the target closes the websocket right after rejecting it
+                protocol::socket::SocketMessage::HandshakeRejected(rejected) => {
+                    phase.set(HandshakePhase::Done);
+                    error!(
+                        "{} rejected the connection, it supports protocol versions {} to {}: {}",
+                        connection.address, rejected.min_version, rejected.version, rejected.reason
+                    );
+                    connection.rejected.set(true);
+                    return;
+                }
+                // only clients send handshakes
+                protocol::socket::SocketMessage::Handshake(_) => return,
+                // responses and stream messages answer calls of the target
+                message => {
+                    if let Err(err) = responses_sender.send(message) {
+                        error!("Could not pass response to target: {}", err);
+                    }
+                    return;
+                }
+            };
+
+            let serialized = match response.encode(codec) {
+                Ok(v) => v,
+                Err(err) => {
+                    error!("Could not serialize socket message: {}", err);
+                    return;
+                }
+            };
+
+            // the websocket might have closed while the handler ran
+            if let Err(err) = cloned_ws.send_with_u8_array(&serialized) {
+                console::error_2(&JsValue::from_str("Could not send on WebSocket: "), &err);
             }
         });
+    });
+    ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+
+    let onerror_callback = Closure::<dyn FnMut(_)>::new(move |e: ErrorEvent| {
+        console::error_2(&JsValue::from_str("websocket error event: "), &e.error());
+    });
+    ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
+
+    let cloned_connection = connection.clone();
+    let cloned_ws = ws.clone();
+    let onclose_callback = Closure::<dyn FnMut(_)>::new(move |e: CloseEvent| {
+        if e.code() == protocol::socket::HANDSHAKE_REJECTED_CLOSE_CODE {
+            cloned_connection.rejected.set(true);
+        } else if phase.get() == HandshakePhase::Sent {
+            // targets which predate the handshake close the websocket because they can't read it
+            error!(
+                "{} closed the connection before answering the handshake, trying once without one",
+                cloned_connection.address
+            );
+            cloned_connection.legacy.set(true);
+        }
+        closed(cloned_connection.clone(), &cloned_ws, &producers);
+    });
+    ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
+
+    connection.callbacks.replace(Some(SocketCallbacks {
+        _onopen: onopen_callback,
+        _onmessage: onmessage_callback,
+        _onerror: onerror_callback,
+        _onclose: onclose_callback,
+    }));
+}
+
+/**
+   Passes the websocket to the target once both sides agreed on the protocol
+*/
+fn open(
+    connection: &Connection,
+    ws: &WebSocket,
+    responses: flume::Receiver<protocol::socket::SocketMessage>,
+) {
+    connection.notify(ConnectionState::Open);
+
+    let (requests_sender, requests_reciever) =
+        flume::unbounded::<protocol::socket::SocketMessage>();
+    connection.target.set_socket(Socket {
+        requests: requests_sender,
+        responses,
+    });
+
+    // forwards the calls of the target until the target drops the socket
+    let ws = ws.clone();
+    let codec = connection.codec;
+    wasm_bindgen_futures::spawn_local(async move {
+        while let Ok(message) = requests_reciever.recv_async().await {
+            let serialized = match message.encode(codec) {
+                Ok(v) => v,
+                Err(err) => {
+                    error!("Could not serialize socket message: {}", err);
+                    continue;
+                }
+            };
+
+            if let Err(err) = ws.send_with_u8_array(&serialized) {
+                console::error_2(&JsValue::from_str("Could not send on WebSocket: "), &err);
+                return;
+            }
+        }
+    });
+}
+
+/**
+   Cleans up after the websocket closed and connects again, unless the target rejected this client
+*/
+fn closed(connection: Rc<Connection>, ws: &WebSocket, producers: &StreamProducers) {
+    // the callbacks must not be called anymore since they are dropped with the next websocket
+    ws.set_onopen(None);
+    ws.set_onmessage(None);
+    ws.set_onerror(None);
+    ws.set_onclose(None);
+
+    // a rejected connection is not established again, so nothing would replay its calls
+    connection.target.close_socket(!connection.rejected.get());
+    producers.clear();
+    if connection.rejected.get() {
+        connection.notify(ConnectionState::Rejected);
+        return;
     }
+    connection.notify(ConnectionState::Closed);
+    reconnect(connection);
+}
 
-    pub fn stop(&self) -> Result<(), String> {
-        //TODO
-        Err("Stopping not supported yet".to_string())
+/**
+   Pings the target until the websocket closes.
+   Browsers might not notice a dead connection for a long time, so it is closed once too many pongs are missing
+*/
+async fn ping(
+    connection: Rc<Connection>,
+    ws: WebSocket,
+    heartbeat: Rc<RefCell<HeartbeatMonitor>>,
+    producers: StreamProducers,
+) {
+    loop {
+        let interval = heartbeat.borrow().interval();
+        gloo_timers::future::sleep(interval).await;
+
+        if ws.ready_state() != WebSocket::OPEN {
+            return;
+        }
+
+        let ping = heartbeat.borrow_mut().ping();
+        let ping = match ping {
+            Some(v) => v,
+            None => {
+                error!(
+                    "{} did not answer the heartbeat, reconnecting",
+                    connection.address
+                );
+                if let Err(err) = ws.close_with_code_and_reason(1000, "Heartbeat timeout") {
+                    console::error_2(&JsValue::from_str("Could not close WebSocket: "), &err);
+                }
+                // the close event might take long for a dead connection, so it is not waited for
+                closed(connection, &ws, &producers);
+                return;
+            }
+        };
+
+        let serialized = match ping.encode(connection.codec) {
+            Ok(v) => v,
+            Err(err) => {
+                error!("Could not serialize socket message: {}", err);
+                continue;
+            }
+        };
+        if let Err(err) = ws.send_with_u8_array(&serialized) {
+            console::error_2(&JsValue::from_str("Could not send on WebSocket: "), &err);
+        }
     }
 }
+
+/**
+   Connects again after the backoff delay
+*/
+fn reconnect(connection: Rc<Connection>) {
+    let attempt = connection.attempt.get();
+    let delay = connection.reconnect_options.delay(attempt);
+    connection.attempt.set(attempt.saturating_add(1));
+
+    wasm_bindgen_futures::spawn_local(async move {
+        gloo_timers::future::sleep(delay).await;
+        connect(connection);
+    });
+}
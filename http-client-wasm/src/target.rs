@@ -1,25 +1,67 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use erpc::{
-    protocol::{self, SendableError},
+    codec::Codec,
+    protocol::{
+        self,
+        socket::{RelayRequest, SocketMessage},
+        SendableError,
+    },
+    stream::{StreamConsumers, StreamReceiver},
     target::TargetType,
 };
-use futures::channel::oneshot;
+use futures::{channel::oneshot, future::Either, Future};
 use log::error;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, RequestMode, Response, console};
 
 use crate::{Socket, CREATED_TARGETS};
 
+/**
+   Requests which were sent over the socket and wait for a response. The request is kept to be able to re-send it
+*/
+type OpenRequests =
+    Arc<Mutex<HashMap<String, (oneshot::Sender<protocol::Response>, protocol::Request)>>>;
+
 #[derive(Debug, Clone)]
 pub struct Target {
     address: String,
     target_type: TargetType,
-    socket: Option<Socket>,
+    socket: Arc<RwLock<Option<Socket>>>,
     //TODO check if this is optimal
-    open_socket_requests: Arc<Mutex<HashMap<String, oneshot::Sender<protocol::Response>>>>,
+    open_socket_requests: OpenRequests,
+    /**
+       The timeout which is used for calls without their own timeout. None waits forever
+    */
+    timeout: Arc<RwLock<Option<Duration>>>,
+    /**
+       Whether open requests are re-sent on the next socket after the connection was lost instead of failing
+    */
+    replay_requests: Arc<RwLock<bool>>,
+    /**
+       The format of http requests to the target, responses are read in the format the target answers with
+    */
+    codec: Arc<RwLock<Codec>>,
+    /**
+       The hash of the .erpc sources the target was generated from, it is sent with every http request and the handshake of the websocket
+    */
+    schema_hash: Arc<RwLock<Option<String>>>,
+    /**
+       Streams which were requested over the socket and still receive items
+    */
+    open_streams: StreamConsumers,
+    /**
+       The browser which calls of a browser target are relayed to by the server the socket is connected to
+    */
+    relay_destination: Arc<RwLock<Option<RelayDestination>>>,
+}
+
+#[derive(Debug, Clone)]
+struct RelayDestination {
+    role: String,
+    socket_id: String,
 }
 
 //TODO remove unwraps
@@ -32,111 +74,321 @@ impl Target {
         let t = Target {
             address,
             target_type,
-            socket: None,
+            socket: Arc::new(RwLock::new(None)),
             open_socket_requests: Arc::new(Mutex::new(HashMap::new())),
+            timeout: Arc::new(RwLock::new(None)),
+            replay_requests: Arc::new(RwLock::new(false)),
+            codec: Arc::new(RwLock::new(Codec::default())),
+            schema_hash: Arc::new(RwLock::new(None)),
+            open_streams: StreamConsumers::default(),
+            relay_destination: Arc::new(RwLock::new(None)),
         };
         CREATED_TARGETS.send(t.clone()).unwrap();
         t
     }
 
+    /**
+       Sets the timeout for all calls on this target and its clones. None waits forever
+    */
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        *self.timeout.write() = timeout;
+    }
+
+    /**
+       Sets whether open requests are re-sent when the connection was lost and is established again.
+       Otherwise they fail with a ConnectionClosed error.
+    */
+    pub fn set_replay_requests(&self, replay_requests: bool) {
+        *self.replay_requests.write() = replay_requests;
+    }
+
+    /**
+       Sets the format of http requests to the target for this target and its clones.
+       Calls over websockets use the format of the socket instead.
+    */
+    pub fn set_codec(&self, codec: Codec) {
+        *self.codec.write() = codec;
+    }
+
+    /**
+       Sets the schema hash for this target and its clones, the generated code calls this.
+       The server reports calls of targets which were generated from other .erpc sources than itself
+    */
+    pub fn set_schema_hash(&self, schema_hash: Option<String>) {
+        *self.schema_hash.write() = schema_hash;
+    }
+
+    pub fn schema_hash(&self) -> Option<String> {
+        self.schema_hash.read().clone()
+    }
+
+    /**
+       Sets the browser which is called by this browser target. The address of the target is the server which relays
+       the calls, the socket id is the one the server assigned to the connection of the destination.
+    */
+    pub fn set_relay_destination(&self, role: String, socket_id: String) {
+        self.relay_destination
+            .write()
+            .replace(RelayDestination { role, socket_id });
+    }
+
     pub async fn call(&self, request: protocol::Request) -> protocol::Response {
+        let timeout = *self.timeout.read();
+        self.call_with_timeout(request, timeout).await
+    }
+
+    /**
+       Calls the target and returns a Timeout error if no response arrived within the timeout.
+       Timed out requests over websockets are cancelled on the remote side.
+    */
+    pub async fn call_with_timeout(
+        &self,
+        request: protocol::Request,
+        timeout: Option<Duration>,
+    ) -> protocol::Response {
+        // cloned so the lock is not held across the await points below
+        let socket = self.socket.read().clone();
         match self.target_type {
-            TargetType::HttpServer => match &self.socket {
+            TargetType::HttpServer | TargetType::Browser => match socket {
                 Some(socket) => {
-                    let request = protocol::socket::Request {
-                        id: nanoid::nanoid!(),
-                        request,
+                    let id = nanoid::nanoid!();
+                    let message = match self.socket_request(&id, request.clone()) {
+                        Ok(v) => v,
+                        Err(err) => return err.into(),
                     };
 
                     let (sender, reciever) = oneshot::channel();
                     self.open_socket_requests
                         .lock()
-                        .insert(request.id.clone(), sender);
+                        .insert(id.clone(), (sender, request));
 
-                    socket.requests.send(request).unwrap();
+                    if let Err(err) = socket.requests.send(message) {
+                        self.open_socket_requests.lock().remove(&id);
+                        error!("Could not send request on socket: {err}");
+                        return SendableError::ConnectionClosed.into();
+                    }
 
-                    match reciever.await {
-                        Ok(v) => v,
-                        Err(err) => {
-                            SendableError::from(format!("Recieving response cancelled: {}", err)).into()
+                    match with_timeout(reciever, timeout).await {
+                        Some(Ok(v)) => v,
+                        Some(Err(err)) => {
+                            error!("Recieving response cancelled: {}", err);
+                            SendableError::ConnectionClosed.into()
+                        }
+                        None => {
+                            // nobody waits for the response anymore, so the entry would never be removed
+                            self.open_socket_requests.lock().remove(&id);
+
+                            if let Err(err) = socket.requests.send(
+                                protocol::socket::SocketMessage::Cancel(protocol::socket::Cancel {
+                                    id,
+                                }),
+                            ) {
+                                error!("Could not send cancel on socket: {err}");
+                            }
+
+                            SendableError::Timeout.into()
                         }
                     }
                 }
-                None => {
-                    let mut opts = RequestInit::new();
-                    opts.method("POST");
-                    opts.mode(RequestMode::Cors);
+                None if matches!(self.target_type, TargetType::Browser) => {
+                    // browsers can only be reached through the socket to the relaying server
+                    error!(
+                        "No relay available, the socket to {} is not connected",
+                        self.address
+                    );
+                    SendableError::ConnectionClosed.into()
+                }
+                None => match with_timeout(self.fetch(request), timeout).await {
+                    Some(v) => v,
+                    None => SendableError::Timeout.into(),
+                },
+            },
+            TargetType::Loopback => {
+                SendableError::from("Loopback targets are not supported in browsers").into()
+            }
+        }
+    }
 
-                    let stringified = match serde_json::to_string(&request.parameters) {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    };
+    /**
+       Calls a streaming endpoint of the target. Streams need a websocket, calls before the socket is connected fail.
+       Dropping the receiver before the stream ended cancels it on the remote side.
+    */
+    pub fn call_stream(&self, request: protocol::Request) -> Result<StreamReceiver, SendableError> {
+        let requests = match self.target_type {
+            TargetType::HttpServer => match self.socket.read().as_ref() {
+                Some(v) => v.requests.clone(),
+                None => return Err(SendableError::from("Socket not set for this target")),
+            },
+            TargetType::Browser => {
+                return Err(SendableError::from(
+                    "Streams are not relayed between browsers",
+                ))
+            }
+            TargetType::Loopback => {
+                return Err(SendableError::from(
+                    "Loopback targets are not supported in browsers",
+                ))
+            }
+        };
 
-                    opts.body(Some(&JsValue::from_str(stringified.as_str())));
+        let id = nanoid::nanoid!();
+        let items = self.open_streams.open(id.clone(), requests.clone());
 
-                    let url = format!(
-                        "{}/{}/{}",
-                        self.address,
-                        protocol::routes::HANDLERS_ROUTE,
-                        request.identifier
-                    );
-                    let request = match Request::new_with_str_and_init(&url, &opts) {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    };
+        match requests.send(SocketMessage::StreamRequest(protocol::socket::Request {
+            id,
+            request,
+        })) {
+            Ok(_) => Ok(items),
+            Err(err) => {
+                error!("Could not send stream request on socket: {err}");
+                Err(SendableError::ConnectionClosed)
+            }
+        }
+    }
 
-                    match request.headers().set("Content-Type", "application/json") {
-                        Ok(_) => {},
-                        Err(err) => return SendableError::from(format!("Could not add header to request: {:#?}", err)).into(),
-                    };
+    /**
+       Wraps the request for the socket, requests of browser targets are addressed to the relay destination
+    */
+    fn socket_request(
+        &self,
+        id: &str,
+        request: protocol::Request,
+    ) -> Result<SocketMessage, SendableError> {
+        match self.target_type {
+            TargetType::Browser => match self.relay_destination.read().as_ref() {
+                Some(destination) => Ok(SocketMessage::RelayRequest(RelayRequest {
+                    id: id.to_string(),
+                    role: destination.role.clone(),
+                    socket_id: destination.socket_id.clone(),
+                    request,
+                })),
+                None => Err(SendableError::from(
+                    "No relay destination set for this browser target",
+                )),
+            },
+            _ => Ok(SocketMessage::Request(
+                protocol::socket::Request::from_request(request, id),
+            )),
+        }
+    }
 
-                    let window = match web_sys::window() {
-                        Some(v) => v,
-                        None => return SendableError::from("Could not access window object").into(),
-                    };
-                    let resp_value = match JsFuture::from(window.fetch_with_request(&request)).await
-                    {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    };
+    async fn fetch(&self, request: protocol::Request) -> protocol::Response {
+        let mut opts = RequestInit::new();
+        opts.method("POST");
+        opts.mode(RequestMode::Cors);
 
-                    let resp: Response = match resp_value.dyn_into() {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    };
+        let codec = *self.codec.read();
+        let body = match codec.encode(&request.parameters) {
+            Ok(v) => v,
+            Err(err) => return err.into(),
+        };
 
-                    let body = match JsFuture::from(match resp.array_buffer() {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    })
-                    .await
-                    {
-                        Ok(v) => v,
-                        Err(err) => return SendableError::from(err).into(),
-                    };
-                    let body: serde_json::Value =
-                        match serde_json::from_slice(&js_sys::Uint8Array::new(&body).to_vec()) {
-                            Ok(v) => v,
-                            Err(err) => return SendableError::from(err).into(),
-                        };
+        let body: JsValue = js_sys::Uint8Array::from(body.as_slice()).into();
+        opts.body(Some(&body));
 
-                    protocol::Response { body: Ok(body) }
-                }
-            },
-            TargetType::Browser => {
-                panic!("Browser to browser is not supported yet")
+        let url = format!(
+            "{}/{}/{}",
+            self.address,
+            protocol::routes::HANDLERS_ROUTE,
+            request.identifier
+        );
+        let request = match Request::new_with_str_and_init(&url, &opts) {
+            Ok(v) => v,
+            Err(err) => return SendableError::from(err).into(),
+        };
+
+        match request.headers().set("Content-Type", codec.content_type()) {
+            Ok(_) => {},
+            Err(err) => return SendableError::from(format!("Could not add header to request: {:#?}", err)).into(),
+        };
+        if let Some(schema_hash) = self.schema_hash.read().as_ref() {
+            if let Err(err) = request
+                .headers()
+                .set(protocol::routes::SCHEMA_HASH_HEADER, schema_hash)
+            {
+                return SendableError::from(format!("Could not add header to request: {:#?}", err))
+                    .into();
             }
         }
+
+        let window = match web_sys::window() {
+            Some(v) => v,
+            None => return SendableError::from("Could not access window object").into(),
+        };
+        let resp_value = match JsFuture::from(window.fetch_with_request(&request)).await
+        {
+            Ok(v) => v,
+            Err(err) => return SendableError::from(err).into(),
+        };
+
+        let resp: Response = match resp_value.dyn_into() {
+            Ok(v) => v,
+            Err(err) => return SendableError::from(err).into(),
+        };
+
+        // responses without a known content type, e.g. from proxies, are read as json
+        let response_codec = match resp.headers().get("Content-Type") {
+            Ok(Some(v)) => Codec::from_content_type(&v).unwrap_or_default(),
+            _ => Codec::default(),
+        };
+
+        let body = match JsFuture::from(match resp.array_buffer() {
+            Ok(v) => v,
+            Err(err) => return SendableError::from(err).into(),
+        })
+        .await
+        {
+            Ok(v) => v,
+            Err(err) => return SendableError::from(err).into(),
+        };
+        let body = js_sys::Uint8Array::new(&body).to_vec();
+
+        if !resp.ok() {
+            // the server sends the error in the body, the status code is the fallback
+            return response_codec
+                .decode::<SendableError>(&body)
+                .unwrap_or_else(|_| SendableError::from_status_code(resp.status()))
+                .into();
+        }
+
+        protocol::Response {
+            body: response_codec.decode(&body),
+        }
     }
 
-    pub fn set_socket(&mut self, socket: Socket) {
+    /**
+       Sets the socket which is used for calls. Open requests of a previous socket are re-sent if enabled
+    */
+    pub fn set_socket(&self, socket: Socket) {
+        for (id, (_, request)) in self.open_socket_requests.lock().iter() {
+            let message = match self.socket_request(id, request.clone()) {
+                Ok(v) => v,
+                Err(err) => {
+                    error!("Could not re-send request {id}: {err}");
+                    continue;
+                }
+            };
+            if let Err(err) = socket.requests.send(message) {
+                error!("Could not re-send request {id}: {err}");
+            }
+        }
+
         let responses = socket.responses.clone();
-        self.socket = Some(socket);
+        self.socket.write().replace(socket);
         let open_requests = self.open_socket_requests.clone();
+        let open_streams = self.open_streams.clone();
         wasm_bindgen_futures::spawn_local(async move {
-            while let Ok(response) = responses.recv_async().await {
+            while let Ok(message) = responses.recv_async().await {
+                let response = match message {
+                    SocketMessage::Response(v) => v,
+                    message => {
+                        open_streams.dispatch(message);
+                        continue;
+                    }
+                };
+
                 let mut open_requests = open_requests.lock();
-                let responder = match open_requests.remove(&response.id) {
+                let (responder, _) = match open_requests.remove(&response.id) {
                     Some(v) => v,
                     None => {
                         error!(
@@ -157,7 +409,40 @@ impl Target {
         });
     }
 
+    /**
+       Removes the socket after its connection was lost, calls are made via http until a new socket is set.
+       Open requests fail with ConnectionClosed unless they are re-sent on the next socket, which needs a reconnect.
+    */
+    pub fn close_socket(&self, reconnecting: bool) {
+        self.socket.write().take();
+
+        if !reconnecting || !*self.replay_requests.read() {
+            // dropping the senders fails the requests
+            self.open_socket_requests.lock().clear();
+        }
+
+        // streams can't be resumed on another socket, dropping their senders fails them
+        self.open_streams.clear();
+    }
+
     pub fn address(&self) -> &str {
         &self.address
     }
 }
+
+/**
+   Resolves to None if the future did not complete within the timeout
+*/
+async fn with_timeout<F: Future>(future: F, timeout: Option<Duration>) -> Option<F::Output> {
+    let timeout = match timeout {
+        Some(v) => v,
+        None => return Some(future.await),
+    };
+
+    let sleep = gloo_timers::future::sleep(timeout);
+    futures::pin_mut!(future, sleep);
+    match futures::future::select(future, sleep).await {
+        Either::Left((v, _)) => Some(v),
+        Either::Right(_) => None,
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Buf, Bytes};
+use erpc::{
+    codec::Codec,
+    protocol::{self, SendableError},
+};
+use futures_util::Future;
+use http_body::{Body, Full};
+
+use crate::{context::RequestContext, server::Server};
+
+/**
+   The response to a call which was dispatched without the server of easy-rpc, e.g. from another http framework
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    fn new(response: protocol::Response, codec: Codec) -> Self {
+        let (status, encoded) = match &response.body {
+            Ok(v) => (200, codec.encode(v)),
+            // the body carries the variant so clients can reconstruct the error
+            Err(err) => (err.status_code().as_u16(), codec.encode(err)),
+        };
+
+        match encoded {
+            Ok(body) => RawResponse {
+                status,
+                content_type: codec.content_type(),
+                body,
+            },
+            Err(_) => RawResponse {
+                status: 500,
+                content_type: codec.content_type(),
+                body: vec![],
+            },
+        }
+    }
+}
+
+impl From<SendableError> for RawResponse {
+    /**
+       The response for errors which happen before the format of the request is known, it is encoded as json
+    */
+    fn from(err: SendableError) -> Self {
+        RawResponse::new(err.into(), Codec::default())
+    }
+}
+
+impl Server {
+    /**
+       Calls the endpoint with the parameters encoded in the body, like a POST to the handler route would.
+       This lets other http frameworks serve the endpoints, the response is encoded in the format of the request.
+       The content type is the Content-Type header of the request, bodies without one are read as json.
+    */
+    pub async fn dispatch(
+        &self,
+        identifier: String,
+        content_type: Option<&str>,
+        body: &[u8],
+        context: RequestContext,
+    ) -> RawResponse {
+        let codec = match content_type {
+            Some(content_type) => match Codec::from_content_type(content_type) {
+                Some(v) => v,
+                None => {
+                    // the caller sent a format we can't read, so it is answered in the default format
+                    let err = SendableError::BadRequest {
+                        message: format!("Unsupported content type {}", content_type),
+                        path: None,
+                    };
+                    return err.into();
+                }
+            },
+            None => Codec::default(),
+        };
+
+        // the body was read already, but it is limited like the one of requests to the handler route
+        if body.len() > self.max_body_size() {
+            return RawResponse::new(body_too_large(self.max_body_size()).into(), codec);
+        }
+
+        let dispatcher = self.dispatcher();
+        let context = match dispatcher.authenticate(context).await {
+            Ok(v) => v,
+            Err(err) => return RawResponse::new(err.into(), codec),
+        };
+
+        let parameters = match codec.decode_request(body) {
+            Ok(v) => v,
+            Err(err) => return RawResponse::new(err.into(), codec),
+        };
+
+        let response = dispatcher
+            .call(
+                protocol::Request {
+                    identifier,
+                    parameters,
+                },
+                context,
+            )
+            .await;
+        RawResponse::new(response, codec)
+    }
+
+    /**
+       The identifier of the endpoint a request path calls, None if the path is not below the handler route.
+       The route prefix of the server is part of the handler route.
+    */
+    pub fn identifier_from_path(&self, path: &str) -> Option<String> {
+        // query strings are not part of the identifier
+        let path = path.split('?').next().unwrap_or_default();
+        let route = format!(
+            "{}{}/",
+            self.route_prefix(),
+            protocol::routes::HANDLERS_ROUTE
+        );
+
+        match path.trim_start_matches('/').strip_prefix(&route) {
+            Some(v) if !v.is_empty() => Some(v.to_string()),
+            _ => None,
+        }
+    }
+
+    /**
+       A tower service serving the handler route of this server, e.g. to nest it into an axum router or serve it with hyper.
+       Websockets are not available through it, they need the server of easy-rpc.
+    */
+    pub fn service(&self) -> ErpcService {
+        ErpcService {
+            server: self.clone(),
+        }
+    }
+}
+
+/**
+   Serves the endpoints of a server as tower service, see Server::service
+*/
+#[derive(Clone)]
+pub struct ErpcService {
+    server: Server,
+}
+
+impl<B> tower_service::Service<http::Request<B>> for ErpcService
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Display,
+{
+    type Response = http::Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let server = self.server.clone();
+
+        Box::pin(async move {
+            let response = match server.identifier_from_path(request.uri().path()) {
+                Some(identifier) if request.method() == http::Method::POST => {
+                    let context = RequestContext::from_headers(
+                        request
+                            .headers()
+                            .iter()
+                            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                    );
+                    let content_type = request
+                        .headers()
+                        .get(http::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.to_string());
+
+                    match read_body(request.into_body(), server.max_body_size()).await {
+                        Ok(body) => {
+                            server
+                                .dispatch(identifier, content_type.as_deref(), &body, context)
+                                .await
+                        }
+                        Err(err) => err.into(),
+                    }
+                }
+                _ => SendableError::NotFound.into(),
+            };
+
+            let mut http_response = http::Response::new(Full::new(Bytes::from(response.body)));
+            *http_response.status_mut() = http::StatusCode::from_u16(response.status)
+                .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+            http_response.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static(response.content_type),
+            );
+            Ok(http_response)
+        })
+    }
+}
+
+/**
+   Buffers the body, it is rejected as soon as it grows larger than max_size bytes
+*/
+async fn read_body<B>(body: B, max_size: usize) -> Result<Vec<u8>, SendableError>
+where
+    B: Body,
+    B::Error: Display,
+{
+    let mut body = Box::pin(body);
+    let mut bytes = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let mut chunk = chunk.map_err(|err| SendableError::BadRequest {
+            message: format!("Could not read request: {}", err),
+            path: None,
+        })?;
+        if bytes.len() + chunk.remaining() > max_size {
+            return Err(body_too_large(max_size));
+        }
+        while chunk.has_remaining() {
+            let part = chunk.chunk();
+            bytes.extend_from_slice(part);
+            let length = part.len();
+            chunk.advance(length);
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn body_too_large(max_size: usize) -> SendableError {
+    SendableError::BadRequest {
+        message: format!("The request body is larger than {max_size} bytes"),
+        path: None,
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use erpc::protocol::SendableError;
+use futures_util::Future;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::server::format_socket_address;
+
+tokio::task_local! {
+    static CURRENT_CONTEXT: RequestContext;
+}
+
+/**
+   Decides if a request or socket connection is allowed. It resolves to the auth data which is stored in the context,
+   an error rejects the request before any middleware or handler runs.
+*/
+pub type InternalAuthenticator = Box<
+    dyn Fn(
+            RequestContext,
+        )
+            -> Pin<Box<dyn Future<Output = Result<serde_json::Value, SendableError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/**
+   Information about the caller of an endpoint.
+   Calls over a websocket share the context which was created when the socket connected.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestContext {
+    /**
+       The headers of the http request or the websocket upgrade request, names are lowercase
+    */
+    pub headers: HashMap<String, String>,
+    pub remote_address: Option<String>,
+    /**
+       Set for calls over a websocket, the id is unique per connection
+    */
+    pub socket_id: Option<String>,
+    /**
+       The role the browser connected with, set for calls over a websocket
+    */
+    pub role: Option<String>,
+    /**
+       The data the authenticator resolved to, null if no authenticator is set
+    */
+    pub auth: serde_json::Value,
+    /**
+       The values the middleware of the endpoint stored in the context of the call, empty before the middleware ran
+    */
+    #[serde(default)]
+    pub middleware: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RequestContext {
+    pub(crate) fn from_salvo_request(req: &salvo::Request) -> Self {
+        Self {
+            remote_address: format_socket_address(req.remote_addr()),
+            ..Self::from_headers(
+                req.headers()
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_bytes())),
+            )
+        }
+    }
+
+    /**
+       Creates the context of a request with the headers, header values which are not utf-8 are left out
+    */
+    pub(crate) fn from_headers<'a>(headers: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Self {
+        let mut map: HashMap<String, String> = HashMap::new();
+        for (name, value) in headers {
+            let value = match std::str::from_utf8(value) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            // repeated headers are combined like http allows it
+            map.entry(name.to_lowercase())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+
+        Self {
+            headers: map,
+            ..Default::default()
+        }
+    }
+
+    /**
+       The context of the call the current handler is running for.
+       It is available while the future of a typed handler runs, not while the items of a returned stream are produced.
+    */
+    pub fn current() -> Option<RequestContext> {
+        CURRENT_CONTEXT.try_with(|v| v.clone()).ok()
+    }
+
+    /**
+       Runs the future with this context as the current one
+    */
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_CONTEXT.scope(self, future).await
+    }
+}
+
+/**
+   Holds the authenticator of a server, without one all requests are allowed
+*/
+#[derive(Clone, Default)]
+pub(crate) struct Authenticator {
+    authenticator: Arc<RwLock<Option<InternalAuthenticator>>>,
+}
+
+impl Authenticator {
+    pub fn set(&self, authenticator: InternalAuthenticator) {
+        self.authenticator.write().replace(authenticator);
+    }
+
+    /**
+       Fills the auth data of the context or returns the error the request is rejected with
+    */
+    pub async fn authenticate(
+        &self,
+        mut context: RequestContext,
+    ) -> Result<RequestContext, SendableError> {
+        let future = match self.authenticator.read().as_ref() {
+            Some(authenticator) => authenticator(context.clone()),
+            None => return Ok(context),
+        };
+
+        context.auth = future.await?;
+        Ok(context)
+    }
+}
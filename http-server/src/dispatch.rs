@@ -0,0 +1,104 @@
+use std::fmt::Debug;
+
+use erpc::{
+    protocol::{self, SendableError},
+    stream::ItemStream,
+};
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    context::{Authenticator, RequestContext},
+    middleware::MiddlewareRegistry,
+    schema_hash::SchemaHash,
+    server::{HandlerMap, SchemaMap, StreamHandlerMap},
+};
+
+/**
+   Runs calls through the schema validation, the middleware and the handler of their endpoint.
+   All transports dispatch through this, so a call is treated the same no matter how it arrived.
+*/
+#[derive(Clone)]
+pub(crate) struct Dispatcher {
+    pub handlers: HandlerMap,
+    pub stream_handlers: StreamHandlerMap,
+    pub middleware: MiddlewareRegistry,
+    pub schemas: SchemaMap,
+    pub authenticator: Authenticator,
+    pub schema_hash: SchemaHash,
+}
+
+impl Debug for Dispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dispatcher").finish_non_exhaustive()
+    }
+}
+
+impl Dispatcher {
+    pub async fn authenticate(
+        &self,
+        context: RequestContext,
+    ) -> Result<RequestContext, SendableError> {
+        self.authenticator.authenticate(context).await
+    }
+
+    pub async fn call(
+        &self,
+        request: protocol::Request,
+        context: RequestContext,
+    ) -> protocol::Response {
+        let (request, context) = match self.prepare(request, context).await {
+            Ok(v) => v,
+            Err(err) => return err.into(),
+        };
+
+        let response = {
+            let handlers = self.handlers.read();
+            match handlers.get(&request.identifier) {
+                Some(handler) => handler(request, context),
+                None => return SendableError::NotFound.into(),
+            }
+        };
+
+        response.await
+    }
+
+    /**
+       Calls a streaming endpoint, errors before the handler runs are sent as the only item
+    */
+    pub async fn call_stream(
+        &self,
+        request: protocol::Request,
+        context: RequestContext,
+    ) -> ItemStream {
+        let (request, context) = match self.prepare(request, context).await {
+            Ok(v) => v,
+            Err(err) => return stream::once(async move { Err(err) }).boxed(),
+        };
+
+        let stream_handlers = self.stream_handlers.read();
+        match stream_handlers.get(&request.identifier) {
+            Some(handler) => handler(request, context),
+            None => stream::once(async { Err(SendableError::NotFound) }).boxed(),
+        }
+    }
+
+    async fn prepare(
+        &self,
+        request: protocol::Request,
+        context: RequestContext,
+    ) -> Result<(protocol::Request, RequestContext), SendableError> {
+        self.schema_hash.check_request(&context)?;
+        self.validate_parameters(&request)?;
+        self.middleware.run(request, context).await
+    }
+
+    /**
+       Checks the parameters of the request against the schema of its endpoint, endpoints without a schema accept any parameters
+    */
+    fn validate_parameters(&self, request: &protocol::Request) -> Result<(), SendableError> {
+        match self.schemas.read().get(&request.identifier) {
+            Some(schema) => schema.validate(&request.parameters),
+            None => Ok(()),
+        }
+    }
+}
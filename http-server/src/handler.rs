@@ -6,6 +6,16 @@
 
 use futures_util::Future;
 
+use crate::Server;
+
+/**
+   A handler whose identifier and types the handler macro checked against the .erpc sources, see Server::register
+*/
+pub trait EndpointHandler {
+    const IDENTIFIER: &'static str;
+    fn register(self, server: &mut Server);
+}
+
 pub trait Handler<Args>: Send + Sync + Clone {
   type Output;
   type Future: Future<Output = Self::Output>;
@@ -1,7 +1,32 @@
+// the handler macro refers to this crate by its name, also within its own tests
+extern crate self as http_server;
+
+mod adapter;
+mod context;
+mod dispatch;
 mod handler;
+mod limits;
+mod middleware;
+mod registry;
+mod relay;
+mod requests;
+mod schema_hash;
 mod server;
 mod target;
 mod tests;
-pub use server::Server;
-pub use server::Socket;
+pub use adapter::{ErpcService, RawResponse};
+pub use context::{InternalAuthenticator, RequestContext};
+#[cfg(feature = "macros")]
+pub use erpc_macros::handler;
+pub use handler::EndpointHandler;
+pub use limits::{InFlight, InFlightPermit};
+pub use middleware::{InternalMiddleware, MiddlewareCall};
+pub use schema_hash::SchemaMismatch;
+pub use server::{DisconnectReason, Server, ServerOptions, TlsOptions};
+pub use server::{Socket, SocketMetrics};
 pub use target::Target;
+
+// generated code and the handler macro refer to these through this crate, projects only need to depend on it
+pub use erpc;
+pub use serde;
+pub use serde_json;
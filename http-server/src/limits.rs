@@ -0,0 +1,76 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use erpc::protocol::{socket::SocketMessage, SendableError};
+
+/**
+   Counts the calls which wait for a response of a socket, calls above the limit are rejected
+*/
+#[derive(Clone, Debug)]
+pub struct InFlight {
+    count: Arc<AtomicUsize>,
+    limit: usize,
+}
+
+impl InFlight {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            count: Arc::new(AtomicUsize::new(0)),
+            limit,
+        }
+    }
+
+    /**
+       Reserves a slot for a call, it is freed when the permit is dropped.
+       Fails with Overloaded if the limit is reached
+    */
+    pub fn acquire(&self) -> Result<InFlightPermit, SendableError> {
+        let acquired = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < self.limit).then_some(count + 1)
+            });
+
+        match acquired {
+            Ok(_) => Ok(InFlightPermit {
+                count: self.count.clone(),
+            }),
+            Err(_) => Err(SendableError::Overloaded),
+        }
+    }
+
+    /**
+       The calls which currently wait for a response
+    */
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug)]
+pub struct InFlightPermit {
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/**
+   Queues the message for a socket without waiting.
+   Fails with Overloaded if the queue of the socket is full and with ConnectionClosed if the socket is gone
+*/
+pub(crate) fn enqueue(
+    queue: &flume::Sender<SocketMessage>,
+    message: SocketMessage,
+) -> Result<(), SendableError> {
+    match queue.try_send(message) {
+        Ok(_) => Ok(()),
+        Err(flume::TrySendError::Full(_)) => Err(SendableError::Overloaded),
+        Err(flume::TrySendError::Disconnected(_)) => Err(SendableError::ConnectionClosed),
+    }
+}
@@ -0,0 +1,105 @@
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use erpc::protocol::{self, SendableError};
+use futures_util::Future;
+use log::error;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::RequestContext;
+
+pub type InternalMiddleware = Box<
+    dyn Fn(
+            MiddlewareCall,
+        ) -> Pin<Box<dyn Future<Output = Result<MiddlewareCall, SendableError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/**
+   A call passing through the middleware chain of an endpoint.
+   Middleware can change the parameters before they reach the handler and store values in the context
+   which are visible to all middleware running after it and to the handler as the middleware values of its RequestContext.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MiddlewareCall {
+    /**
+       The identifier of the called endpoint, changing it has no effect
+    */
+    pub identifier: String,
+    pub parameters: protocol::Parameters,
+    #[serde(default)]
+    pub context: serde_json::Map<String, serde_json::Value>,
+    /**
+       Who made the call including the auth data of the authenticator, changing it has no effect
+    */
+    #[serde(default)]
+    pub caller: RequestContext,
+}
+
+/**
+   Holds all registered middleware and which of them run in what order for an endpoint
+*/
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareRegistry {
+    middleware: Arc<RwLock<HashMap<String, InternalMiddleware>>>,
+    chains: Arc<RwLock<HashMap<String, Vec<String>>>>,
+}
+
+impl MiddlewareRegistry {
+    pub fn register(&self, middleware: InternalMiddleware, identifier: String) {
+        self.middleware.write().insert(identifier, middleware);
+    }
+
+    pub fn set_chain(&self, endpoint_identifier: String, middleware_identifiers: Vec<String>) {
+        self.chains
+            .write()
+            .insert(endpoint_identifier, middleware_identifiers);
+    }
+
+    /**
+       Passes the request through all middleware of its endpoint in order, the values they stored are added to the context.
+       The first middleware returning an error stops the chain and the error is sent instead of calling the handler.
+    */
+    pub async fn run(
+        &self,
+        request: protocol::Request,
+        mut context: RequestContext,
+    ) -> Result<(protocol::Request, RequestContext), SendableError> {
+        let chain = match self.chains.read().get(&request.identifier) {
+            Some(v) => v.clone(),
+            None => return Ok((request, context)),
+        };
+
+        let identifier = request.identifier;
+        let mut call = MiddlewareCall {
+            identifier: identifier.clone(),
+            parameters: request.parameters,
+            context: serde_json::Map::new(),
+            caller: context.clone(),
+        };
+
+        for middleware_identifier in chain {
+            let future = match self.middleware.read().get(&middleware_identifier) {
+                Some(middleware) => middleware(call),
+                None => {
+                    // running the handler without its middleware could skip checks like authentication
+                    error!(
+                        "Middleware {middleware_identifier} of endpoint {identifier} is not registered"
+                    );
+                    return Err(SendableError::Internal);
+                }
+            };
+            call = future.await?;
+        }
+
+        context.middleware = call.context;
+        Ok((
+            protocol::Request {
+                identifier,
+                parameters: call.parameters,
+            },
+            context,
+        ))
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use erpc::protocol::{
+    self,
+    socket::{Cancel, SocketMessage},
+    SendableError,
+};
+use futures_util::future::join_all;
+use log::error;
+use nanoid::nanoid;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::oneshot;
+
+use crate::{
+    context::RequestContext,
+    limits::enqueue,
+    server::{DisconnectReason, Socket},
+};
+
+/**
+   Called with the socket and the reason once a socket closed
+*/
+pub(crate) type DisconnectListener = Box<dyn Fn(&Socket, &DisconnectReason) + Send + Sync>;
+
+/**
+   The browsers which are connected via websocket, by the id of their connection
+*/
+#[derive(Clone, Default)]
+pub(crate) struct SocketRegistry {
+    sockets: Arc<RwLock<HashMap<String, RegisteredSocket>>>,
+    /**
+       Calls the server made on sockets without a target, e.g. broadcasts, by their request id
+    */
+    open_calls: Arc<Mutex<HashMap<String, OpenCall>>>,
+    disconnect_listeners: Arc<RwLock<Vec<DisconnectListener>>>,
+}
+
+struct RegisteredSocket {
+    socket: Socket,
+    /**
+       The context the socket connected with, broadcasts are filtered by it
+    */
+    context: RequestContext,
+}
+
+struct OpenCall {
+    socket_id: String,
+    responder: oneshot::Sender<protocol::Response>,
+}
+
+impl SocketRegistry {
+    pub fn register(&self, socket: Socket, context: RequestContext) {
+        self.sockets
+            .write()
+            .insert(socket.id.clone(), RegisteredSocket { socket, context });
+    }
+
+    /**
+       Removes a closed socket, calls which wait for its response fail with ConnectionClosed.
+       The disconnect listeners are called afterwards, nothing happens if the socket was not registered
+    */
+    pub fn unregister(&self, socket_id: &str, reason: DisconnectReason) {
+        let registered = match self.sockets.write().remove(socket_id) {
+            Some(v) => v,
+            None => return,
+        };
+
+        {
+            // scope to drop the calls lock before the listeners run
+            let mut open_calls = self.open_calls.lock();
+            let closed = open_calls
+                .iter()
+                .filter(|(_, v)| v.socket_id == socket_id)
+                .map(|(k, _)| k.clone())
+                .collect::<Vec<_>>();
+            for id in closed {
+                if let Some(call) = open_calls.remove(&id) {
+                    // the caller might have stopped waiting already
+                    let _ = call.responder.send(SendableError::ConnectionClosed.into());
+                }
+            }
+        }
+
+        for listener in self.disconnect_listeners.read().iter() {
+            listener(&registered.socket, &reason);
+        }
+    }
+
+    pub fn on_disconnect(&self, listener: DisconnectListener) {
+        self.disconnect_listeners.write().push(listener);
+    }
+
+    /**
+       The connected sockets in no particular order, only those of the role if one is given
+    */
+    pub fn sockets(&self, role: Option<&str>) -> Vec<Socket> {
+        self.sockets
+            .read()
+            .values()
+            .filter(|v| role.is_none_or(|role| v.socket.role == role))
+            .map(|v| v.socket.clone())
+            .collect()
+    }
+
+    pub fn socket(&self, socket_id: &str) -> Option<Socket> {
+        self.sockets.read().get(socket_id).map(|v| v.socket.clone())
+    }
+
+    /**
+       The socket with the id, None if no socket with the id and role is connected
+    */
+    pub fn socket_with_role(&self, socket_id: &str, role: &str) -> Option<Socket> {
+        self.sockets
+            .read()
+            .get(socket_id)
+            .filter(|v| v.socket.role == role)
+            .map(|v| v.socket.clone())
+    }
+
+    /**
+       Sends the message to the socket, nothing happens if it is not connected anymore
+    */
+    pub fn send(&self, socket_id: &str, message: SocketMessage) {
+        if let Some(registered) = self.sockets.read().get(socket_id) {
+            if let Err(err) = enqueue(&registered.socket.requests, message) {
+                error!("Could not send message to socket {socket_id}: {err}");
+            }
+        }
+    }
+
+    /**
+       Calls the endpoint on all sockets of the role which the filter accepts.
+       Resolves once every socket answered, failed or timed out, with the response of each socket by its id
+    */
+    pub async fn broadcast<F>(
+        &self,
+        role: &str,
+        request: protocol::Request,
+        filter: F,
+        timeout: Option<Duration>,
+    ) -> HashMap<String, protocol::Response>
+    where
+        F: Fn(&RequestContext) -> bool,
+    {
+        let sockets = self
+            .sockets
+            .read()
+            .values()
+            .filter(|v| v.socket.role == role && filter(&v.context))
+            .map(|v| v.socket.clone())
+            .collect::<Vec<_>>();
+
+        let calls = sockets.into_iter().map(|socket| {
+            let socket_id = socket.id.clone();
+            let response = self.call(socket, request.clone(), timeout);
+            async move { (socket_id, response.await) }
+        });
+
+        join_all(calls).await.into_iter().collect()
+    }
+
+    async fn call(
+        &self,
+        socket: Socket,
+        request: protocol::Request,
+        timeout: Option<Duration>,
+    ) -> protocol::Response {
+        // held until the call returned
+        let _permit = match socket.in_flight.acquire() {
+            Ok(v) => v,
+            Err(err) => return err.into(),
+        };
+
+        let id = nanoid!();
+        let (sender, reciever) = oneshot::channel();
+        self.open_calls.lock().insert(
+            id.clone(),
+            OpenCall {
+                socket_id: socket.id.clone(),
+                responder: sender,
+            },
+        );
+
+        if let Err(err) = enqueue(
+            &socket.requests,
+            SocketMessage::Request(protocol::socket::Request::from_request(request, &id)),
+        ) {
+            self.open_calls.lock().remove(&id);
+            error!("Could not send request on socket: {err}");
+            return err.into();
+        }
+
+        let response = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, reciever).await.ok(),
+            None => Some(reciever.await),
+        };
+
+        match response {
+            Some(Ok(v)) => v,
+            Some(Err(_)) => SendableError::ConnectionClosed.into(),
+            None => {
+                // nobody waits for the response anymore, so the entry would never be removed
+                self.open_calls.lock().remove(&id);
+                if let Err(err) = enqueue(&socket.requests, SocketMessage::Cancel(Cancel { id })) {
+                    error!("Could not send cancel on socket: {err}");
+                }
+                SendableError::Timeout.into()
+            }
+        }
+    }
+
+    /**
+       Resolves the call the response answers.
+       Responses to calls which were not made by the registry are returned, they belong to the targets of the socket
+    */
+    pub fn respond(
+        &self,
+        response: protocol::socket::Response,
+    ) -> Option<protocol::socket::Response> {
+        let call = match self.open_calls.lock().remove(&response.id) {
+            Some(v) => v,
+            None => return Some(response),
+        };
+
+        // the caller might have timed out in the meantime
+        let _ = call.responder.send(response.response);
+        None
+    }
+}
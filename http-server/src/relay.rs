@@ -0,0 +1,185 @@
+use std::{collections::HashMap, sync::Arc};
+
+use erpc::protocol::{
+    self,
+    socket::{Cancel, RelayRequest, SocketMessage},
+    SendableError,
+};
+use log::error;
+use parking_lot::Mutex;
+
+use crate::{
+    limits::{enqueue, InFlightPermit},
+    registry::SocketRegistry,
+};
+
+/**
+   Forwards requests between browsers which are connected via websocket, so they can call each other through the server
+*/
+#[derive(Clone)]
+pub(crate) struct Relay {
+    sockets: SocketRegistry,
+    /**
+       Requests which were forwarded and wait for a response, by the id they were forwarded with
+    */
+    open_requests: Arc<Mutex<HashMap<String, OpenRelay>>>,
+}
+
+struct OpenRelay {
+    /**
+       The socket the request came from
+    */
+    origin: String,
+    /**
+       The id of the request on the socket it came from
+    */
+    id: String,
+    /**
+       The socket the request was forwarded to
+    */
+    destination: String,
+    /**
+       Counts the request as in flight on the destination until it is answered
+    */
+    _permit: InFlightPermit,
+}
+
+impl Relay {
+    pub fn new(sockets: SocketRegistry) -> Self {
+        Self {
+            sockets,
+            open_requests: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /**
+       Cleans up after a socket closed. Requests which were forwarded to it fail and requests it sent are cancelled
+    */
+    pub fn close(&self, socket_id: &str) {
+        let mut open_requests = self.open_requests.lock();
+        let closed = open_requests
+            .iter()
+            .filter(|(_, v)| v.origin == socket_id || v.destination == socket_id)
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+
+        for relay_id in closed {
+            let open = match open_requests.remove(&relay_id) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            if open.destination == socket_id {
+                self.sockets.send(
+                    &open.origin,
+                    SocketMessage::Response(protocol::socket::Response::from_response(
+                        SendableError::ConnectionClosed.into(),
+                        &open.id,
+                    )),
+                );
+            } else {
+                self.sockets.send(
+                    &open.destination,
+                    SocketMessage::Cancel(Cancel { id: relay_id }),
+                );
+            }
+        }
+    }
+
+    /**
+       Forwards the request of the origin socket to its destination.
+       The origin gets a ConnectionClosed error if no socket with the id and role is connected
+       and an Overloaded error if the destination has too many requests to answer
+    */
+    pub fn forward(&self, origin: &str, request: RelayRequest) {
+        let destination = self
+            .sockets
+            .socket_with_role(&request.socket_id, &request.role)
+            .ok_or(SendableError::ConnectionClosed)
+            .and_then(|v| Ok((v.in_flight.acquire()?, v.requests)));
+        let (permit, destination) = match destination {
+            Ok(v) => v,
+            Err(err) => {
+                self.sockets.send(
+                    origin,
+                    SocketMessage::Response(protocol::socket::Response::from_response(
+                        err.into(),
+                        &request.id,
+                    )),
+                );
+                return;
+            }
+        };
+
+        // socket ids are unique, so the relay id is unique among all forwarded requests
+        let relay_id = relay_id(origin, &request.id);
+        self.open_requests.lock().insert(
+            relay_id.clone(),
+            OpenRelay {
+                origin: origin.to_string(),
+                id: request.id.clone(),
+                destination: request.socket_id,
+                _permit: permit,
+            },
+        );
+
+        if let Err(err) = enqueue(
+            &destination,
+            SocketMessage::Request(protocol::socket::Request::from_request(
+                request.request,
+                &relay_id,
+            )),
+        ) {
+            error!("Could not forward request: {err}");
+            self.open_requests.lock().remove(&relay_id);
+            self.sockets.send(
+                origin,
+                SocketMessage::Response(protocol::socket::Response::from_response(
+                    err.into(),
+                    &request.id,
+                )),
+            );
+        }
+    }
+
+    /**
+       Passes the response of a forwarded request back to the socket it came from.
+       Responses to requests which were not forwarded are returned, they belong to the targets of the socket
+    */
+    pub fn respond(
+        &self,
+        response: protocol::socket::Response,
+    ) -> Option<protocol::socket::Response> {
+        let open = match self.open_requests.lock().remove(&response.id) {
+            Some(v) => v,
+            None => return Some(response),
+        };
+
+        self.sockets.send(
+            &open.origin,
+            SocketMessage::Response(protocol::socket::Response {
+                id: open.id,
+                response: response.response,
+            }),
+        );
+        None
+    }
+
+    /**
+       Cancels a forwarded request on its destination, nothing happens if the request was not forwarded
+    */
+    pub fn cancel(&self, origin: &str, id: &str) {
+        let relay_id = relay_id(origin, id);
+        let open = self.open_requests.lock().remove(&relay_id);
+        if let Some(open) = open {
+            self.sockets.send(
+                &open.destination,
+                SocketMessage::Cancel(Cancel { id: relay_id }),
+            );
+        }
+    }
+}
+
+fn relay_id(origin: &str, id: &str) -> String {
+    format!("{origin}/{id}")
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use erpc::{
+    protocol::{
+        self,
+        socket::{SocketMessage, StreamError},
+        SendableError,
+    },
+    stream::{ItemStream, StreamProducers},
+};
+use log::error;
+use parking_lot::Mutex;
+use tokio::{sync::Semaphore, task::AbortHandle};
+
+/**
+   The calls a browser made over its socket which did not respond yet.
+   They run concurrently so a slow handler doesn't hold up the other messages of the socket,
+   but never more than the limit at once, further calls wait until one finished.
+   Only a limited amount of calls can wait, calls beyond that fail with SendableError::Overloaded.
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct SocketRequests {
+    running: Arc<Mutex<HashMap<String, AbortHandle>>>,
+    permits: Arc<Semaphore>,
+    /**
+       Taken by every call which runs or waits for a permit
+    */
+    slots: Arc<Semaphore>,
+}
+
+impl SocketRequests {
+    pub fn new(max_concurrent: usize, max_waiting: usize) -> Self {
+        Self {
+            running: Arc::new(Mutex::new(HashMap::new())),
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            slots: Arc::new(Semaphore::new(max_concurrent.saturating_add(max_waiting))),
+        }
+    }
+
+    /**
+       Runs the call in its own task, its response is queued on outgoing as soon as it finished,
+       so responses arrive in the order the calls complete
+    */
+    pub fn start<F>(&self, id: String, call: F, outgoing: flume::Sender<SocketMessage>)
+    where
+        F: Future<Output = protocol::Response> + Send + 'static,
+    {
+        let response_id = id.clone();
+        self.spawn(
+            id,
+            |id, err| {
+                SocketMessage::Response(protocol::socket::Response::from_response(err.into(), &id))
+            },
+            async move {
+                let response = call.await;
+                Some(SocketMessage::Response(
+                    protocol::socket::Response::from_response(response, &response_id),
+                ))
+            },
+            outgoing,
+        );
+    }
+
+    /**
+       Opens the stream in its own task like a call, once it is open the producers send its items
+    */
+    pub fn start_stream<F>(
+        &self,
+        id: String,
+        open: F,
+        producers: StreamProducers,
+        outgoing: flume::Sender<SocketMessage>,
+    ) where
+        F: Future<Output = ItemStream> + Send + 'static,
+    {
+        let stream_id = id.clone();
+        let items_outgoing = outgoing.clone();
+        self.spawn(
+            id,
+            |id, error| SocketMessage::StreamError(StreamError { id, error }),
+            async move {
+                let items = open.await;
+                // the producer is registered before the call is removed, so a cancel always finds one of them
+                tokio::spawn(producers.start(stream_id, items, items_outgoing));
+                None
+            },
+            outgoing,
+        );
+    }
+
+    fn spawn<F>(
+        &self,
+        id: String,
+        reject: fn(String, SendableError) -> SocketMessage,
+        task: F,
+        outgoing: flume::Sender<SocketMessage>,
+    ) where
+        F: Future<Output = Option<SocketMessage>> + Send + 'static,
+    {
+        // the task removes itself when it finished, which must not happen before it was inserted
+        let mut lock = self.running.lock();
+        if lock.contains_key(&id) {
+            let message = format!("A call with the id {id} is running already");
+            return send_rejection(
+                &outgoing,
+                reject(
+                    id,
+                    SendableError::BadRequest {
+                        message,
+                        path: None,
+                    },
+                ),
+            );
+        }
+        let slot = match self.slots.clone().try_acquire_owned() {
+            Ok(v) => v,
+            Err(_) => return send_rejection(&outgoing, reject(id, SendableError::Overloaded)),
+        };
+
+        let running = self.running.clone();
+        let permits = self.permits.clone();
+        let task = tokio::spawn({
+            let id = id.clone();
+            async move {
+                let _slot = slot;
+                let message = match permits.acquire_owned().await {
+                    Ok(_permit) => task.await,
+                    // the semaphore is never closed
+                    Err(_) => return,
+                };
+                running.lock().remove(&id);
+
+                // waits for room in the queue of the socket instead of dropping the response
+                if let Some(message) = message {
+                    if let Err(err) = outgoing.send_async(message).await {
+                        error!("Could not send response for {id}: {err}");
+                    }
+                }
+            }
+        });
+        lock.insert(id, task.abort_handle());
+    }
+
+    /**
+       Stops the call, nothing happens if there is none or it already finished
+    */
+    pub fn cancel(&self, id: &str) {
+        if let Some(task) = self.running.lock().remove(id) {
+            task.abort();
+        }
+    }
+
+    /**
+       Stops all calls, used when the socket closed
+    */
+    pub fn clear(&self) {
+        for (_, task) in self.running.lock().drain() {
+            task.abort();
+        }
+    }
+}
+
+/**
+   Rejections must not wait for room in the queue while the socket is read, so they are dropped if it is full
+*/
+fn send_rejection(outgoing: &flume::Sender<SocketMessage>, message: SocketMessage) {
+    if let Err(err) = outgoing.try_send(message) {
+        error!("Could not send rejection: {err}");
+    }
+}
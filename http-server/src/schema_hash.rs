@@ -0,0 +1,132 @@
+use std::{collections::HashSet, sync::Arc};
+
+use erpc::protocol::{self, SendableError};
+use log::warn;
+use parking_lot::{Mutex, RwLock};
+
+use crate::context::RequestContext;
+
+/**
+   How many drifted hashes are logged, clients sending a new hash with every call must not grow the set forever
+*/
+const MAX_LOGGED_HASHES: usize = 64;
+
+/**
+   Called whenever a client which was generated from other .erpc sources than the server calls it
+*/
+pub(crate) type MismatchListener = Box<dyn Fn(&SchemaMismatch) + Send + Sync>;
+
+/**
+   A client sent another schema hash than the one the server was generated with,
+   so its calls might not match the endpoints of the server anymore
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaMismatch {
+    /**
+       The schema hash of the server
+    */
+    pub expected: String,
+    /**
+       The schema hash the client was generated with
+    */
+    pub received: String,
+    pub context: RequestContext,
+}
+
+/**
+   Compares the schema hashes clients send with the one of the server.
+   Clients which send no hash, e.g. hand written ones, are never reported
+*/
+#[derive(Clone, Default)]
+pub(crate) struct SchemaHash {
+    hash: Arc<RwLock<Option<String>>>,
+    /**
+       Rejects mismatching clients instead of only reporting them
+    */
+    strict: bool,
+    listeners: Arc<RwLock<Vec<MismatchListener>>>,
+    /**
+       The hashes which were logged already, a drifted client is logged once instead of on every call.
+       Only valid hashes are logged and stored, anything else in the header is sent by clients that weren't generated
+    */
+    logged: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SchemaHash {
+    pub fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            ..Default::default()
+        }
+    }
+
+    pub fn set(&self, hash: String) {
+        self.hash.write().replace(hash);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.hash.read().clone()
+    }
+
+    pub fn on_mismatch(&self, listener: MismatchListener) {
+        self.listeners.write().push(listener);
+    }
+
+    /**
+       Checks the hash a http request sent in its header
+    */
+    pub fn check_request(&self, context: &RequestContext) -> Result<(), SendableError> {
+        match context.headers.get(protocol::routes::SCHEMA_HASH_HEADER) {
+            Some(received) => self.check(received, context),
+            None => Ok(()),
+        }
+    }
+
+    /**
+       Reports the mismatch to the listeners, it is an error in strict mode
+    */
+    pub fn check(&self, received: &str, context: &RequestContext) -> Result<(), SendableError> {
+        let expected = match self.hash.read().as_ref() {
+            Some(v) if v != received => v.clone(),
+            _ => return Ok(()),
+        };
+
+        if is_schema_hash(received) && self.log_once(received) {
+            warn!(
+                "A client with the schema hash {received} called the server with the schema hash {expected}, it was generated from other .erpc sources"
+            );
+        }
+
+        let mismatch = SchemaMismatch {
+            expected,
+            received: received.to_string(),
+            context: context.clone(),
+        };
+        for listener in self.listeners.read().iter() {
+            listener(&mismatch);
+        }
+
+        if self.strict {
+            return Err(SendableError::BadRequest {
+                message: format!(
+                    "The client was generated from other .erpc sources than the server (schema hash {}, expected {}), regenerate it",
+                    mismatch.received, mismatch.expected
+                ),
+                path: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn log_once(&self, received: &str) -> bool {
+        let mut logged = self.logged.lock();
+        logged.len() < MAX_LOGGED_HASHES && logged.insert(received.to_string())
+    }
+}
+
+/**
+   Checks if the value looks like a hash the transpiler generates, 16 lowercase hex characters
+*/
+pub(crate) fn is_schema_hash(value: &str) -> bool {
+    value.len() == 16 && value.bytes().all(|v| matches!(v, b'0'..=b'9' | b'a'..=b'f'))
+}
@@ -1,32 +1,256 @@
 //TODO: check the channels for optimal tool for the problem (e.g. swithc to broadcast, mpsc where applicable)
 
-use std::{collections::HashMap, pin::Pin, sync::Arc};
+use std::{collections::HashMap, panic::AssertUnwindSafe, pin::Pin, sync::Arc, time::Duration};
 
-use erpc::protocol::{self, socket::SocketMessage, SendableError};
-use futures_util::Future;
+use erpc::{
+    codec::Codec,
+    heartbeat::{HeartbeatMonitor, HeartbeatOptions},
+    protocol::{
+        self,
+        socket::{HandshakeRejected, SocketMessage, HANDSHAKE_REJECTED_CLOSE_CODE},
+        Parameters, SendableError,
+    },
+    schema::EndpointSchema,
+    stream::{ItemStream, StreamProducers},
+};
+use futures_util::{stream, Future, FutureExt, Stream, StreamExt};
 use log::error;
+use nanoid::nanoid;
 use parking_lot::RwLock;
 use reqwest::Method;
-use salvo::{catcher::Catcher, cors::Cors, prelude::*};
+use salvo::{
+    catcher::Catcher,
+    conn::{
+        quinn::QuinnListener,
+        rustls::{Keycert, RustlsConfig},
+        Acceptor, Holding,
+    },
+    cors::Cors,
+    prelude::*,
+};
 use serde::{de::DeserializeOwned, Serialize};
-use tokio::sync::oneshot;
+use tokio::{
+    sync::{oneshot, watch},
+    time::{Instant, Interval, MissedTickBehavior},
+};
 
-use crate::handler;
+#[cfg(unix)]
+use salvo::conn::unix::UnixListener;
+
+use crate::{
+    context::{Authenticator, InternalAuthenticator, RequestContext},
+    dispatch::Dispatcher,
+    handler::{EndpointHandler, Handler},
+    limits::InFlight,
+    middleware::{InternalMiddleware, MiddlewareCall, MiddlewareRegistry},
+    registry::SocketRegistry,
+    relay::Relay,
+    requests::SocketRequests,
+    schema_hash::{SchemaHash, SchemaMismatch},
+};
 
 pub type InternalHandler = Box<
-    dyn Fn(protocol::Request) -> Pin<Box<dyn Future<Output = protocol::Response> + Send + Sync>>
+    dyn Fn(
+            protocol::Request,
+            RequestContext,
+        ) -> Pin<Box<dyn Future<Output = protocol::Response> + Send>>
         + Send
         + Sync,
 >;
 
-type HandlerMap = Arc<RwLock<HashMap<String, InternalHandler>>>;
+/**
+   Handles calls of streaming endpoints, errors of the call like invalid parameters are sent as the only item
+*/
+pub type InternalStreamHandler =
+    Box<dyn Fn(protocol::Request, RequestContext) -> ItemStream + Send + Sync>;
+
+pub(crate) type HandlerMap = Arc<RwLock<HashMap<String, InternalHandler>>>;
+pub(crate) type StreamHandlerMap = Arc<RwLock<HashMap<String, InternalStreamHandler>>>;
+pub(crate) type SchemaMap = Arc<RwLock<HashMap<String, EndpointSchema>>>;
 type SocketBroadcaster = (flume::Sender<Socket>, flume::Receiver<Socket>);
 
 #[derive(Clone, Debug)]
 pub struct Socket {
-    pub requests: flume::Sender<erpc::protocol::socket::Request>,
-    pub responses: flume::Receiver<erpc::protocol::socket::Response>,
+    /**
+       Identifies the connection while it is open, it is the socket_id of the RequestContext the browser connected with
+    */
+    pub id: String,
+    /**
+       Messages which are sent to the browser, these are requests and cancellations of requests
+    */
+    pub requests: flume::Sender<erpc::protocol::socket::SocketMessage>,
+    /**
+       Messages from the browser answering the requests, these are responses and stream messages
+    */
+    pub responses: flume::Receiver<erpc::protocol::socket::SocketMessage>,
     pub role: String,
+    /**
+       The calls which wait for a response of the browser, see ServerOptions.max_in_flight_requests
+    */
+    pub in_flight: InFlight,
+}
+
+impl Socket {
+    pub fn metrics(&self) -> SocketMetrics {
+        SocketMetrics {
+            id: self.id.clone(),
+            role: self.role.clone(),
+            outgoing_queue: self.requests.len(),
+            incoming_queue: self.responses.len(),
+            in_flight_requests: self.in_flight.count(),
+        }
+    }
+}
+
+/**
+   A snapshot of the queues of a socket, full queues mean that the browser or the server can't keep up
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SocketMetrics {
+    pub id: String,
+    pub role: String,
+    /**
+       Messages which wait to be sent to the browser
+    */
+    pub outgoing_queue: usize,
+    /**
+       Messages from the browser which wait to be read by the targets of the socket
+    */
+    pub incoming_queue: usize,
+    /**
+       Calls which wait for a response of the browser
+    */
+    pub in_flight_requests: usize,
+}
+
+/**
+   Why a websocket connection ended
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /**
+       The browser closed the connection
+    */
+    Closed,
+    /**
+       Reading from or writing to the connection failed, e.g. because the network of the browser went away
+    */
+    ConnectionLost(String),
+    /**
+       A message could not be read or written in the format of the socket
+    */
+    InvalidMessage(String),
+    /**
+       The browser did not answer the pings of the heartbeat
+    */
+    HeartbeatTimeout,
+    /**
+       No messages were exchanged within the idle timeout, the server closed the connection
+    */
+    IdleTimeout,
+    /**
+       The server can't talk to the browser, e.g. because its protocol version is too old
+    */
+    HandshakeRejected(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerOptions {
+    pub port: u16,
+    pub allowed_cors_origins: Vec<String>,
+    /**
+       The address the listeners bind to, all interfaces (0.0.0.0) if not set.
+       IPv6 addresses like ::1 work as well, unix:/path/to.sock listens on a unix domain socket instead of a port.
+       Port 0 binds to a free port, see local_address for the one which was chosen.
+    */
+    pub bind_address: Option<String>,
+    /**
+       Serves https instead of http if set, it is not applied to unix domain sockets
+    */
+    pub tls: Option<TlsOptions>,
+    /**
+       The path the routes of the server are served under, e.g. /rpc serves /rpc/handlers/... and /rpc/ws/...
+       Targets calling the server need the prefix at the end of their address.
+    */
+    pub route_prefix: Option<String>,
+    /**
+       Pings every websocket in the interval and closes the ones which stopped answering, no pings are sent if not set
+    */
+    pub heartbeat: Option<HeartbeatOptions>,
+    /**
+       Closes websockets which sent or received no message other than heartbeats for this long, sockets stay open if not set
+    */
+    pub idle_timeout: Option<Duration>,
+    /**
+       How many messages can wait to be sent to a single websocket or to be read from it.
+       Calls to a socket whose queue is full fail with SendableError::Overloaded
+    */
+    pub socket_queue_size: usize,
+    /**
+       How many calls can wait for the response of a single websocket at once, further calls fail with SendableError::Overloaded
+    */
+    pub max_in_flight_requests: usize,
+    /**
+       How many calls of a single websocket are handled at once, further calls wait until one of them finished.
+       At most socket_queue_size calls wait, calls beyond that fail with SendableError::Overloaded
+    */
+    pub max_concurrent_socket_requests: usize,
+    /**
+       Rejects calls and websockets of clients which were generated from other .erpc sources than the server,
+       see Server::set_schema_hash. They are only reported to the schema mismatch listeners if not set
+    */
+    pub strict_schema: bool,
+    /**
+       Rejects websockets whose first message is not a handshake, they only count as connected once it was accepted.
+       Clients from before the handshake existed are served like ones which sent one if not set
+    */
+    pub require_handshake: bool,
+    /**
+       How long a websocket has to send its handshake if require_handshake is set, it is closed afterwards
+    */
+    pub handshake_timeout: Duration,
+    /**
+       The largest request body in bytes the handler route reads, larger calls fail with SendableError::BadRequest
+    */
+    pub max_body_size: usize,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            port: 80,
+            allowed_cors_origins: vec![],
+            bind_address: None,
+            tls: None,
+            route_prefix: None,
+            heartbeat: None,
+            idle_timeout: None,
+            socket_queue_size: 1024,
+            max_in_flight_requests: 256,
+            max_concurrent_socket_requests: 64,
+            strict_schema: false,
+            require_handshake: false,
+            handshake_timeout: Duration::from_secs(10),
+            // the limit salvo applies to bodies by default
+            max_body_size: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TlsOptions {
+    /**
+       The PEM encoded certificate chain
+    */
+    pub certificate: Vec<u8>,
+    /**
+       The PEM encoded private key of the certificate
+    */
+    pub key: Vec<u8>,
+    /**
+       Additionally listens for HTTP/3 (QUIC) on the same port via udp, this needs a fixed port
+    */
+    pub http3: bool,
 }
 
 //TODO: check where rwlock/mutex is necessary
@@ -36,60 +260,217 @@ pub struct Server {
       Shutdown signal to exit the webserver gracefully
     */
     shutdown_signal: Arc<RwLock<Option<oneshot::Sender<()>>>>,
+    /**
+      The address the server listens on while it is running
+    */
+    local_address: Arc<watch::Sender<Option<String>>>,
     enabled_sockets: bool,
-    allowed_cors_origins: Vec<String>,
-    port: u16,
+    options: ServerOptions,
     handler_map: HandlerMap,
+    stream_handler_map: StreamHandlerMap,
+    middleware: MiddlewareRegistry,
+    authenticator: Authenticator,
+    schemas: SchemaMap,
+    /**
+      The hash of the .erpc sources the server was generated from
+    */
+    schema_hash: SchemaHash,
     socket_broadcaster: SocketBroadcaster,
+    /**
+      The browsers which are connected via websocket
+    */
+    sockets: SocketRegistry,
+    /**
+      Forwards calls between the connected browsers
+    */
+    relay: Relay,
 }
 
 impl Server {
-    pub fn new(port: u16, allowed_cors_origins: Vec<String>, enabled_sockets: bool) -> Self {
+    pub fn new(options: ServerOptions, enabled_sockets: bool) -> Self {
+        let sockets = SocketRegistry::default();
+        let socket_broadcaster = flume::bounded(options.socket_queue_size);
+        let schema_hash = SchemaHash::new(options.strict_schema);
+
         Self {
             shutdown_signal: Arc::new(RwLock::new(None)),
+            local_address: Arc::new(watch::channel(None).0),
             enabled_sockets,
-            allowed_cors_origins,
-            port,
+            options,
             handler_map: Arc::new(RwLock::new(HashMap::new())),
-            socket_broadcaster: flume::unbounded(),
+            stream_handler_map: Arc::new(RwLock::new(HashMap::new())),
+            middleware: MiddlewareRegistry::default(),
+            authenticator: Authenticator::default(),
+            schemas: Arc::new(RwLock::new(HashMap::new())),
+            schema_hash,
+            socket_broadcaster,
+            relay: Relay::new(sockets.clone()),
+            sockets,
         }
     }
 
+    /**
+       Calls the endpoint on every connected browser of the role which the filter accepts, e.g. to notify them about changes.
+       Resolves once all of them answered, failed or timed out, with the response of each socket by its id.
+       The id is the socket_id of the RequestContext the browser connected with.
+    */
+    pub async fn broadcast<F>(
+        &self,
+        role: &str,
+        request: protocol::Request,
+        filter: F,
+        timeout: Option<Duration>,
+    ) -> HashMap<String, protocol::Response>
+    where
+        F: Fn(&RequestContext) -> bool,
+    {
+        self.sockets.broadcast(role, request, filter, timeout).await
+    }
+
     pub fn get_socket_broadcaster(&self) -> &flume::Receiver<Socket> {
         &self.socket_broadcaster.1
     }
 
-    //TODO implement a way to enable compile time handler registration
+    /**
+       Calls the listener with the socket and the reason whenever a websocket connection ended.
+       Calls which wait for a response of the socket fail with ConnectionClosed.
+    */
+    pub fn on_disconnect<F>(&self, listener: F)
+    where
+        F: Fn(&Socket, &DisconnectReason) + Send + Sync + 'static,
+    {
+        self.sockets.on_disconnect(Box::new(listener));
+    }
+
+    /**
+       The sockets which are connected right now, only those of the role if one is given
+    */
+    pub fn connected_sockets(&self, role: Option<&str>) -> Vec<Socket> {
+        self.sockets.sockets(role)
+    }
+
+    /**
+       The socket with the id, None if it is not connected (anymore)
+    */
+    pub fn connected_socket(&self, id: &str) -> Option<Socket> {
+        self.sockets.socket(id)
+    }
+
+    /**
+       Sets the hash of the .erpc sources the server was generated from, the generated code calls this.
+       Clients send the hash they were generated with, mismatches are reported or rejected, see ServerOptions::strict_schema
+    */
+    pub fn set_schema_hash(&self, hash: impl Into<String>) {
+        self.schema_hash.set(hash.into());
+    }
+
+    pub fn schema_hash(&self) -> Option<String> {
+        self.schema_hash.get()
+    }
+
+    /**
+       Calls the listener whenever a client which was generated from other .erpc sources calls the server or connects to it
+    */
+    pub fn on_schema_mismatch<F>(&self, listener: F)
+    where
+        F: Fn(&SchemaMismatch) + Send + Sync + 'static,
+    {
+        self.schema_hash.on_mismatch(Box::new(listener));
+    }
+
+    /**
+       The queue depths of all connected sockets
+    */
+    pub fn socket_metrics(&self) -> Vec<SocketMetrics> {
+        self.sockets
+            .sockets(None)
+            .iter()
+            .map(Socket::metrics)
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn register_raw_handler(&self, handler: InternalHandler, identifier: String) {
         self.handler_map.write().insert(identifier, handler);
     }
 
+    /**
+       Registers a handler which was declared with the handler macro of the macros feature, e.g. #[handler("api/ping")].
+       Unlike the other register functions the identifier and the types are checked at compile time.
+    */
+    pub fn register<H: EndpointHandler>(&mut self, handler: H) {
+        handler.register(self);
+    }
+
     #[allow(dead_code)]
     pub fn register_handler<H, P>(&mut self, handler: H, identifier: &str)
     where
-        H: handler::Handler<P> + 'static,
+        H: Handler<P> + 'static,
+        P: DeserializeOwned + Send + Sync,
+        H::Output: Serialize + 'static,
+        H::Future: Future<Output = H::Output> + Send,
+    {
+        self.insert_handler(handler, identifier, |result| {
+            match serde_json::to_value(&result) {
+                Ok(v) => v.into(),
+                Err(err) => SendableError::from(err).into(),
+            }
+        });
+    }
+
+    /**
+       Registers a handler for an endpoint which declares an error type with throws.
+       Errors returned by the handler are sent as SendableError::Application.
+    */
+    #[allow(dead_code)]
+    pub fn register_fallible_handler<H, P, T, E>(&mut self, handler: H, identifier: &str)
+    where
+        H: Handler<P, Output = Result<T, E>> + 'static,
+        P: DeserializeOwned + Send + Sync,
+        T: Serialize + 'static,
+        E: Serialize + 'static,
+        H::Future: Future<Output = H::Output> + Send,
+    {
+        self.insert_handler(handler, identifier, |result| match result {
+            Ok(value) => match serde_json::to_value(&value) {
+                Ok(v) => v.into(),
+                Err(err) => SendableError::from(err).into(),
+            },
+            Err(error) => match serde_json::to_value(&error) {
+                Ok(v) => SendableError::Application { error: v }.into(),
+                Err(err) => SendableError::from(err).into(),
+            },
+        });
+    }
+
+    fn insert_handler<H, P>(
+        &mut self,
+        handler: H,
+        identifier: &str,
+        into_response: fn(H::Output) -> protocol::Response,
+    ) where
+        H: Handler<P> + 'static,
         P: DeserializeOwned + Send + Sync,
-        H::Output: Serialize,
-        H::Future: Future<Output = H::Output> + Send + Sync,
+        H::Output: 'static,
+        H::Future: Future<Output = H::Output> + Send,
     {
-        let v: InternalHandler = Box::new(move |request| {
+        let v: InternalHandler = Box::new(move |request, context| {
             let handler = handler.clone();
             Box::pin(async move {
-                let parameters = match serde_json::to_value(request.parameters) {
-                    Ok(v) => v,
-                    Err(err) => return SendableError::from(err).into(),
-                };
-                let parameters = match serde_json::from_value::<P>(parameters) {
-                    Ok(v) => v,
-                    Err(err) => return SendableError::from(err).into(),
-                };
-                let result = handler.call(parameters).await;
-                let serialized = match serde_json::to_value(&result) {
+                let parameters = match parse_parameters::<P>(request.parameters) {
                     Ok(v) => v,
-                    Err(err) => return SendableError::from(err).into(),
+                    Err(err) => return err.into(),
                 };
-                serialized.into()
+                match context
+                    .scope(AssertUnwindSafe(handler.call(parameters)).catch_unwind())
+                    .await
+                {
+                    Ok(v) => into_response(v),
+                    Err(_) => {
+                        error!("Handler for {} panicked", request.identifier);
+                        SendableError::HandlerPanicked.into()
+                    }
+                }
             })
         });
 
@@ -99,6 +480,146 @@ impl Server {
             .insert(identifier.to_string(), v);
     }
 
+    #[allow(dead_code)]
+    pub fn register_raw_stream_handler(&self, handler: InternalStreamHandler, identifier: String) {
+        self.stream_handler_map.write().insert(identifier, handler);
+    }
+
+    /**
+       Registers a handler for a streaming endpoint. The handler resolves to a stream whose items are sent to the caller
+       over its websocket, the stream is only polled as fast as the caller consumes the items.
+    */
+    #[allow(dead_code)]
+    pub fn register_stream_handler<H, P, T>(&mut self, handler: H, identifier: &str)
+    where
+        H: Handler<P> + 'static,
+        P: DeserializeOwned + Send + Sync,
+        H::Output: Stream<Item = T> + Send + 'static,
+        H::Future: Future<Output = H::Output> + Send + 'static,
+        T: Serialize,
+    {
+        let v: InternalStreamHandler = Box::new(move |request, context| {
+            let handler = handler.clone();
+            let items = async move {
+                let parameters = match parse_parameters::<P>(request.parameters) {
+                    Ok(v) => v,
+                    Err(err) => return stream::once(async move { Err(err) }).boxed(),
+                };
+
+                match context
+                    .scope(AssertUnwindSafe(handler.call(parameters)).catch_unwind())
+                    .await
+                {
+                    Ok(items) => items
+                        .map(|item| serde_json::to_value(item).map_err(SendableError::from))
+                        .boxed(),
+                    Err(_) => {
+                        error!("Stream handler for {} panicked", request.identifier);
+                        stream::once(async { Err(SendableError::HandlerPanicked) }).boxed()
+                    }
+                }
+            };
+
+            stream::once(items).flatten().boxed()
+        });
+
+        self.stream_handler_map
+            .write()
+            .insert(identifier.to_string(), v);
+    }
+
+    #[allow(dead_code)]
+    pub fn register_raw_middleware(&self, middleware: InternalMiddleware, identifier: String) {
+        self.middleware.register(middleware, identifier);
+    }
+
+    /**
+       Registers a middleware which can be run before endpoint handlers.
+       It can change the parameters of the call, add values to the context for the following middleware
+       or reject the call by returning an error.
+    */
+    #[allow(dead_code)]
+    pub fn register_middleware<M, F>(&mut self, middleware: M, identifier: &str)
+    where
+        M: Fn(MiddlewareCall) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<MiddlewareCall, SendableError>> + Send + 'static,
+    {
+        self.middleware.register(
+            Box::new(move |call| Box::pin(middleware(call))),
+            identifier.to_string(),
+        );
+    }
+
+    #[allow(dead_code)]
+    pub fn set_raw_authenticator(&self, authenticator: InternalAuthenticator) {
+        self.authenticator.set(authenticator);
+    }
+
+    /**
+       Sets the authenticator which runs for every http request and once before a websocket is upgraded.
+       The value it resolves to is available to handlers as the auth data of the request context,
+       returning an error like SendableError::Unauthorized rejects the request or connection.
+    */
+    #[allow(dead_code)]
+    pub fn set_authenticator<A, F>(&mut self, authenticator: A)
+    where
+        A: Fn(RequestContext) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<serde_json::Value, SendableError>> + Send + 'static,
+    {
+        self.authenticator
+            .set(Box::new(move |context| Box::pin(authenticator(context))));
+    }
+
+    /**
+       Sets the middleware which run in the given order before the handler of the endpoint
+    */
+    pub fn set_endpoint_middleware(&self, identifier: &str, middleware_identifiers: Vec<String>) {
+        self.middleware
+            .set_chain(identifier.to_string(), middleware_identifiers);
+    }
+
+    /**
+       Sets the schema the parameters of calls to the endpoint are checked against before any middleware or the handler runs.
+       Generated code sets it for every endpoint, since the types of handlers can't express everything the sources declare,
+       e.g. the length of arrays, deserializing the parameters would accept them otherwise.
+    */
+    pub fn set_endpoint_schema(&self, identifier: &str, schema: EndpointSchema) {
+        self.schemas.write().insert(identifier.to_string(), schema);
+    }
+
+    /**
+       The dispatcher shares the handlers, middleware and schemas with the server, later registrations are visible to it
+    */
+    pub(crate) fn dispatcher(&self) -> Dispatcher {
+        Dispatcher {
+            handlers: self.handler_map.clone(),
+            stream_handlers: self.stream_handler_map.clone(),
+            middleware: self.middleware.clone(),
+            schemas: self.schemas.clone(),
+            authenticator: self.authenticator.clone(),
+            schema_hash: self.schema_hash.clone(),
+        }
+    }
+
+    pub(crate) fn max_body_size(&self) -> usize {
+        self.options.max_body_size
+    }
+
+    /**
+       The route prefix without surrounding slashes but with a trailing one, empty if there is none
+    */
+    pub(crate) fn route_prefix(&self) -> String {
+        match self
+            .options
+            .route_prefix
+            .as_deref()
+            .map(|v| v.trim_matches('/'))
+        {
+            Some(v) if !v.is_empty() => format!("{v}/"),
+            _ => String::new(),
+        }
+    }
+
     pub async fn run(&self) -> impl Future<Output = ()> {
         let (tx, rx) = oneshot::channel::<()>();
         self.shutdown_signal.write().replace(tx);
@@ -107,17 +628,21 @@ impl Server {
             .allow_methods(vec![Method::POST, Method::OPTIONS])
             .allow_headers("*");
 
-        if self.allowed_cors_origins.contains(&"*".to_string()) {
+        if self.options.allowed_cors_origins.contains(&"*".to_string()) {
             cors_handler = cors_handler.allow_origin("*");
         } else {
-            cors_handler = cors_handler.allow_origin(&self.allowed_cors_origins.clone());
+            cors_handler = cors_handler.allow_origin(&self.options.allowed_cors_origins.clone());
         }
 
-        let mut router = Router::with_hoop(affix::inject(self.handler_map.clone())).push(
+        let mut router = Router::with_hoop(
+            affix::inject(self.dispatcher()).inject(self.options.clone()),
+        )
+        .push(
             Router::with_hoop(cors_handler.into_handler())
                 .options(salvo::handler::empty())
                 .path(format!(
-                    "{}/<**identifier>",
+                    "{}{}/<**identifier>",
+                    self.route_prefix(),
                     protocol::routes::HANDLERS_ROUTE
                 ))
                 .post(request_handler),
@@ -125,28 +650,111 @@ impl Server {
 
         if self.enabled_sockets {
             router = router.push(
-                Router::with_hoop(affix::inject(self.socket_broadcaster.clone()))
-                    .path(format!("{}/<*role>", protocol::routes::WEBSOCKETS_ROUTE))
-                    .handle(socket_handler),
+                Router::with_hoop(
+                    affix::inject(self.socket_broadcaster.clone())
+                        .inject(self.sockets.clone())
+                        .inject(self.relay.clone()),
+                )
+                .path(format!(
+                    "{}{}/<*role>",
+                    self.route_prefix(),
+                    protocol::routes::WEBSOCKETS_ROUTE
+                ))
+                .handle(socket_handler),
             );
         }
 
-        // let config = RustlsConfig::new(None);
-        // let listener = TcpListener::new(("0.0.0.0", self.port));
-        // let acceptor = QuinnListener::new(config, ("0.0.0.0", self.port))
-        //     .join(listener)
-        //     .bind()
-        //     .await;
+        let service = Service::new(router).catcher(Catcher::default().hoop(error_handler));
+        let shutdown = async {
+            rx.await.ok();
+        };
 
-        let acceptor = TcpListener::new(("0.0.0.0", self.port)).bind().await;
+        let address = self
+            .options
+            .bind_address
+            .clone()
+            .unwrap_or_else(|| "0.0.0.0".to_string());
 
-        salvo::Server::new(acceptor).serve_with_graceful_shutdown(
-            Service::new(router).catcher(Catcher::default().hoop(error_handler)),
-            async {
-                rx.await.ok();
-            },
-            None,
-        )
+        // reverse proxies in front of unix sockets terminate tls themselves
+        #[cfg(unix)]
+        if let Some(path) = address.strip_prefix("unix:") {
+            // a socket file left over by a previous run would make binding fail
+            let _ = std::fs::remove_file(path);
+            let acceptor = UnixListener::new(path.to_string()).bind().await;
+            self.local_address.send_replace(Some(address.clone()));
+            return salvo::Server::new(acceptor)
+                .serve_with_graceful_shutdown(service, shutdown, None)
+                .boxed();
+        }
+
+        let address = (address, self.options.port);
+        let listener = TcpListener::new(address.clone());
+
+        // the acceptors have different types, so the servers are boxed
+        match &self.options.tls {
+            None => {
+                let acceptor = listener.bind().await;
+                self.set_local_address(acceptor.holdings());
+                salvo::Server::new(acceptor)
+                    .serve_with_graceful_shutdown(service, shutdown, None)
+                    .boxed()
+            }
+            Some(tls) => {
+                let config = RustlsConfig::new(
+                    Keycert::new()
+                        .cert(tls.certificate.clone())
+                        .key(tls.key.clone()),
+                );
+                let listener = listener.rustls(config.clone());
+
+                if tls.http3 {
+                    // the tcp listener comes first, so its address is the one reported
+                    let acceptor = listener
+                        .join(QuinnListener::new(config, address))
+                        .bind()
+                        .await;
+                    self.set_local_address(acceptor.holdings());
+                    salvo::Server::new(acceptor)
+                        .serve_with_graceful_shutdown(service, shutdown, None)
+                        .boxed()
+                } else {
+                    let acceptor = listener.bind().await;
+                    self.set_local_address(acceptor.holdings());
+                    salvo::Server::new(acceptor)
+                        .serve_with_graceful_shutdown(service, shutdown, None)
+                        .boxed()
+                }
+            }
+        }
+    }
+
+    fn set_local_address(&self, holdings: &[Holding]) {
+        let address = holdings
+            .first()
+            .and_then(|v| format_socket_address(&v.local_addr));
+        self.local_address.send_replace(address);
+    }
+
+    /**
+       The address the server listens on, e.g. 127.0.0.1:8080 or unix:/run/app.sock.
+       It is set as soon as run().await returned the future serving the requests, so the port chosen for port 0 can be read there.
+    */
+    pub fn local_address(&self) -> Option<String> {
+        self.local_address.borrow().clone()
+    }
+
+    /**
+       Waits until the server listens and returns its local address
+    */
+    pub async fn listening(&self) -> String {
+        let mut receiver = self.local_address.subscribe();
+        loop {
+            if let Some(v) = receiver.borrow_and_update().clone() {
+                return v;
+            }
+            // the sender is part of the server, so it can't be dropped while this waits
+            let _ = receiver.changed().await;
+        }
     }
 
     pub fn stop(&self) -> Result<(), String> {
@@ -168,35 +776,99 @@ impl Server {
             }
         };
 
+        self.local_address.send_replace(None);
         Ok(())
     }
 }
 
+/**
+   Formats tcp and udp addresses as ip:port, other kinds of addresses have no such representation
+*/
+pub(crate) fn format_socket_address(address: &salvo::conn::SocketAddr) -> Option<String> {
+    match address {
+        salvo::conn::SocketAddr::IPv4(v) => Some(v.to_string()),
+        salvo::conn::SocketAddr::IPv6(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/**
+   Deserializes the parameters of a call into the parameter tuple of a typed handler.
+   Named parameters are deserialized into the single struct the handler takes.
+*/
+fn parse_parameters<P: DeserializeOwned>(parameters: Parameters) -> Result<P, SendableError> {
+    let parameters = match parameters {
+        // handlers without parameters deserialize into (), which is only valid from null
+        Parameters::Positional(v) if v.is_empty() => serde_json::Value::Null,
+        Parameters::Positional(v) => serde_json::Value::Array(v),
+        Parameters::Named(v) => {
+            let parameters = serde_json::Value::Array(vec![serde_json::Value::Object(v)]);
+            return serde_path_to_error::deserialize::<_, P>(parameters).map_err(|err| {
+                match SendableError::from(err) {
+                    // the caller sent the struct itself, so the path starts at its fields, e.g. "user" instead of "[0].user"
+                    SendableError::BadRequest { message, path } => SendableError::BadRequest {
+                        message,
+                        path: path
+                            .as_deref()
+                            .and_then(|v| v.strip_prefix("[0]"))
+                            .map(|v| v.trim_start_matches('.').to_string())
+                            .filter(|v| !v.is_empty()),
+                    },
+                    err => err,
+                }
+            });
+        }
+    };
+
+    Ok(serde_path_to_error::deserialize::<_, P>(parameters)?)
+}
+
 #[handler]
-async fn request_handler(
+async fn request_handler(req: &mut Request, res: &mut Response, depot: &mut Depot) {
+    let codec = match Codec::from_salvo_request(req) {
+        Ok(v) => v,
+        Err(err) => {
+            // the caller sent a format we can't read, so it is answered in the default format
+            err.render_with_codec(res, Codec::default());
+            return;
+        }
+    };
+
+    // the response is sent in the format of the request
+    match handle_request(req, depot, codec).await {
+        Ok(response) => response.render_with_codec(res, codec),
+        Err(err) => err.render_with_codec(res, codec),
+    }
+}
+
+async fn handle_request(
     req: &mut Request,
     depot: &mut Depot,
+    codec: Codec,
 ) -> Result<protocol::Response, protocol::SendableError> {
-    let response = {
-        let identifier = req
-            .param::<String>("**identifier")
-            .ok_or("Could not read identifier from path")?;
+    let identifier = req
+        .param::<String>("**identifier")
+        .ok_or("Could not read identifier from path")?;
 
-        let req = protocol::Request::try_from_salvo_request(req, identifier).await?;
+    let dispatcher = depot
+        .obtain::<Dispatcher>()
+        .ok_or("Could not obtain dispatcher")?
+        .clone();
 
-        let handlers = depot
-            .obtain::<HandlerMap>()
-            .ok_or("Could not obtain handler map")?
-            .read();
+    let max_body_size = depot
+        .obtain::<ServerOptions>()
+        .ok_or("Could not obtain server options")?
+        .max_body_size;
 
-        let handler = handlers
-            .get(&req.identifier)
-            .ok_or(protocol::SendableError::NotFound)?;
+    // unauthorized callers are rejected before their body is read
+    let context = dispatcher
+        .authenticate(RequestContext::from_salvo_request(req))
+        .await?;
 
-        handler(req)
-    };
+    let req =
+        protocol::Request::try_from_salvo_request(req, identifier, codec, max_body_size).await?;
 
-    Ok(response.await)
+    Ok(dispatcher.call(req, context).await)
 }
 
 #[handler]
@@ -209,9 +881,9 @@ async fn socket_handler(
         .param::<String>("*role")
         .ok_or("Could not read role from path")?;
 
-    let handlers = depot
-        .obtain::<HandlerMap>()
-        .ok_or("Could not obtain handler map")?
+    let dispatcher = depot
+        .obtain::<Dispatcher>()
+        .ok_or("Could not obtain dispatcher")?
         .clone();
 
     let socket_broadcaster = depot
@@ -219,93 +891,346 @@ async fn socket_handler(
         .ok_or("Could not obtain socket broadcaster")?
         .clone();
 
+    let sockets = depot
+        .obtain::<SocketRegistry>()
+        .ok_or("Could not obtain socket registry")?
+        .clone();
+
+    let relay = depot
+        .obtain::<Relay>()
+        .ok_or("Could not obtain relay")?
+        .clone();
+
+    let options = depot
+        .obtain::<ServerOptions>()
+        .ok_or("Could not obtain server options")?
+        .clone();
+
+    // the connection is authenticated once, all calls over it share the context
+    let socket_id = nanoid!();
+    let context = dispatcher
+        .authenticate(RequestContext {
+            socket_id: Some(socket_id.clone()),
+            role: Some(role.clone()),
+            ..RequestContext::from_salvo_request(req)
+        })
+        .await?;
+
+    // clients which don't offer a subprotocol use json
+    let codec = match req
+        .headers()
+        .get(salvo::http::header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(offered) => {
+            let codec = Codec::negotiate_subprotocol(offered).ok_or_else(|| {
+                protocol::SendableError::BadRequest {
+                    message: format!("Unsupported websocket subprotocols {}", offered),
+                    path: None,
+                }
+            })?;
+            // browsers close the connection if the accepted subprotocol is not confirmed
+            res.headers_mut().insert(
+                salvo::http::header::SEC_WEBSOCKET_PROTOCOL,
+                salvo::http::header::HeaderValue::from_static(codec.subprotocol()),
+            );
+            codec
+        }
+        None => Codec::default(),
+    };
+
     // interfaces for this socket, they mirror requests of this socket 1:1
-    let (requests_sender, requests_reciever) = flume::unbounded::<protocol::socket::Request>();
-    let (responses_sender, responses_reciever) = flume::unbounded::<protocol::socket::Response>();
+    let (requests_sender, requests_reciever) =
+        flume::bounded::<protocol::socket::SocketMessage>(options.socket_queue_size);
+    let (responses_sender, responses_reciever) =
+        flume::bounded::<protocol::socket::SocketMessage>(options.socket_queue_size);
 
     let socket = Socket {
+        id: socket_id.clone(),
         responses: responses_reciever,
         requests: requests_sender.clone(),
-        role,
+        role: role.clone(),
+        in_flight: InFlight::new(options.max_in_flight_requests),
     };
 
-    socket_broadcaster
-        .0
-        .send_async(socket)
-        .await
-        .map_err(|err| format!("Could not broadcast socket: {}", err))?;
+    // the connection outlives this handler, so it gets its own copies of the options
+    let heartbeat_options = options.heartbeat;
+    let idle_timeout = options.idle_timeout;
 
     WebSocketUpgrade::new()
-        .upgrade(req, res, |mut ws| async move {
-            loop {
+        .upgrade(req, res, move |mut ws| async move {
+            // streams the browser requested from this server
+            let producers = StreamProducers::default();
+            // calls the browser made to this server
+            let requests = SocketRequests::new(
+                options.max_concurrent_socket_requests,
+                options.socket_queue_size,
+            );
+            let mut heartbeat = heartbeat_options.map(HeartbeatMonitor::new);
+            let mut heartbeat_interval = heartbeat.as_ref().map(|v| {
+                // the first ping is due after one interval, not right away
+                let mut interval =
+                    tokio::time::interval_at(Instant::now() + v.interval(), v.interval());
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            });
+            let mut last_activity = Instant::now();
+            // only the first message of the browser can be a handshake
+            let mut awaiting_handshake = true;
+            let mut handshake_deadline = options
+                .require_handshake
+                .then(|| Instant::now() + options.handshake_timeout);
+
+            // browsers which only receive calls might never send a message, so only a required handshake is waited for
+            if !options.require_handshake {
+                sockets.register(socket.clone(), context.clone());
+                broadcast_socket(&socket_broadcaster, socket.clone());
+            }
+
+            let reason = loop {
                 tokio::select! {
-                    Some(msg) = ws.recv() => {
-                        let msg = if let Ok(msg) = msg {
-                            msg
-                        } else {
-                            return;
+                    _ = next_tick(&mut heartbeat_interval) => {
+                        let ping = match heartbeat.as_mut().and_then(|v| v.ping()) {
+                            Some(v) => v,
+                            None => break DisconnectReason::HeartbeatTimeout,
+                        };
+                        let message = match ping.try_into_salvo_message(codec) {
+                            Ok(v) => v,
+                            Err(err) => break DisconnectReason::InvalidMessage(err.to_string()),
+                        };
+                        if let Err(err) = ws.send(message).await {
+                            break DisconnectReason::ConnectionLost(err.to_string());
+                        }
+                    }
+                    _ = sleep_until(idle_timeout.map(|v| last_activity + v)) => {
+                        // the close frame ends the connection cleanly on the browser side as well
+                        let _ = ws
+                            .send(salvo::websocket::Message::close_with(1000u16, "Idle timeout"))
+                            .await;
+                        break DisconnectReason::IdleTimeout;
+                    }
+                    _ = sleep_until(handshake_deadline) => {
+                        let reason = "Expected a handshake in time, the client is probably outdated";
+                        if let Ok(message) = SocketMessage::HandshakeRejected(HandshakeRejected::new(reason)).try_into_salvo_message(codec) {
+                            let _ = ws.send(message).await;
+                        }
+                        let _ = ws
+                            .send(salvo::websocket::Message::close_with(HANDSHAKE_REJECTED_CLOSE_CODE, "Handshake rejected"))
+                            .await;
+                        break DisconnectReason::HandshakeRejected(reason.to_string());
+                    }
+                    msg = ws.recv() => {
+                        let msg = match msg {
+                            Some(Ok(v)) => v,
+                            Some(Err(err)) => break DisconnectReason::ConnectionLost(err.to_string()),
+                            None => break DisconnectReason::Closed,
                         };
 
-                        let msg = match SocketMessage::try_from(msg) {
+                        if msg.is_close() {
+                            break DisconnectReason::Closed;
+                        }
+                        // pings are answered by the websocket itself
+                        if msg.is_ping() || msg.is_pong() {
+                            continue;
+                        }
+
+                        let msg = match SocketMessage::try_from_salvo_message(msg, codec) {
                             Ok(v) => v,
                             Err(err) => {
                                 error!("Could not parse incoming socket request: {:?}", err);
-                                return;
+                                break DisconnectReason::InvalidMessage(err.to_string());
                             }
                         };
 
-                        match msg {
-                            SocketMessage::Request(r) => {
-                                let response = {
-                                    let handlers = handlers.read();
-                                    let handler = match handlers.get(&r.request.identifier) {
-                                        Some(v) => v,
-                                        None => {
-                                            if let Err(err) = responses_sender.send(
-                                                protocol::socket::Response::from_response(protocol::SendableError::NotFound.into(), &r.id)
-                                            ) {
-                                                error!("Could not send response: {:?}", err);
-                                            };
-                                            return;
-                                        },
+                        // heartbeats don't keep an idle socket open
+                        if !matches!(msg, SocketMessage::Ping(_) | SocketMessage::Pong(_)) {
+                            last_activity = Instant::now();
+                        }
+
+                        if awaiting_handshake {
+                            awaiting_handshake = false;
+                            handshake_deadline = None;
+                            let negotiated = match &msg {
+                                SocketMessage::Handshake(h) => Some(h.negotiate(codec).and_then(|accepted| {
+                                    // clients generated from other sources are reported like their http calls
+                                    match h.schema_hash.as_deref().map(|v| dispatcher.schema_hash.check(v, &context)) {
+                                        Some(Err(err)) => Err(HandshakeRejected::new(err.to_string())),
+                                        _ => Ok(accepted),
+                                    }
+                                })),
+                                _ if options.require_handshake => Some(Err(HandshakeRejected::new(
+                                    "Expected a handshake as the first message, the client is probably outdated",
+                                ))),
+                                _ => None,
+                            };
+
+                            match negotiated {
+                                Some(Ok(accepted)) => {
+                                    let message = match SocketMessage::HandshakeAccepted(accepted).try_into_salvo_message(codec) {
+                                        Ok(v) => v,
+                                        Err(err) => break DisconnectReason::InvalidMessage(err.to_string()),
                                     };
+                                    if let Err(err) = ws.send(message).await {
+                                        break DisconnectReason::ConnectionLost(err.to_string());
+                                    }
+                                }
+                                Some(Err(rejected)) => {
+                                    let reason = rejected.reason.clone();
+                                    if let Ok(message) = SocketMessage::HandshakeRejected(rejected).try_into_salvo_message(codec) {
+                                        let _ = ws.send(message).await;
+                                    }
+                                    // the reason might be too long for a close frame, the message above carries it
+                                    let _ = ws
+                                        .send(salvo::websocket::Message::close_with(HANDSHAKE_REJECTED_CLOSE_CODE, "Handshake rejected"))
+                                        .await;
+                                    break DisconnectReason::HandshakeRejected(reason);
+                                }
+                                None => {}
+                            }
 
-                                    handler(r.request)
-                                };
+                            // other browsers and broadcasts can call this one once the required handshake succeeded
+                            if options.require_handshake {
+                                sockets.register(socket.clone(), context.clone());
+                                broadcast_socket(&socket_broadcaster, socket.clone());
+                            }
+                        }
 
-                                if let Err(err) = responses_sender.send_async(protocol::socket::Response::from_response(response.await, &r.id)).await {
-                                    error!("Could not send response: {:?}", err);
+                        match msg {
+                            SocketMessage::Request(r) => {
+                                // calls of the browser are answered on the same socket once they finished
+                                let dispatcher = dispatcher.clone();
+                                let context = context.clone();
+                                requests.start(
+                                    r.id,
+                                    async move { dispatcher.call(r.request, context).await },
+                                    requests_sender.clone(),
+                                );
+                            },
+                            SocketMessage::StreamRequest(r) => {
+                                // the items are sent to the browser like the requests of the targets
+                                let dispatcher = dispatcher.clone();
+                                let context = context.clone();
+                                requests.start_stream(
+                                    r.id,
+                                    async move { dispatcher.call_stream(r.request, context).await },
+                                    producers.clone(),
+                                    requests_sender.clone(),
+                                );
+                            },
+                            SocketMessage::StreamDemand(d) => producers.demand(d),
+                            SocketMessage::RelayRequest(r) => relay.forward(&socket_id, r),
+                            SocketMessage::Ping(h) => {
+                                let message = match SocketMessage::Pong(h).try_into_salvo_message(codec) {
+                                    Ok(v) => v,
+                                    Err(err) => break DisconnectReason::InvalidMessage(err.to_string()),
                                 };
+                                if let Err(err) = ws.send(message).await {
+                                    break DisconnectReason::ConnectionLost(err.to_string());
+                                }
+                            },
+                            SocketMessage::Pong(_) => {
+                                if let Some(heartbeat) = heartbeat.as_mut() {
+                                    heartbeat.pong();
+                                }
+                            },
+                            // the handshake was answered above if it was the first message
+                            SocketMessage::Handshake(_)
+                            | SocketMessage::HandshakeAccepted(_)
+                            | SocketMessage::HandshakeRejected(_) => {},
+                            SocketMessage::Cancel(c) => {
+                                requests.cancel(&c.id);
+                                producers.cancel(&c.id);
+                                relay.cancel(&socket_id, &c.id);
                             },
                             SocketMessage::Response(r) => {
-                                if let Err(err) = responses_sender.send_async(r).await {
+                                // responses to forwarded requests go back to the browser which sent them
+                                if let Some(r) = relay.respond(r).and_then(|r| sockets.respond(r)) {
+                                    // a browser which sends more than the targets read is not read until there is room,
+                                    // dropping the response would leave its caller waiting
+                                    if let Err(err) = responses_sender.send_async(SocketMessage::Response(r)).await {
+                                        error!("Could not send response: {:?}", err);
+                                    };
+                                }
+                            },
+                            // stream messages answer calls of the targets of this socket
+                            msg => {
+                                if let Err(err) = responses_sender.send_async(msg).await {
                                     error!("Could not send response: {:?}", err);
                                 };
                             },
                         };
                     }
-                    Ok(req) = requests_reciever.recv_async() => {
-                        let message: salvo::websocket::Message =
-                            match protocol::socket::SocketMessage::Request(req).try_into() {
+                    Ok(msg) = requests_reciever.recv_async() => {
+                        last_activity = Instant::now();
+                        let message =
+                            match msg.try_into_salvo_message(codec) {
                                 Ok(v) => v,
                                 Err(err) => {
-                                    error!("Could not convert request to websocket message: {:?}", err);
-                                    return;
+                                    error!("Could not convert message to websocket message: {:?}", err);
+                                    break DisconnectReason::InvalidMessage(err.to_string());
                                 }
                             };
 
-                        if ws.send(message).await.is_err() {
-                            return;
+                        if let Err(err) = ws.send(message).await {
+                            break DisconnectReason::ConnectionLost(err.to_string());
                         }
                     }
                 }
-            }
+            };
+
+            // targets of this socket fail their open requests once the channels are closed
+            drop(responses_sender);
+            drop(requests_reciever);
+            requests.clear();
+            producers.clear();
+            relay.close(&socket_id);
+            sockets.unregister(&socket_id, reason);
         })
         .await?;
 
     Ok(())
 }
 
+/**
+   Offers the socket to the listeners of new sockets. Nobody might listen, so a full queue drops the oldest socket
+   instead of blocking the connection or losing the new one
+*/
+fn broadcast_socket(broadcaster: &SocketBroadcaster, mut socket: Socket) {
+    loop {
+        match broadcaster.0.try_send(socket) {
+            Ok(_) => return,
+            Err(flume::TrySendError::Full(v)) => {
+                let _ = broadcaster.1.try_recv();
+                socket = v;
+            }
+            // the server holds the receiver, so this doesn't happen
+            Err(flume::TrySendError::Disconnected(_)) => return,
+        }
+    }
+}
+
+/**
+   Waits for the next tick of the interval, forever if there is none
+*/
+async fn next_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(v) => {
+            v.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/**
+   Waits until the deadline, forever if there is none
+*/
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(v) => tokio::time::sleep_until(v).await,
+        None => std::future::pending().await,
+    }
+}
+
 // this is used to remove the default error page, which is salvo branded
 #[handler]
 async fn error_handler(res: &mut Response, ctrl: &mut FlowCtrl) {
@@ -1,19 +1,29 @@
 use erpc::{
-    protocol::{self, SendableError},
+    codec::Codec,
+    protocol::{self, socket::SocketMessage, SendableError},
+    stream::{StreamConsumers, StreamProducers, StreamReceiver},
     target::TargetType,
 };
+use futures_util::{stream, StreamExt};
 use log::error;
 use nanoid::nanoid;
 use parking_lot::{Mutex, RwLock};
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{collections::HashMap, fmt::Debug, sync::Arc, time::Duration};
 use tokio::sync::oneshot;
 
-use crate::server::Socket;
+use crate::{
+    context::RequestContext,
+    dispatch::Dispatcher,
+    limits::enqueue,
+    server::{Server, Socket},
+};
 
 lazy_static::lazy_static! {
   static ref REQWEST_CLIENT: reqwest::Client = reqwest::Client::new();
 }
 
+type OpenSocketRequests = Arc<Mutex<HashMap<String, oneshot::Sender<protocol::socket::Response>>>>;
+
 //TODO find a better/faster way to store open requests
 #[derive(Debug, Clone)]
 pub struct Target {
@@ -21,7 +31,24 @@ pub struct Target {
     target_type: TargetType,
     socket: Arc<RwLock<Option<Socket>>>,
     //TODO check if this is optimal
-    open_socket_requests: Arc<Mutex<HashMap<String, oneshot::Sender<protocol::socket::Response>>>>,
+    open_socket_requests: OpenSocketRequests,
+    /**
+       The timeout which is used for calls without their own timeout. None waits forever
+    */
+    timeout: Arc<RwLock<Option<Duration>>>,
+    /**
+       The format of http requests to the target, responses are read in the format the target answers with
+    */
+    codec: Arc<RwLock<Codec>>,
+    /**
+       The hash of the .erpc sources the target was generated from, it is sent with every http request
+    */
+    schema_hash: Arc<RwLock<Option<String>>>,
+    open_streams: StreamConsumers,
+    /**
+       The server loopback targets dispatch their calls to
+    */
+    loopback: Option<Dispatcher>,
 }
 
 impl Target {
@@ -35,43 +62,141 @@ impl Target {
             target_type,
             socket: Arc::new(RwLock::new(None)),
             open_socket_requests: Arc::new(Mutex::new(HashMap::new())),
+            timeout: Arc::new(RwLock::new(None)),
+            codec: Arc::new(RwLock::new(Codec::default())),
+            schema_hash: Arc::new(RwLock::new(None)),
+            open_streams: StreamConsumers::default(),
+            loopback: None,
         }
     }
 
+    /**
+       Creates a target which calls the handlers of a server in the same process, e.g. for server side rendering.
+       Calls take the same path through the authenticator, schema validation and middleware as calls over http,
+       the authenticator is called with an empty context.
+    */
+    pub fn new_loopback(server: &Server) -> Self {
+        let mut target = Target::new(String::new(), TargetType::Loopback);
+        target.set_loopback(server);
+        target
+    }
+
+    /**
+       Dispatches all further calls of this target to the server, clones made before keep calling the old address
+    */
+    pub fn set_loopback(&mut self, server: &Server) {
+        self.target_type = TargetType::Loopback;
+        self.loopback = Some(server.dispatcher());
+    }
+
+    /**
+       Sets the timeout for all calls on this target and its clones. None waits forever
+    */
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        *self.timeout.write() = timeout;
+    }
+
+    /**
+       The timeout which is used for calls without their own timeout
+    */
+    pub fn timeout(&self) -> Option<Duration> {
+        *self.timeout.read()
+    }
+
+    /**
+       Sets the format of http requests to the target for this target and its clones
+    */
+    pub fn set_codec(&self, codec: Codec) {
+        *self.codec.write() = codec;
+    }
+
+    /**
+       Sets the schema hash for this target and its clones, the generated code calls this.
+       The server reports calls of targets which were generated from other .erpc sources than itself
+    */
+    pub fn set_schema_hash(&self, schema_hash: Option<String>) {
+        *self.schema_hash.write() = schema_hash;
+    }
+
     pub async fn call(&self, request: protocol::Request) -> protocol::Response {
+        let timeout = *self.timeout.read();
+        self.call_with_timeout(request, timeout).await
+    }
+
+    /**
+       Calls the target and returns a Timeout error if no response arrived within the timeout.
+       Timed out requests over websockets are cancelled on the remote side.
+    */
+    pub async fn call_with_timeout(
+        &self,
+        request: protocol::Request,
+        timeout: Option<Duration>,
+    ) -> protocol::Response {
         match self.target_type {
             TargetType::HttpServer => {
-                let r = REQWEST_CLIENT
+                let codec = *self.codec.read();
+                let body = match codec.encode(&request.parameters) {
+                    Ok(v) => v,
+                    Err(err) => return err.into(),
+                };
+
+                let mut r = REQWEST_CLIENT
                     .post(format!(
                         "{}/{}/{}",
                         self.address,
                         protocol::routes::HANDLERS_ROUTE,
                         request.identifier
                     ))
-                    .body(
-                        serde_json::to_vec(&request.parameters)
-                            .expect("Vec of json::Value should be ok"),
-                    );
+                    .header(reqwest::header::CONTENT_TYPE, codec.content_type())
+                    .body(body);
+
+                if let Some(timeout) = timeout {
+                    r = r.timeout(timeout);
+                }
+                if let Some(schema_hash) = self.schema_hash.read().as_ref() {
+                    r = r.header(protocol::routes::SCHEMA_HASH_HEADER, schema_hash);
+                }
 
                 let response = match r.send().await {
                     Ok(v) => v,
                     Err(err) => return SendableError::from(err).into(),
                 };
 
+                let status = response.status();
+                // responses without a known content type, e.g. from proxies, are read as json
+                let response_codec = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(Codec::from_content_type)
+                    .unwrap_or_default();
                 let bytes = match response.bytes().await {
                     Ok(v) => v,
                     Err(err) => return SendableError::from(err).into(),
                 };
 
+                if !status.is_success() {
+                    // the server sends the error in the body, the status code is the fallback
+                    return response_codec
+                        .decode::<SendableError>(&bytes)
+                        .unwrap_or_else(|_| SendableError::from_status_code(status.as_u16()))
+                        .into();
+                }
+
                 protocol::Response {
-                    body: serde_json::from_slice(&bytes).map_err(SendableError::from),
+                    body: response_codec.decode(&bytes),
                 }
             }
             TargetType::Browser => {
-                let request_over_socket_channel = match self.socket.read().as_ref() {
-                    Some(v) => v.requests.clone(),
+                let (request_over_socket_channel, in_flight) = match self.socket.read().as_ref() {
+                    Some(v) => (v.requests.clone(), v.in_flight.clone()),
                     None => return SendableError::from("Socket not set for this target").into(),
                 };
+                // held until the call returned
+                let _permit = match in_flight.acquire() {
+                    Ok(v) => v,
+                    Err(err) => return err.into(),
+                };
 
                 let id = nanoid!();
                 let (sender, reciever) = oneshot::channel::<protocol::socket::Response>();
@@ -82,43 +207,179 @@ impl Target {
                     requests.insert(id.clone(), sender);
                 }
 
-                match request_over_socket_channel.send(protocol::socket::Request {
-                    id: id.clone(),
-                    request,
-                }) {
+                match enqueue(
+                    &request_over_socket_channel,
+                    protocol::socket::SocketMessage::Request(protocol::socket::Request {
+                        id: id.clone(),
+                        request,
+                    }),
+                ) {
                     Ok(_) => {}
                     Err(err) => {
                         let mut requests = self.open_socket_requests.lock();
 
                         requests.remove(&id);
 
-                        return SendableError::from(format!(
-                            "Could not send request on socket: {err}"
-                        ))
-                        .into();
+                        error!("Could not send request on socket: {err}");
+                        return err.into();
                     }
                 }
 
-                let response = match reciever.await {
+                // cleans up if the call times out or its future is dropped before the response arrived
+                let open_request = OpenSocketRequest {
+                    id,
+                    open_socket_requests: self.open_socket_requests.clone(),
+                    requests: request_over_socket_channel,
+                    finished: false,
+                };
+
+                let response = match timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, reciever).await {
+                        Ok(v) => v,
+                        Err(_) => return SendableError::Timeout.into(),
+                    },
+                    None => reciever.await,
+                };
+                open_request.finish();
+
+                let response = match response {
                     Ok(v) => v,
                     Err(err) => {
-                        return SendableError::from(format!(
-                            "Could not await response channel: {err}"
-                        ))
-                        .into()
+                        error!("Could not await response channel: {err}");
+                        return SendableError::ConnectionClosed.into();
                     }
                 };
 
                 response.response
             }
+            TargetType::Loopback => {
+                let dispatcher = match &self.loopback {
+                    Some(v) => v,
+                    None => return SendableError::from("Server not set for this target").into(),
+                };
+
+                let call = async {
+                    match dispatcher.authenticate(RequestContext::default()).await {
+                        Ok(context) => dispatcher.call(request, context).await,
+                        Err(err) => err.into(),
+                    }
+                };
+
+                match timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                        Ok(v) => v,
+                        Err(_) => SendableError::Timeout.into(),
+                    },
+                    None => call.await,
+                }
+            }
+        }
+    }
+
+    /**
+       Calls a streaming endpoint of the target. Streams need a websocket, so only browser targets support them.
+       Dropping the receiver before the stream ended cancels it on the remote side.
+    */
+    pub fn call_stream(&self, request: protocol::Request) -> Result<StreamReceiver, SendableError> {
+        let requests = match self.target_type {
+            TargetType::HttpServer => {
+                return Err(SendableError::from(
+                    "Streams are not supported between http servers",
+                ))
+            }
+            TargetType::Browser => match self.socket.read().as_ref() {
+                Some(v) => v.requests.clone(),
+                None => return Err(SendableError::from("Socket not set for this target")),
+            },
+            TargetType::Loopback => match &self.loopback {
+                Some(v) => return Ok(self.loopback_stream(v.clone(), request)),
+                None => return Err(SendableError::from("Server not set for this target")),
+            },
+        };
+
+        let id = nanoid!();
+        let items = self.open_streams.open(id.clone(), requests.clone());
+
+        match enqueue(
+            &requests,
+            protocol::socket::SocketMessage::StreamRequest(protocol::socket::Request {
+                id,
+                request,
+            }),
+        ) {
+            Ok(_) => Ok(items),
+            Err(err) => {
+                error!("Could not send stream request on socket: {err}");
+                Err(err)
+            }
         }
     }
 
+    /**
+       Connects the stream of a loopback call with channels in place of a socket,
+       so it has the same backpressure and cancellation as streams over websockets
+    */
+    fn loopback_stream(
+        &self,
+        dispatcher: Dispatcher,
+        request: protocol::Request,
+    ) -> StreamReceiver {
+        let id = nanoid!();
+        // demand and cancellations of the receiver
+        let (requests_sender, requests_reciever) = flume::unbounded::<SocketMessage>();
+        // items of the producer
+        let (responses_sender, responses_reciever) = flume::unbounded::<SocketMessage>();
+
+        let items = self.open_streams.open(id.clone(), requests_sender);
+        let open_streams = self.open_streams.clone();
+
+        tokio::spawn(async move {
+            let stream = match dispatcher.authenticate(RequestContext::default()).await {
+                Ok(context) => dispatcher.call_stream(request, context).await,
+                Err(err) => stream::once(async move { Err(err) }).boxed(),
+            };
+
+            let producers = StreamProducers::default();
+            tokio::spawn(producers.start(id, stream, responses_sender));
+
+            // ends once the producer finished and the receiver was dropped
+            loop {
+                tokio::select! {
+                    Ok(message) = requests_reciever.recv_async() => match message {
+                        SocketMessage::StreamDemand(d) => producers.demand(d),
+                        SocketMessage::Cancel(c) => producers.cancel(&c.id),
+                        _ => {}
+                    },
+                    Ok(message) = responses_reciever.recv_async() => open_streams.dispatch(message),
+                    else => break,
+                }
+            }
+        });
+
+        items
+    }
+
+    /**
+       The id of the socket the target calls, None if no socket is set
+    */
+    pub fn socket_id(&self) -> Option<String> {
+        self.socket.read().as_ref().map(|v| v.id.clone())
+    }
+
     pub fn set_socket(&mut self, socket: Socket) {
         self.socket.write().replace(socket.clone());
         let open_socket_requests = self.open_socket_requests.clone();
+        let open_streams = self.open_streams.clone();
         tokio::spawn(async move {
-            while let Ok(response) = socket.responses.recv_async().await {
+            while let Ok(message) = socket.responses.recv_async().await {
+                let response = match message {
+                    protocol::socket::SocketMessage::Response(v) => v,
+                    message => {
+                        open_streams.dispatch(message);
+                        continue;
+                    }
+                };
+
                 let mut requests = open_socket_requests.lock();
 
                 let return_channel = match requests.remove(&response.id) {
@@ -134,6 +395,50 @@ impl Target {
                     Err(ret_res) => error!("Could not send response for {}", ret_res.id),
                 };
             }
+
+            // the socket is closed, dropping the senders fails all requests which are still open
+            open_socket_requests.lock().clear();
+            open_streams.clear();
         });
     }
 }
+
+/**
+   A request which waits for the response of a browser. If it is dropped before the response arrived,
+   because the call timed out or its future was dropped, nobody waits for the response anymore.
+   Its entry is removed then, since it would never be removed otherwise, and the request is cancelled on the remote side.
+*/
+struct OpenSocketRequest {
+    id: String,
+    open_socket_requests: OpenSocketRequests,
+    requests: flume::Sender<SocketMessage>,
+    finished: bool,
+}
+
+impl OpenSocketRequest {
+    /**
+       Marks the request as answered, the response channel resolved and the entry is already gone
+    */
+    fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for OpenSocketRequest {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.open_socket_requests.lock().remove(&self.id);
+
+        if let Err(err) = enqueue(
+            &self.requests,
+            SocketMessage::Cancel(protocol::socket::Cancel {
+                id: self.id.clone(),
+            }),
+        ) {
+            error!("Could not send cancel on socket: {err}");
+        }
+    }
+}
@@ -0,0 +1,13 @@
+import { ERPCServer, ServerOptions } from "@easy-rpc/node"
+
+export default class Backend extends ERPCServer {
+    /**
+        @param options The options to set for the easy-rpc object
+        @param callbacks Callbacks to register for this server
+    */
+    constructor(options: ServerOptions, callbacks?: {
+    }) {
+        super(options, "http-server", false, "Backend", "ab331237d32d9fbb")
+    }
+
+}
\ No newline at end of file
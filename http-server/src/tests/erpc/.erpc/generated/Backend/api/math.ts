@@ -0,0 +1,56 @@
+
+export interface DivisionError {
+    message: string
+}
+
+export default class math {
+    private server: any
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It can be used to set the server of the object.
+    */
+    private setERPCServer(server: any) {
+        this.server = server
+
+        // trigger the setters to set the handlers on the server object
+        if (this.add) {
+            this.add = this.add
+        }
+        if (this.divide) {
+            this.divide = this.divide
+        }
+    }
+
+    constructor(callbacks?: {
+        add: (a: number, b: number) => Promise<number>
+        divide: (a: number, b: number) => Promise<{ ok: true, value: number } | { ok: false, error: DivisionError }>
+    }) {
+        if (callbacks?.add) {
+            this.add = callbacks.add
+        }
+
+        if (callbacks?.divide) {
+            this.divide = callbacks.divide
+        }
+
+    }
+
+    private _add: (a: number, b: number) => Promise<number> = undefined as any
+    set add(value: (a: number, b: number) => Promise<number>) {
+        this._add = value
+        this.server?.registerERPCHandler(value, "api/math/add", false, [], {"parameters":[{"name":"a","optional":false,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}},{"name":"b","optional":false,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}}],"types":{}})
+    }
+    get add() {
+        return this._add
+    }
+
+    private _divide: (a: number, b: number) => Promise<{ ok: true, value: number } | { ok: false, error: DivisionError }> = undefined as any
+    set divide(value: (a: number, b: number) => Promise<{ ok: true, value: number } | { ok: false, error: DivisionError }>) {
+        this._divide = value
+        this.server?.registerERPCHandler(value, "api/math/divide", true, [], {"parameters":[{"name":"a","optional":false,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}},{"name":"b","optional":false,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}}],"types":{}})
+    }
+    get divide() {
+        return this._divide
+    }
+
+
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+import { ERPCTarget, TargetOptions } from "@easy-rpc/node"
+
+export default class Frontend extends ERPCTarget {
+    /**
+        @param options The options to set for the easy-rpc object
+    */
+    constructor(options: TargetOptions) {
+        super(options, "browser")
+    }
+}
\ No newline at end of file
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use erpc::protocol::{
+        socket::{Cancel, SocketMessage},
+        SendableError,
+    };
+
+    use crate::limits::{enqueue, InFlight};
+
+    fn cancel() -> SocketMessage {
+        SocketMessage::Cancel(Cancel {
+            id: "1".to_string(),
+        })
+    }
+
+    #[test]
+    fn in_flight_limit() {
+        let in_flight = InFlight::new(2);
+
+        let first = in_flight.acquire().unwrap();
+        let _second = in_flight.acquire().unwrap();
+        assert_eq!(in_flight.count(), 2);
+        assert!(matches!(
+            in_flight.clone().acquire(),
+            Err(SendableError::Overloaded)
+        ));
+
+        // dropping a permit frees its slot
+        drop(first);
+        assert_eq!(in_flight.count(), 1);
+        assert!(in_flight.acquire().is_ok());
+    }
+
+    #[test]
+    fn full_queue() {
+        let (sender, reciever) = flume::bounded(1);
+
+        assert_eq!(enqueue(&sender, cancel()), Ok(()));
+        assert_eq!(enqueue(&sender, cancel()), Err(SendableError::Overloaded));
+
+        drop(reciever);
+        assert_eq!(
+            enqueue(&sender, cancel()),
+            Err(SendableError::ConnectionClosed)
+        );
+    }
+}
@@ -1 +1,6 @@
-mod server;
\ No newline at end of file
+mod limits;
+mod registry;
+mod relay;
+mod requests;
+mod schema_hash;
+mod server;
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use erpc::protocol::{
+        socket::{Response, SocketMessage},
+        Request, SendableError,
+    };
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    use crate::{registry::SocketRegistry, DisconnectReason, InFlight, RequestContext, Socket};
+
+    fn socket(id: &str, role: &str, requests: flume::Sender<SocketMessage>) -> Socket {
+        Socket {
+            id: id.to_string(),
+            requests,
+            responses: flume::unbounded().1,
+            role: role.to_string(),
+            in_flight: InFlight::new(16),
+        }
+    }
+
+    /**
+       Registers a socket which answers every request with its id and the parameter
+    */
+    fn answering_socket(sockets: &SocketRegistry, id: &str, role: &str, auth: serde_json::Value) {
+        let (sender, reciever) = flume::unbounded();
+        sockets.register(
+            socket(id, role, sender),
+            RequestContext {
+                auth,
+                ..Default::default()
+            },
+        );
+
+        let sockets = sockets.clone();
+        let socket_id = id.to_string();
+        tokio::spawn(async move {
+            while let Ok(message) = reciever.recv_async().await {
+                if let SocketMessage::Request(r) = message {
+                    let value = json!([socket_id, r.request.parameters.into_arguments()[0]]);
+                    sockets.respond(Response {
+                        id: r.id,
+                        response: value.into(),
+                    });
+                }
+            }
+        });
+    }
+
+    fn request() -> Request {
+        Request {
+            identifier: "notify".to_string(),
+            parameters: vec![json!("changed")].into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn broadcast() {
+        let sockets = SocketRegistry::default();
+        answering_socket(&sockets, "a", "Frontend", json!("admin"));
+        answering_socket(&sockets, "b", "Frontend", json!("user"));
+        answering_socket(&sockets, "c", "Admin", json!("admin"));
+
+        let results = sockets
+            .broadcast("Frontend", request(), |_| true, None)
+            .await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["a"].body, Ok(json!(["a", "changed"])));
+        assert_eq!(results["b"].body, Ok(json!(["b", "changed"])));
+
+        let results = sockets
+            .broadcast(
+                "Frontend",
+                request(),
+                |context| context.auth == json!("admin"),
+                None,
+            )
+            .await;
+        assert_eq!(results.keys().collect::<Vec<_>>(), vec!["a"]);
+
+        let results = sockets
+            .broadcast("Missing", request(), |_| true, None)
+            .await;
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn broadcast_failures() {
+        let sockets = SocketRegistry::default();
+        answering_socket(&sockets, "a", "Frontend", json!(null));
+
+        // a socket which never answers
+        let (sender, reciever) = flume::unbounded();
+        sockets.register(
+            socket("silent", "Frontend", sender),
+            RequestContext::default(),
+        );
+
+        let results = sockets
+            .broadcast(
+                "Frontend",
+                request(),
+                |_| true,
+                Some(Duration::from_millis(100)),
+            )
+            .await;
+        assert_eq!(results["a"].body, Ok(json!(["a", "changed"])));
+        assert_eq!(results["silent"].body, Err(SendableError::Timeout));
+
+        // the timed out request is cancelled on the socket
+        assert!(matches!(
+            reciever.try_recv().unwrap(),
+            SocketMessage::Request(_)
+        ));
+        assert!(matches!(
+            reciever.try_recv().unwrap(),
+            SocketMessage::Cancel(_)
+        ));
+
+        // calls to sockets which close fail instead of waiting forever
+        let closing = sockets.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            closing.unregister("silent", DisconnectReason::Closed);
+        });
+        let results = sockets
+            .broadcast("Frontend", request(), |_| true, None)
+            .await;
+        assert_eq!(results["silent"].body, Err(SendableError::ConnectionClosed));
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn lookup_and_disconnect() {
+        let sockets = SocketRegistry::default();
+        sockets.register(
+            socket("a", "Frontend", flume::unbounded().0),
+            RequestContext::default(),
+        );
+        sockets.register(
+            socket("b", "Admin", flume::unbounded().0),
+            RequestContext::default(),
+        );
+
+        let mut ids = sockets
+            .sockets(None)
+            .into_iter()
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec!["a", "b"]);
+        let admins = sockets.sockets(Some("Admin"));
+        assert_eq!(admins.len(), 1);
+        assert_eq!(admins[0].id, "b");
+        assert_eq!(
+            sockets.socket("a").map(|v| v.role),
+            Some("Frontend".to_string())
+        );
+
+        let disconnected = Arc::new(Mutex::new(vec![]));
+        let listener_disconnected = disconnected.clone();
+        sockets.on_disconnect(Box::new(move |socket, reason| {
+            listener_disconnected
+                .lock()
+                .push((socket.id.clone(), reason.clone()));
+        }));
+
+        sockets.unregister("a", DisconnectReason::ConnectionLost("reset".to_string()));
+        // sockets which are already gone are not reported twice
+        sockets.unregister("a", DisconnectReason::Closed);
+
+        assert_eq!(
+            *disconnected.lock(),
+            vec![(
+                "a".to_string(),
+                DisconnectReason::ConnectionLost("reset".to_string())
+            )]
+        );
+        assert!(sockets.socket("a").is_none());
+        assert_eq!(sockets.sockets(Some("Frontend")).len(), 0);
+    }
+}
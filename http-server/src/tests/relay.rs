@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use erpc::protocol::{
+        socket::{Cancel, RelayRequest, Response, SocketMessage},
+        Request, SendableError,
+    };
+    use serde_json::json;
+
+    use crate::{
+        registry::SocketRegistry, relay::Relay, DisconnectReason, InFlight, RequestContext, Socket,
+    };
+
+    fn relay_request(id: &str, role: &str, socket_id: &str) -> RelayRequest {
+        RelayRequest {
+            id: id.to_string(),
+            role: role.to_string(),
+            socket_id: socket_id.to_string(),
+            request: Request {
+                identifier: "greet".to_string(),
+                parameters: vec![json!("a")].into(),
+            },
+        }
+    }
+
+    fn register(
+        sockets: &SocketRegistry,
+        id: &str,
+        role: &str,
+        messages: flume::Sender<SocketMessage>,
+    ) {
+        sockets.register(
+            Socket {
+                id: id.to_string(),
+                requests: messages,
+                responses: flume::unbounded().1,
+                role: role.to_string(),
+                in_flight: InFlight::new(16),
+            },
+            RequestContext::default(),
+        );
+    }
+
+    fn response(message: SocketMessage) -> Response {
+        match message {
+            SocketMessage::Response(r) => r,
+            v => panic!("Expected a response, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn forward_and_respond() {
+        let sockets = SocketRegistry::default();
+        let relay = Relay::new(sockets.clone());
+        let (a_sender, a_reciever) = flume::unbounded();
+        let (b_sender, b_reciever) = flume::unbounded();
+        register(&sockets, "a", "Frontend", a_sender);
+        register(&sockets, "b", "Frontend", b_sender);
+
+        relay.forward("a", relay_request("1", "Frontend", "b"));
+        let forwarded = match b_reciever.try_recv().unwrap() {
+            SocketMessage::Request(r) => r,
+            v => panic!("Expected a request, got {:?}", v),
+        };
+        assert_eq!(forwarded.request.identifier, "greet");
+
+        // responses to requests of the server itself are not relayed
+        let unrelated = relay.respond(Response {
+            id: "other".to_string(),
+            response: json!(1).into(),
+        });
+        assert_eq!(unrelated.map(|v| v.id), Some("other".to_string()));
+
+        assert!(relay
+            .respond(Response {
+                id: forwarded.id,
+                response: json!("hello a").into(),
+            })
+            .is_none());
+        let answer = response(a_reciever.try_recv().unwrap());
+        assert_eq!(answer.id, "1");
+        assert_eq!(answer.response.body, Ok(json!("hello a")));
+    }
+
+    #[test]
+    fn unknown_destination() {
+        let sockets = SocketRegistry::default();
+        let relay = Relay::new(sockets.clone());
+        let (a_sender, a_reciever) = flume::unbounded();
+        let (b_sender, b_reciever) = flume::unbounded();
+        register(&sockets, "a", "Frontend", a_sender);
+        register(&sockets, "b", "Admin", b_sender);
+
+        relay.forward("a", relay_request("1", "Frontend", "missing"));
+        let answer = response(a_reciever.try_recv().unwrap());
+        assert_eq!(answer.id, "1");
+        assert_eq!(answer.response.body, Err(SendableError::ConnectionClosed));
+
+        // the role has to match the one the destination connected with
+        relay.forward("a", relay_request("2", "Frontend", "b"));
+        let answer = response(a_reciever.try_recv().unwrap());
+        assert_eq!(answer.id, "2");
+        assert!(b_reciever.is_empty());
+    }
+
+    #[test]
+    fn cancel_and_close() {
+        let sockets = SocketRegistry::default();
+        let relay = Relay::new(sockets.clone());
+        let (a_sender, a_reciever) = flume::unbounded();
+        let (b_sender, b_reciever) = flume::unbounded();
+        register(&sockets, "a", "Frontend", a_sender);
+        register(&sockets, "b", "Frontend", b_sender);
+
+        relay.forward("a", relay_request("1", "Frontend", "b"));
+        let forwarded = b_reciever.try_recv().unwrap();
+        relay.cancel("a", "1");
+        match b_reciever.try_recv().unwrap() {
+            SocketMessage::Cancel(Cancel { id }) => assert_eq!(id, forwarded.id()),
+            v => panic!("Expected a cancel, got {:?}", v),
+        }
+
+        // requests to a socket which closed fail instead of waiting forever
+        relay.forward("a", relay_request("2", "Frontend", "b"));
+        relay.close("b");
+        sockets.unregister("b", DisconnectReason::Closed);
+        let answer = response(a_reciever.try_recv().unwrap());
+        assert_eq!(answer.id, "2");
+        assert_eq!(answer.response.body, Err(SendableError::ConnectionClosed));
+
+        relay.forward("a", relay_request("3", "Frontend", "b"));
+        let answer = response(a_reciever.try_recv().unwrap());
+        assert_eq!(answer.response.body, Err(SendableError::ConnectionClosed));
+    }
+}
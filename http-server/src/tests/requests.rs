@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use erpc::protocol::{socket::SocketMessage, Request, SendableError};
+    use serde_json::json;
+    use tokio::time::{sleep, timeout};
+
+    use crate::{requests::SocketRequests, RequestContext, Server, ServerOptions};
+
+    fn server() -> Server {
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register_handler(
+            |ms: u64| async move {
+                sleep(Duration::from_millis(ms)).await;
+                ms
+            },
+            "wait",
+        );
+        server
+    }
+
+    fn start(
+        requests: &SocketRequests,
+        server: &Server,
+        id: &str,
+        ms: u64,
+        outgoing: &flume::Sender<SocketMessage>,
+    ) {
+        let dispatcher = server.dispatcher();
+        requests.start(
+            id.to_string(),
+            async move {
+                dispatcher
+                    .call(
+                        Request {
+                            identifier: "wait".to_string(),
+                            parameters: vec![json!(ms)].into(),
+                        },
+                        RequestContext::default(),
+                    )
+                    .await
+            },
+            outgoing.clone(),
+        );
+    }
+
+    async fn next_response(outgoing: &flume::Receiver<SocketMessage>) -> String {
+        match timeout(Duration::from_secs(2), outgoing.recv_async()).await {
+            Ok(Ok(SocketMessage::Response(v))) => v.id,
+            v => panic!("Expected a response, got {:?}", v),
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_call_does_not_block() {
+        let server = server();
+        let requests = SocketRequests::new(4, 4);
+        let (sender, reciever) = flume::bounded(4);
+
+        start(&requests, &server, "slow", 500, &sender);
+        start(&requests, &server, "fast", 0, &sender);
+
+        // responses are sent in the order the calls finished
+        assert_eq!(next_response(&reciever).await, "fast");
+        assert_eq!(next_response(&reciever).await, "slow");
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit() {
+        let server = server();
+        let requests = SocketRequests::new(1, 4);
+        let (sender, reciever) = flume::bounded(4);
+
+        start(&requests, &server, "slow", 200, &sender);
+        start(&requests, &server, "fast", 0, &sender);
+
+        // the fast call waits until the slow one gave up its slot
+        assert_eq!(next_response(&reciever).await, "slow");
+        assert_eq!(next_response(&reciever).await, "fast");
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let server = server();
+        let requests = SocketRequests::new(1, 4);
+        let (sender, reciever) = flume::bounded(4);
+
+        start(&requests, &server, "slow", 60_000, &sender);
+        start(&requests, &server, "fast", 0, &sender);
+        sleep(Duration::from_millis(50)).await;
+        assert!(reciever.is_empty());
+
+        // cancelling the running call frees its slot for the waiting one
+        requests.cancel("slow");
+        assert_eq!(next_response(&reciever).await, "fast");
+
+        start(&requests, &server, "other", 60_000, &sender);
+        requests.clear();
+        sleep(Duration::from_millis(50)).await;
+        assert!(reciever.is_empty());
+    }
+
+    async fn next_error(outgoing: &flume::Receiver<SocketMessage>) -> (String, SendableError) {
+        match timeout(Duration::from_secs(2), outgoing.recv_async()).await {
+            Ok(Ok(SocketMessage::Response(v))) => match v.response.body {
+                Err(err) => (v.id, err),
+                v => panic!("Expected an error, got {:?}", v),
+            },
+            v => panic!("Expected a response, got {:?}", v),
+        }
+    }
+
+    #[tokio::test]
+    async fn waiting_limit() {
+        let server = server();
+        let requests = SocketRequests::new(1, 1);
+        let (sender, reciever) = flume::bounded(4);
+
+        start(&requests, &server, "running", 200, &sender);
+        start(&requests, &server, "waiting", 0, &sender);
+        start(&requests, &server, "rejected", 0, &sender);
+
+        // the third call neither runs nor waits, it is answered right away
+        assert_eq!(
+            next_error(&reciever).await,
+            ("rejected".to_string(), SendableError::Overloaded)
+        );
+        assert_eq!(next_response(&reciever).await, "running");
+        assert_eq!(next_response(&reciever).await, "waiting");
+
+        // the finished calls gave up their slots
+        start(&requests, &server, "later", 0, &sender);
+        assert_eq!(next_response(&reciever).await, "later");
+    }
+
+    #[tokio::test]
+    async fn duplicate_id() {
+        let server = server();
+        let requests = SocketRequests::new(4, 4);
+        let (sender, reciever) = flume::bounded(4);
+
+        start(&requests, &server, "call", 200, &sender);
+        start(&requests, &server, "call", 0, &sender);
+
+        match next_error(&reciever).await {
+            (id, SendableError::BadRequest { .. }) => assert_eq!(id, "call"),
+            v => panic!("Expected a bad request, got {:?}", v),
+        }
+        // the running call can still be cancelled
+        requests.cancel("call");
+        sleep(Duration::from_millis(300)).await;
+        assert!(reciever.is_empty());
+    }
+}
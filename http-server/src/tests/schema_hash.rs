@@ -0,0 +1,16 @@
+#[cfg(test)]
+mod tests {
+    use crate::schema_hash::is_schema_hash;
+
+    #[test]
+    fn schema_hash_format() {
+        assert!(is_schema_hash("0123456789abcdef"));
+
+        assert!(!is_schema_hash(""));
+        assert!(!is_schema_hash("abc"));
+        assert!(!is_schema_hash("0123456789ABCDEF"));
+        assert!(!is_schema_hash("0123456789abcdeg"));
+        assert!(!is_schema_hash("0123456789abcdef0"));
+        assert!(!is_schema_hash("0123456789abcde\n"));
+    }
+}
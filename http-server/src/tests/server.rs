@@ -1,19 +1,45 @@
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{collections::HashMap, time::Duration};
 
-    use tokio::time::sleep;
+    use erpc::{
+        codec::Codec,
+        heartbeat::HeartbeatOptions,
+        protocol::{
+            socket::{Cancel, Handshake, SocketMessage, StreamEnd, StreamItem},
+            Parameters, Request, SendableError,
+        },
+        schema::{EndpointSchema, FieldSchema, Schema},
+        stream::STREAM_WINDOW,
+        target::TargetType,
+    };
+    use futures::{SinkExt, StreamExt};
+    use serde_json::json;
+    use tokio::{net::TcpStream, time::sleep};
+    use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
 
-    use crate::Server;
+    use crate::{
+        DisconnectReason, InFlight, MiddlewareCall, RequestContext, Server, ServerOptions, Socket,
+        Target, TlsOptions,
+    };
+
+    fn options(port: u16, allowed_cors_origins: Vec<String>) -> ServerOptions {
+        ServerOptions {
+            port,
+            allowed_cors_origins,
+            bind_address: Some("127.0.0.1".to_string()),
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn creation() {
-        Server::new(5678, vec!["http://localhost".to_string()], true);
+        Server::new(options(5678, vec!["http://localhost".to_string()]), true);
     }
 
     #[tokio::test]
     async fn run_stop() {
-        let server = Server::new(5678, vec!["http://localhost".to_string()], true);
+        let server = Server::new(options(5678, vec!["http://localhost".to_string()]), true);
         let s2 = server.clone();
         tokio::spawn(async move {
             sleep(Duration::from_millis(3000)).await;
@@ -21,4 +47,1462 @@ mod tests {
         });
         server.run().await;
     }
+
+    #[tokio::test]
+    async fn typed_errors() {
+        let mut server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_handler(
+            |name: String, amount: i32| async move { format!("{name}{amount}") },
+            "concat",
+        );
+        server.register_handler(
+            || async move {
+                if true {
+                    panic!("handler failed");
+                }
+            },
+            "panics",
+        );
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+
+        let response = target
+            .call(Request {
+                identifier: "concat".to_string(),
+                parameters: vec![json!("a"), json!("b")].into(),
+            })
+            .await;
+        match response.body {
+            Err(SendableError::BadRequest { path, .. }) => {
+                assert_eq!(path, Some("[1]".to_string()))
+            }
+            v => panic!("Expected a bad request, got {:?}", v),
+        }
+
+        let response = target
+            .call(Request {
+                identifier: "panics".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::HandlerPanicked));
+
+        let response = target
+            .call(Request {
+                identifier: "missing".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::NotFound));
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn application_errors() {
+        let mut server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_fallible_handler::<_, (String,), String, String>(
+            |password: String| async move {
+                if password == "secret" {
+                    Ok("session".to_string())
+                } else {
+                    Err("wrong password".to_string())
+                }
+            },
+            "login",
+        );
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: vec![json!("secret")].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!("session")));
+
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: vec![json!("guess")].into(),
+            })
+            .await;
+        assert_eq!(
+            response.body,
+            Err(SendableError::Application {
+                error: json!("wrong password")
+            })
+        );
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn middleware() {
+        let mut server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+        server.register_middleware(
+            |mut call: MiddlewareCall| async move {
+                call.parameters = call
+                    .parameters
+                    .into_arguments()
+                    .iter()
+                    .map(|v| json!(v.as_i64().unwrap_or(0) * 2))
+                    .collect();
+                call.context.insert("doubled".to_string(), json!(true));
+                Ok(call)
+            },
+            "double",
+        );
+        server.register_middleware(
+            |call: MiddlewareCall| async move {
+                match call.context.get("doubled") {
+                    Some(_) => Err(SendableError::Unauthorized),
+                    None => Ok(call),
+                }
+            },
+            "rejectDoubled",
+        );
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+        let request = Request {
+            identifier: "add".to_string(),
+            parameters: vec![json!(1), json!(2)].into(),
+        };
+
+        let response = target.call(request.clone()).await;
+        assert_eq!(response.body, Ok(json!(3)));
+
+        server.set_endpoint_middleware("add", vec!["double".to_string()]);
+        let response = target.call(request.clone()).await;
+        assert_eq!(response.body, Ok(json!(6)));
+
+        server.set_endpoint_middleware(
+            "add",
+            vec!["double".to_string(), "rejectDoubled".to_string()],
+        );
+        let response = target.call(request.clone()).await;
+        assert_eq!(response.body, Err(SendableError::Unauthorized));
+
+        // a missing middleware must not be skipped
+        server.set_endpoint_middleware("add", vec!["missing".to_string()]);
+        let response = target.call(request).await;
+        assert_eq!(response.body, Err(SendableError::Internal));
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn middleware_context() {
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register_handler(
+            || async move { RequestContext::current().unwrap().middleware },
+            "whoami",
+        );
+        server.register_middleware(
+            |mut call: MiddlewareCall| async move {
+                // the middleware sees who made the call
+                let user = call.caller.auth["user"].clone();
+                call.context.insert("user".to_string(), user);
+                Ok(call)
+            },
+            "user",
+        );
+        server.set_endpoint_middleware("whoami", vec!["user".to_string()]);
+
+        let response = server
+            .dispatcher()
+            .call(
+                Request {
+                    identifier: "whoami".to_string(),
+                    parameters: vec![].into(),
+                },
+                RequestContext {
+                    auth: json!({ "user": "admin" }),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert_eq!(response.body, Ok(json!({ "user": "admin" })));
+    }
+
+    #[tokio::test]
+    async fn non_sync_futures() {
+        let mut server = Server::new(ServerOptions::default(), false);
+        // a Cell is Send but not Sync, so neither are futures holding it across an await
+        server.register_handler(
+            |a: i32| async move {
+                let value = std::cell::Cell::new(a);
+                tokio::task::yield_now().await;
+                value.get() * 2
+            },
+            "double",
+        );
+        server.register_middleware(
+            |call: MiddlewareCall| async move {
+                let call = std::cell::Cell::new(Some(call));
+                tokio::task::yield_now().await;
+                Ok(call.take().unwrap())
+            },
+            "pass",
+        );
+        server.set_endpoint_middleware("double", vec!["pass".to_string()]);
+
+        let response = server
+            .dispatcher()
+            .call(
+                Request {
+                    identifier: "double".to_string(),
+                    parameters: vec![json!(21)].into(),
+                },
+                RequestContext::default(),
+            )
+            .await;
+        assert_eq!(response.body, Ok(json!(42)));
+    }
+
+    #[tokio::test]
+    async fn schema_validation() {
+        let server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_raw_handler(
+            Box::new(|request, _| {
+                Box::pin(async move { request.parameters.into_arguments()[0].clone().into() })
+            }),
+            "echo".to_string(),
+        );
+        server.set_endpoint_schema(
+            "echo",
+            EndpointSchema {
+                parameters: vec![FieldSchema {
+                    name: "amount".to_string(),
+                    optional: false,
+                    schema: Schema::Integer {
+                        min: -128,
+                        max: 127,
+                    },
+                }],
+                types: HashMap::new(),
+                named: false,
+            },
+        );
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+
+        let response = target
+            .call(Request {
+                identifier: "echo".to_string(),
+                parameters: vec![json!(5)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!(5)));
+
+        for parameters in [vec![json!("5")], vec![json!(300)], vec![]] {
+            let response = target
+                .call(Request {
+                    identifier: "echo".to_string(),
+                    parameters: parameters.into(),
+                })
+                .await;
+            match response.body {
+                Err(SendableError::BadRequest { path, .. }) => {
+                    assert_eq!(path, Some("[0]".to_string()))
+                }
+                v => panic!("Expected a bad request, got {:?}", v),
+            }
+        }
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn message_pack() {
+        let mut server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_handler(
+            |name: String, amount: i32| async move { format!("{name}{amount}") },
+            "concat",
+        );
+        server.register_fallible_handler::<_, (String,), String, String>(
+            |password: String| async move {
+                if password == "secret" {
+                    Ok("session".to_string())
+                } else {
+                    Err("wrong password".to_string())
+                }
+            },
+            "login",
+        );
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+        target.set_codec(Codec::MessagePack);
+
+        let response = target
+            .call(Request {
+                identifier: "concat".to_string(),
+                parameters: vec![json!("a"), json!(1)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!("a1")));
+
+        let response = target
+            .call(Request {
+                identifier: "concat".to_string(),
+                parameters: vec![json!("a"), json!("b")].into(),
+            })
+            .await;
+        match response.body {
+            Err(SendableError::BadRequest { path, .. }) => {
+                assert_eq!(path, Some("[1]".to_string()))
+            }
+            v => panic!("Expected a bad request, got {:?}", v),
+        }
+
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: vec![json!("guess")].into(),
+            })
+            .await;
+        assert_eq!(
+            response.body,
+            Err(SendableError::Application {
+                error: json!("wrong password")
+            })
+        );
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn socket_call_timeout() {
+        let (requests_sender, requests_reciever) = flume::unbounded();
+        let (_responses_sender, responses_reciever) = flume::unbounded();
+
+        let mut target = Target::new(String::new(), TargetType::Browser);
+        target.set_socket(Socket {
+            id: "socket".to_string(),
+            requests: requests_sender,
+            responses: responses_reciever,
+            role: "Frontend".to_string(),
+            in_flight: InFlight::new(16),
+        });
+
+        let response = target
+            .call_with_timeout(
+                Request {
+                    identifier: "never/answered".to_string(),
+                    parameters: Parameters::default(),
+                },
+                Some(Duration::from_millis(100)),
+            )
+            .await;
+        assert_eq!(response.body, Err(SendableError::Timeout));
+
+        let request_id = match requests_reciever.recv_async().await.unwrap() {
+            SocketMessage::Request(r) => r.id,
+            _ => panic!("Expected a request"),
+        };
+        match requests_reciever.recv_async().await.unwrap() {
+            SocketMessage::Cancel(c) => assert_eq!(c.id, request_id),
+            _ => panic!("Expected a cancel"),
+        }
+    }
+
+    #[tokio::test]
+    async fn socket_call_dropped() {
+        let (requests_sender, requests_reciever) = flume::unbounded();
+        let (_responses_sender, responses_reciever) = flume::unbounded();
+
+        let mut target = Target::new(String::new(), TargetType::Browser);
+        target.set_socket(Socket {
+            id: "socket".to_string(),
+            requests: requests_sender,
+            responses: responses_reciever,
+            role: "Frontend".to_string(),
+            in_flight: InFlight::new(16),
+        });
+
+        // the call waits forever, dropping it has to clean up like a timeout
+        let call = target.call(Request {
+            identifier: "never/answered".to_string(),
+            parameters: Parameters::default(),
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(100), call)
+            .await
+            .is_err());
+
+        let request_id = match requests_reciever.recv_async().await.unwrap() {
+            SocketMessage::Request(r) => r.id,
+            _ => panic!("Expected a request"),
+        };
+        match requests_reciever.recv_async().await.unwrap() {
+            SocketMessage::Cancel(c) => assert_eq!(c.id, request_id),
+            _ => panic!("Expected a cancel"),
+        }
+    }
+
+    #[tokio::test]
+    async fn socket_closed() {
+        let (requests_sender, requests_reciever) = flume::unbounded();
+        let (responses_sender, responses_reciever) = flume::unbounded::<SocketMessage>();
+
+        let mut target = Target::new(String::new(), TargetType::Browser);
+        target.set_socket(Socket {
+            id: "socket".to_string(),
+            requests: requests_sender,
+            responses: responses_reciever,
+            role: "Frontend".to_string(),
+            in_flight: InFlight::new(16),
+        });
+        assert_eq!(target.socket_id(), Some("socket".to_string()));
+
+        let call = tokio::spawn({
+            let target = target.clone();
+            async move {
+                target
+                    .call(Request {
+                        identifier: "never/answered".to_string(),
+                        parameters: Parameters::default(),
+                    })
+                    .await
+            }
+        });
+        assert!(matches!(
+            requests_reciever.recv_async().await.unwrap(),
+            SocketMessage::Request(_)
+        ));
+
+        // the socket handler drops its channels once the connection ended
+        drop(responses_sender);
+        drop(requests_reciever);
+        assert_eq!(
+            call.await.unwrap().body,
+            Err(SendableError::ConnectionClosed)
+        );
+
+        let response = target
+            .call(Request {
+                identifier: "never/answered".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::ConnectionClosed));
+    }
+
+    #[tokio::test]
+    async fn socket_overloaded() {
+        let (requests_sender, requests_reciever) = flume::bounded(1);
+        let (_responses_sender, responses_reciever) = flume::bounded::<SocketMessage>(1);
+
+        let mut target = Target::new(String::new(), TargetType::Browser);
+        let socket = Socket {
+            id: "socket".to_string(),
+            requests: requests_sender,
+            responses: responses_reciever,
+            role: "Frontend".to_string(),
+            in_flight: InFlight::new(2),
+        };
+        target.set_socket(socket.clone());
+
+        let request = || Request {
+            identifier: "never/answered".to_string(),
+            parameters: Parameters::default(),
+        };
+        let waiting = tokio::spawn({
+            let target = target.clone();
+            async move { target.call(request()).await }
+        });
+        sleep(Duration::from_millis(50)).await;
+
+        // the queue of the socket holds only the first request
+        let response = target.call(request()).await;
+        assert_eq!(response.body, Err(SendableError::Overloaded));
+        assert_eq!(socket.metrics().outgoing_queue, 1);
+        assert_eq!(socket.metrics().in_flight_requests, 1);
+
+        // the queue has room again, but two calls are already waiting for a response
+        requests_reciever.drain();
+        let second = tokio::spawn({
+            let target = target.clone();
+            async move { target.call(request()).await }
+        });
+        sleep(Duration::from_millis(50)).await;
+        requests_reciever.drain();
+        let response = target.call(request()).await;
+        assert_eq!(response.body, Err(SendableError::Overloaded));
+        assert_eq!(socket.metrics().in_flight_requests, 2);
+
+        waiting.abort();
+        second.abort();
+        let _ = waiting.await;
+        let _ = second.await;
+        assert_eq!(socket.metrics().in_flight_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn socket_stream() {
+        let (requests_sender, requests_reciever) = flume::unbounded();
+        let (responses_sender, responses_reciever) = flume::unbounded();
+
+        let mut target = Target::new(String::new(), TargetType::Browser);
+        target.set_socket(Socket {
+            id: "socket".to_string(),
+            requests: requests_sender,
+            responses: responses_reciever,
+            role: "Frontend".to_string(),
+            in_flight: InFlight::new(16),
+        });
+
+        let mut items = target
+            .call_stream(Request {
+                identifier: "ticks".to_string(),
+                parameters: vec![json!("ABC")].into(),
+            })
+            .unwrap();
+
+        let id = match requests_reciever.recv_async().await.unwrap() {
+            SocketMessage::StreamRequest(r) => r.id,
+            _ => panic!("Expected a stream request"),
+        };
+
+        // the browser sends the whole window before the first demand
+        for i in 0..STREAM_WINDOW {
+            responses_sender
+                .send(SocketMessage::StreamItem(StreamItem {
+                    id: id.clone(),
+                    value: json!(i),
+                }))
+                .unwrap();
+        }
+        responses_sender
+            .send(SocketMessage::StreamEnd(StreamEnd { id: id.clone() }))
+            .unwrap();
+
+        for i in 0..STREAM_WINDOW {
+            assert_eq!(items.next().await, Some(Ok(json!(i))));
+        }
+        assert_eq!(items.next().await, None);
+
+        // consuming half of the window grants the browser more items
+        for _ in 0..2 {
+            match requests_reciever.recv_async().await.unwrap() {
+                SocketMessage::StreamDemand(d) => {
+                    assert_eq!(d.id, id);
+                    assert_eq!(d.amount, STREAM_WINDOW / 2);
+                }
+                _ => panic!("Expected a demand"),
+            }
+        }
+
+        // streams need a websocket
+        let target = Target::new(String::new(), TargetType::HttpServer);
+        assert!(target
+            .call_stream(Request {
+                identifier: "ticks".to_string(),
+                parameters: Parameters::default(),
+            })
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn authentication() {
+        let mut server = Server::new(options(0, vec!["*".to_string()]), false);
+        server.register_handler(
+            || async move {
+                let context = RequestContext::current().unwrap();
+                (context.auth, context.remote_address.is_some())
+            },
+            "whoami",
+        );
+        server.set_authenticator(|context: RequestContext| async move {
+            match context.headers.get("authorization") {
+                Some(token) if token == "Bearer secret" => Ok(json!({ "user": "admin" })),
+                _ => Err(SendableError::Unauthorized),
+            }
+        });
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+        let response = target
+            .call(Request {
+                identifier: "whoami".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::Unauthorized));
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{address}/handlers/whoami"))
+            .header(reqwest::header::AUTHORIZATION, "Bearer secret")
+            .body("[]")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.json::<serde_json::Value>().await.unwrap(),
+            json!([{ "user": "admin" }, true])
+        );
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn tls() {
+        let certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let mut server = Server::new(
+            ServerOptions {
+                port: 0,
+                allowed_cors_origins: vec!["*".to_string()],
+                bind_address: Some("127.0.0.1".to_string()),
+                tls: Some(TlsOptions {
+                    certificate: certificate.serialize_pem().unwrap().into_bytes(),
+                    key: certificate.serialize_private_key_pem().into_bytes(),
+                    http3: true,
+                }),
+                ..Default::default()
+            },
+            false,
+        );
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+
+        let s2 = server.clone();
+        tokio::spawn(async move {
+            s2.run().await.await;
+        });
+        let address = server.listening().await;
+        let port = address.rsplit_once(':').unwrap().1;
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        let response = client
+            .post(format!("https://localhost:{port}/handlers/add"))
+            .body("[1, 2]")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.json::<serde_json::Value>().await.unwrap(),
+            json!(3)
+        );
+
+        // plain http is not served anymore
+        let response = client
+            .post(format!("http://localhost:{port}/handlers/add"))
+            .body("[1, 2]")
+            .send()
+            .await;
+        assert!(!matches!(response, Ok(v) if v.status().is_success()));
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn ephemeral_port() {
+        let mut server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                ..Default::default()
+            },
+            false,
+        );
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+        assert_eq!(server.local_address(), None);
+
+        let serving = server.run().await;
+        let address = server.local_address().unwrap();
+        assert!(address.starts_with("127.0.0.1:"));
+        assert_ne!(address, "127.0.0.1:0");
+        assert_eq!(server.listening().await, address);
+        tokio::spawn(serving);
+
+        let target = Target::new(format!("http://{address}"), TargetType::HttpServer);
+        let response = target
+            .call(Request {
+                identifier: "add".to_string(),
+                parameters: vec![json!(1), json!(2)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!(3)));
+
+        server.stop().unwrap();
+        assert_eq!(server.local_address(), None);
+    }
+
+    #[tokio::test]
+    async fn ephemeral_port_tls() {
+        let certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+
+        // both acceptors report the address of their tcp listener
+        for http3 in [false, true] {
+            let mut server = Server::new(
+                ServerOptions {
+                    port: 0,
+                    bind_address: Some("127.0.0.1".to_string()),
+                    tls: Some(TlsOptions {
+                        certificate: certificate.serialize_pem().unwrap().into_bytes(),
+                        key: certificate.serialize_private_key_pem().into_bytes(),
+                        http3,
+                    }),
+                    ..Default::default()
+                },
+                false,
+            );
+            server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+
+            let serving = server.run().await;
+            let address = server.local_address().unwrap();
+            assert!(address.starts_with("127.0.0.1:"));
+            assert_ne!(address, "127.0.0.1:0");
+            tokio::spawn(serving);
+
+            let port = address.rsplit_once(':').unwrap().1;
+            let response = client
+                .post(format!("https://localhost:{port}/handlers/add"))
+                .body("[1, 2]")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(
+                response.json::<serde_json::Value>().await.unwrap(),
+                json!(3)
+            );
+
+            server.stop().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket() {
+        let path = std::env::temp_dir().join("erpc-http-server-test.sock");
+        let address = format!("unix:{}", path.display());
+        let server = Server::new(
+            ServerOptions {
+                bind_address: Some(address.clone()),
+                ..Default::default()
+            },
+            false,
+        );
+
+        let serving = server.run().await;
+        assert_eq!(server.local_address(), Some(address));
+        assert!(path.exists());
+        tokio::spawn(serving);
+
+        server.stop().unwrap();
+    }
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    /**
+       Runs the server on a free port and returns its address
+    */
+    async fn serve(server: &Server) -> String {
+        let serving = server.run().await;
+        tokio::spawn(serving);
+        server.local_address().unwrap()
+    }
+
+    /**
+       Connects to the server like a browser of the role does
+    */
+    async fn connect(address: &str, role: &str) -> Client {
+        tokio_tungstenite::connect_async(format!("ws://{address}/ws/{role}"))
+            .await
+            .unwrap()
+            .0
+    }
+
+    async fn send(client: &mut Client, message: SocketMessage) {
+        client
+            .send(tungstenite::Message::Binary(
+                message.encode(Codec::default()).unwrap(),
+            ))
+            .await
+            .unwrap();
+    }
+
+    /**
+       The next message of the server, None once it closed the connection
+    */
+    async fn receive(client: &mut Client) -> Option<SocketMessage> {
+        loop {
+            match client.next().await {
+                Some(Ok(tungstenite::Message::Binary(v))) => {
+                    return Some(SocketMessage::decode(&v, Codec::default()).unwrap())
+                }
+                Some(Ok(tungstenite::Message::Text(v))) => {
+                    return Some(SocketMessage::decode(v.as_bytes(), Codec::default()).unwrap())
+                }
+                Some(Ok(tungstenite::Message::Close(_))) | Some(Err(_)) | None => return None,
+                Some(Ok(_)) => continue,
+            }
+        }
+    }
+
+    /**
+       The reasons of the disconnects of the server, in the order they happened
+    */
+    fn disconnects(server: &Server) -> flume::Receiver<DisconnectReason> {
+        let (sender, receiver) = flume::unbounded();
+        server.on_disconnect(move |_, reason| {
+            let _ = sender.send(reason.clone());
+        });
+        receiver
+    }
+
+    #[tokio::test]
+    async fn heartbeat() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                heartbeat: Some(HeartbeatOptions {
+                    interval: Duration::from_millis(100),
+                    max_missed_pongs: 1,
+                }),
+                ..Default::default()
+            },
+            true,
+        );
+        let disconnects = disconnects(&server);
+        let address = serve(&server).await;
+        let mut client = connect(&address, "Frontend").await;
+
+        // a browser which answers the pings stays connected
+        for _ in 0..3 {
+            match receive(&mut client).await {
+                Some(SocketMessage::Ping(h)) => send(&mut client, SocketMessage::Pong(h)).await,
+                v => panic!("Expected a ping, got {:?}", v),
+            }
+        }
+        assert!(disconnects.is_empty());
+
+        // one which stops answering is dropped
+        let reason = tokio::time::timeout(Duration::from_secs(2), disconnects.recv_async())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reason, DisconnectReason::HeartbeatTimeout);
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn idle_timeout() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                idle_timeout: Some(Duration::from_millis(200)),
+                ..Default::default()
+            },
+            true,
+        );
+        let disconnects = disconnects(&server);
+        let address = serve(&server).await;
+        let mut client = connect(&address, "Frontend").await;
+
+        let started = std::time::Instant::now();
+        assert!(receive(&mut client).await.is_none());
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(
+            disconnects.recv_async().await.unwrap(),
+            DisconnectReason::IdleTimeout
+        );
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn announced_after_handshake() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                require_handshake: true,
+                ..Default::default()
+            },
+            true,
+        );
+        let address = serve(&server).await;
+
+        // a client which is rejected is never offered to the server
+        let mut client = connect(&address, "Frontend").await;
+        send(
+            &mut client,
+            SocketMessage::Cancel(Cancel {
+                id: "1".to_string(),
+            }),
+        )
+        .await;
+        assert!(matches!(
+            receive(&mut client).await,
+            Some(SocketMessage::HandshakeRejected(_))
+        ));
+        assert!(server.get_socket_broadcaster().is_empty());
+
+        let mut client = connect(&address, "Frontend").await;
+        sleep(Duration::from_millis(100)).await;
+        assert!(server.connected_sockets(None).is_empty());
+        assert!(server.get_socket_broadcaster().is_empty());
+
+        send(
+            &mut client,
+            SocketMessage::Handshake(Handshake::new(Codec::default(), None)),
+        )
+        .await;
+        assert!(matches!(
+            receive(&mut client).await,
+            Some(SocketMessage::HandshakeAccepted(_))
+        ));
+        assert_eq!(server.connected_sockets(None).len(), 1);
+        assert_eq!(server.get_socket_broadcaster().len(), 1);
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn announced_without_handshake() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                ..Default::default()
+            },
+            true,
+        );
+        let address = serve(&server).await;
+
+        // browsers which only receive calls never send a message
+        let _client = connect(&address, "Frontend").await;
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(server.connected_sockets(None).len(), 1);
+        assert_eq!(server.get_socket_broadcaster().len(), 1);
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn handshake_timeout() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                require_handshake: true,
+                handshake_timeout: Duration::from_millis(100),
+                ..Default::default()
+            },
+            true,
+        );
+        let address = serve(&server).await;
+
+        let mut client = connect(&address, "Frontend").await;
+        assert!(matches!(
+            receive(&mut client).await,
+            Some(SocketMessage::HandshakeRejected(_))
+        ));
+        assert!(receive(&mut client).await.is_none());
+        assert!(server.connected_sockets(None).is_empty());
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn unread_sockets() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                socket_queue_size: 1,
+                ..Default::default()
+            },
+            true,
+        );
+        let address = serve(&server).await;
+
+        // nobody reads the new sockets, so only the latest one is kept
+        let mut clients = vec![];
+        for _ in 0..3 {
+            let mut client = connect(&address, "Frontend").await;
+            send(
+                &mut client,
+                SocketMessage::Handshake(Handshake::new(Codec::default(), None)),
+            )
+            .await;
+            assert!(matches!(
+                receive(&mut client).await,
+                Some(SocketMessage::HandshakeAccepted(_))
+            ));
+            clients.push(client);
+        }
+
+        assert_eq!(server.connected_sockets(None).len(), 3);
+        let socket = server.get_socket_broadcaster().try_recv().unwrap();
+        assert!(server.get_socket_broadcaster().is_empty());
+        assert!(server
+            .connected_sockets(None)
+            .iter()
+            .any(|v| v.id == socket.id));
+
+        server.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn full_response_queue() {
+        let server = Server::new(
+            ServerOptions {
+                port: 0,
+                bind_address: Some("127.0.0.1".to_string()),
+                socket_queue_size: 1,
+                ..Default::default()
+            },
+            true,
+        );
+        let address = serve(&server).await;
+        let mut client = connect(&address, "Frontend").await;
+        let socket = server.get_socket_broadcaster().recv_async().await.unwrap();
+
+        // the browser answers faster than the targets read, the second response must not be dropped
+        for id in ["1", "2"] {
+            send(
+                &mut client,
+                SocketMessage::Response(erpc::protocol::socket::Response {
+                    id: id.to_string(),
+                    response: json!(id).into(),
+                }),
+            )
+            .await;
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        for id in ["1", "2"] {
+            let message =
+                tokio::time::timeout(Duration::from_secs(1), socket.responses.recv_async()).await;
+            match message.unwrap().unwrap() {
+                SocketMessage::Response(r) => assert_eq!(r.id, id),
+                message => panic!("Expected a response, got {message:?}"),
+            }
+        }
+
+        server.stop().unwrap();
+    }
+
+    #[cfg(feature = "macros")]
+    #[crate::handler("api/math/add", config = "src/tests/erpc/erpc.json")]
+    async fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    #[cfg(feature = "macros")]
+    #[derive(serde::Serialize)]
+    struct DivisionError {
+        message: String,
+    }
+
+    #[cfg(feature = "macros")]
+    #[crate::handler("api/math/divide", config = "src/tests/erpc/erpc.json")]
+    async fn divide(a: i32, b: i32) -> Result<i32, DivisionError> {
+        a.checked_div(b).ok_or(DivisionError {
+            message: "division by zero".to_string(),
+        })
+    }
+
+    #[cfg(feature = "macros")]
+    #[tokio::test]
+    async fn checked_handlers() {
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register(add);
+        server.register(divide);
+
+        let target = Target::new_loopback(&server);
+        let response = target
+            .call(Request {
+                identifier: "api/math/add".to_string(),
+                parameters: vec![json!(1), json!(2)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!(3)));
+
+        let response = target
+            .call(Request {
+                identifier: "api/math/divide".to_string(),
+                parameters: vec![json!(1), json!(0)].into(),
+            })
+            .await;
+        assert_eq!(
+            response.body,
+            Err(SendableError::Application {
+                error: json!({ "message": "division by zero" })
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn loopback() {
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+        server.register_stream_handler(
+            |amount: u32| async move { futures::stream::iter(0..amount) },
+            "count",
+        );
+        server.register_middleware(
+            |mut call: MiddlewareCall| async move {
+                call.parameters = call
+                    .parameters
+                    .into_arguments()
+                    .iter()
+                    .map(|v| json!(v.as_i64().unwrap_or(0) * 2))
+                    .collect();
+                Ok(call)
+            },
+            "double",
+        );
+        server.set_endpoint_middleware("add", vec!["double".to_string()]);
+        server.set_endpoint_schema(
+            "add",
+            EndpointSchema {
+                parameters: vec![
+                    FieldSchema {
+                        name: "a".to_string(),
+                        optional: false,
+                        schema: Schema::Integer {
+                            min: -128,
+                            max: 127,
+                        },
+                    },
+                    FieldSchema {
+                        name: "b".to_string(),
+                        optional: false,
+                        schema: Schema::Integer {
+                            min: -128,
+                            max: 127,
+                        },
+                    },
+                ],
+                types: HashMap::new(),
+                named: false,
+            },
+        );
+
+        // the server is not running, calls don't leave the process
+        let target = Target::new_loopback(&server);
+        let response = target
+            .call(Request {
+                identifier: "add".to_string(),
+                parameters: vec![json!(1), json!(2)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!(6)));
+
+        let response = target
+            .call(Request {
+                identifier: "add".to_string(),
+                parameters: vec![json!(1), json!(300)].into(),
+            })
+            .await;
+        assert!(matches!(
+            response.body,
+            Err(SendableError::BadRequest { .. })
+        ));
+
+        let response = target
+            .call(Request {
+                identifier: "missing".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::NotFound));
+
+        server.set_authenticator(|_| async { Err(SendableError::Unauthorized) });
+        let response = target
+            .call(Request {
+                identifier: "add".to_string(),
+                parameters: vec![json!(1), json!(2)].into(),
+            })
+            .await;
+        assert_eq!(response.body, Err(SendableError::Unauthorized));
+        server.set_authenticator(|_| async { Ok(serde_json::Value::Null) });
+
+        // more items than fit into the window
+        let amount = STREAM_WINDOW * 3;
+        let items = target
+            .call_stream(Request {
+                identifier: "count".to_string(),
+                parameters: vec![json!(amount)].into(),
+            })
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items, (0..amount).map(|i| Ok(json!(i))).collect::<Vec<_>>());
+
+        let target = Target::new(String::new(), TargetType::Loopback);
+        let response = target
+            .call(Request {
+                identifier: "add".to_string(),
+                parameters: Parameters::default(),
+            })
+            .await;
+        assert!(response.body.is_err());
+    }
+
+    #[tokio::test]
+    async fn named_parameters() {
+        #[derive(serde::Deserialize)]
+        struct LoginParameters {
+            user: String,
+            remember: Option<bool>,
+        }
+
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register_handler(
+            |parameters: LoginParameters| async move {
+                format!(
+                    "{}{}",
+                    parameters.user,
+                    parameters.remember.unwrap_or(false)
+                )
+            },
+            "login",
+        );
+        let target = Target::new_loopback(&server);
+        let named = |value: serde_json::Value| match value {
+            serde_json::Value::Object(v) => Parameters::Named(v),
+            _ => unreachable!(),
+        };
+
+        // the order of the keys does not matter
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: named(json!({ "remember": true, "user": "a" })),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!("atrue")));
+
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: named(json!({ "user": "a" })),
+            })
+            .await;
+        assert_eq!(response.body, Ok(json!("afalse")));
+
+        let response = target
+            .call(Request {
+                identifier: "login".to_string(),
+                parameters: named(json!({ "user": 1 })),
+            })
+            .await;
+        match response.body {
+            Err(SendableError::BadRequest { path, .. }) => {
+                assert_eq!(path, Some("user".to_string()))
+            }
+            v => panic!("Expected a bad request, got {:?}", v),
+        }
+    }
+
+    #[tokio::test]
+    async fn schema_mismatch() {
+        let mut server = Server::new(ServerOptions::default(), false);
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+        server.set_schema_hash("abc");
+
+        let mismatches = std::sync::Arc::new(parking_lot::Mutex::new(vec![]));
+        server.on_schema_mismatch({
+            let mismatches = mismatches.clone();
+            move |mismatch| mismatches.lock().push(mismatch.received.clone())
+        });
+
+        let call = |server: Server, schema_hash: Option<&str>| {
+            let context = RequestContext {
+                headers: schema_hash
+                    .map(|v| ("erpc-schema-hash".to_string(), v.to_string()))
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            };
+            async move {
+                server
+                    .dispatch("add".to_string(), None, b"[1, 2]", context)
+                    .await
+            }
+        };
+
+        // clients without a hash and with the same hash are fine
+        assert_eq!(call(server.clone(), None).await.status, 200);
+        assert_eq!(call(server.clone(), Some("abc")).await.status, 200);
+        assert!(mismatches.lock().is_empty());
+
+        // drifted clients are only reported
+        assert_eq!(call(server.clone(), Some("def")).await.status, 200);
+        assert_eq!(*mismatches.lock(), vec!["def".to_string()]);
+
+        let mut strict = Server::new(
+            ServerOptions {
+                strict_schema: true,
+                ..Default::default()
+            },
+            false,
+        );
+        strict.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+        strict.set_schema_hash("abc");
+        assert_eq!(call(strict.clone(), Some("abc")).await.status, 200);
+        assert_eq!(call(strict.clone(), Some("def")).await.status, 400);
+    }
+
+    #[tokio::test]
+    async fn framework_adapter() {
+        let mut server = Server::new(
+            ServerOptions {
+                route_prefix: Some("/rpc/".to_string()),
+                ..Default::default()
+            },
+            false,
+        );
+        server.register_handler(|a: i32, b: i32| async move { a + b }, "add");
+
+        assert_eq!(
+            server.identifier_from_path("/rpc/handlers/math/add?x=1"),
+            Some("math/add".to_string())
+        );
+        assert_eq!(server.identifier_from_path("/handlers/add"), None);
+        assert_eq!(server.identifier_from_path("/rpc/handlers/"), None);
+
+        let response = server
+            .dispatch(
+                "add".to_string(),
+                Some("application/msgpack"),
+                &Codec::MessagePack
+                    .encode(&vec![json!(1), json!(2)])
+                    .unwrap(),
+                RequestContext::default(),
+            )
+            .await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, Codec::MessagePack.content_type());
+        assert_eq!(Codec::MessagePack.decode::<i32>(&response.body).unwrap(), 3);
+
+        let response = server
+            .dispatch(
+                "missing".to_string(),
+                None,
+                b"[]",
+                RequestContext::default(),
+            )
+            .await;
+        assert_eq!(response.status, 404);
+
+        let mut service = server.service();
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/rpc/handlers/add")
+            .body(http_body::Full::new(bytes::Bytes::from("[1, 2]")))
+            .unwrap();
+        let response = tower_service::Service::call(&mut service, request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let body = http_body::Body::data(&mut response.into_body())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(serde_json::from_slice::<i32>(&body).unwrap(), 3);
+
+        let request = http::Request::builder()
+            .method(http::Method::GET)
+            .uri("/rpc/handlers/add")
+            .body(http_body::Full::new(bytes::Bytes::new()))
+            .unwrap();
+        let response = tower_service::Service::call(&mut service, request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn body_limit() {
+        let mut server = Server::new(
+            ServerOptions {
+                max_body_size: 16,
+                ..Default::default()
+            },
+            false,
+        );
+        server.register_handler(|values: Vec<i32>| async move { values.len() }, "count");
+
+        let body = |amount: usize| format!("[[{}]]", vec!["1"; amount].join(","));
+        let call = |body: String| {
+            let mut service = server.service();
+            let request = http::Request::builder()
+                .method(http::Method::POST)
+                .uri("/handlers/count")
+                .body(http_body::Full::new(bytes::Bytes::from(body)))
+                .unwrap();
+            async move {
+                tower_service::Service::call(&mut service, request)
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        assert_eq!(call(body(2)).await, http::StatusCode::OK);
+        assert_eq!(call(body(20)).await, http::StatusCode::BAD_REQUEST);
+
+        let response = server
+            .dispatch(
+                "count".to_string(),
+                None,
+                body(20).as_bytes(),
+                RequestContext::default(),
+            )
+            .await;
+        assert_eq!(response.status, 400);
+    }
 }
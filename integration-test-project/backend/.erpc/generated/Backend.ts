@@ -1,7 +1,16 @@
-import { ERPCServer, ServerOptions } from "@easy-rpc/node"
+import { ERPCError, ERPCServer, ServerOptions } from "@easy-rpc/node"
 import Frontend from "./Frontend"
 import api from "./Backend/api"
 
+/**
+    The endpoints of a browser role called on many sockets at once, calls resolve to the result of each socket by its id
+*/
+type Broadcast<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => Promise<infer R>
+        ? (...args: A) => Promise<Record<string, { ok: true, value: R } | { ok: false, error: ERPCError }>>
+        : T[K] extends object ? Broadcast<T[K]> : never
+}
+
 export default class Backend extends ERPCServer {
     private _api = undefined as any
     set api(value: api) {
@@ -18,7 +27,7 @@ export default class Backend extends ERPCServer {
     constructor(options: ServerOptions, callbacks?: {
         api: api
     }) {
-        super(options, "http-server", true, "Backend")
+        super(options, "http-server", true, "Backend", "be3473ee07862e5c")
         if (callbacks?.api) {
             this.api = callbacks.api
         } else {
@@ -39,4 +48,21 @@ export default class Backend extends ERPCServer {
             }
         })
     }
+
+    /**
+        Calls the connected Frontend browsers at once, e.g. Frontend.all or Frontend.only(socketIds)
+    */
+    get Frontend() {
+        const broadcast = (socketIds?: string[]) => {
+            const ret = new Frontend({address: ""})
+            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+            // @ts-ignore
+            ret.setERPCBroadcast(this, "Frontend", socketIds)
+            return ret as unknown as Broadcast<Frontend>
+        }
+        return {
+            all: broadcast(),
+            only: (socketIds: string[]) => broadcast(socketIds),
+        }
+    }
 }
\ No newline at end of file
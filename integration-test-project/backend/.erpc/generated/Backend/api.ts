@@ -25,7 +25,7 @@ export default class api {
     private _ping: (msg: string) => Promise<string> = undefined as any
     set ping(value: (msg: string) => Promise<string>) {
         this._ping = value
-        this.server?.registerERPCHandler(value, "api/ping")
+        this.server?.registerERPCHandler(value, "api/ping", false, [], {"parameters":[{"name":"msg","optional":false,"schema":{"kind":"string"}}],"types":{}})
     }
     get ping() {
         return this._ping
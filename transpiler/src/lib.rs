@@ -0,0 +1,7 @@
+/*!
+   The transpiler as library, e.g. for the handler macro which checks rust handlers against the .erpc sources
+*/
+
+pub mod error;
+pub mod transpiler;
+pub mod util;
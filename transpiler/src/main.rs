@@ -1,7 +1,5 @@
-mod error;
 mod language_server;
 mod tests;
-mod transpiler;
 mod util;
 use std::{
     env::{self, current_dir},
@@ -11,25 +9,33 @@ use std::{
     time::Duration,
 };
 
+use erpc_transpiler::{error, transpiler};
 use error::DisplayableError;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::runtime::Handle;
-use transpiler::run;
+use transpiler::{compatibility::compare_sources, run};
 use util::normalize_path::normalize_path;
 
 #[tokio::main]
 async fn main() {
     let args: Vec<String> = env::args().collect();
-    run_main(args).await;
+    std::process::exit(run_main(args).await);
 }
 
-async fn run_main(args: Vec<String>) {
+/**
+   Returns the exit code of the process
+*/
+async fn run_main(args: Vec<String>) -> i32 {
+    if args.get(1).map(|v| v.as_str()) == Some("diff") {
+        return run_diff(&args[2..]);
+    }
+
     let entry_path = match args.iter().position(|e| *e == "-p") {
         Some(index) => match args.get(index + 1) {
             Some(v) => normalize_path(&PathBuf::from(v)),
             None => {
                 eprintln!("Could not find path argument after -p flag");
-                return;
+                return 1;
             }
         },
         None => current_dir().unwrap(),
@@ -53,6 +59,59 @@ async fn run_main(args: Vec<String>) {
     } else {
         println!("{}", run_once(entry_path).await);
     }
+    0
+}
+
+/**
+   Compares an old version of a sources directory with the current one and prints the changes of the endpoints.
+   Exits with 1 if a change breaks clients generated from the old version, e.g. to fail a CI pipeline.
+*/
+fn run_diff(args: &[String]) -> i32 {
+    let (old, new) = match args {
+        [old, new, ..] => (
+            normalize_path(&PathBuf::from(old)),
+            normalize_path(&PathBuf::from(new)),
+        ),
+        _ => {
+            eprintln!("Expected the old and the new sources directory after diff, e.g. diff ./old-sources ./sources");
+            return 2;
+        }
+    };
+
+    let changes = match compare_sources(&old, &new) {
+        Ok(v) => v,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err);
+            }
+            return 2;
+        }
+    };
+
+    if changes.is_empty() {
+        println!("No changes");
+        return 0;
+    }
+
+    for (breaking, title) in [(true, "Breaking changes:"), (false, "Compatible changes:")] {
+        let changes = changes
+            .iter()
+            .filter(|change| change.breaking == breaking)
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            continue;
+        }
+        println!("{title}");
+        for change in changes {
+            println!("    {change}");
+        }
+    }
+
+    if changes.iter().any(|change| change.breaking) {
+        1
+    } else {
+        0
+    }
 }
 
 async fn run_watch(
@@ -107,6 +166,7 @@ async fn run_watch(
         for source in config.sources {
             let root_dir = root_dir.clone();
             let role = config.role.clone();
+            let language = config.language.clone();
 
             let error_reporter = error_reporter.clone();
             handles.push(tokio::spawn(async move {
@@ -174,6 +234,7 @@ async fn run_watch(
                                 &normalized_source_path,
                                 &root_dir.join(".erpc").join("generated"),
                                 &role,
+                                &language,
                             )
                             .await;
                             if res.len() > 0 {
@@ -236,11 +297,13 @@ async fn run_once(entry_path: PathBuf) -> String {
         for source in config.sources {
             let root_dir = root_dir.clone();
             let role = config.role.clone();
+            let language = config.language.clone();
             handles.push(tokio::task::spawn(async move {
                 let res = run(
                     &normalize_path(&root_dir.join(source)),
                     &root_dir.join(".erpc").join("generated"),
                     &role,
+                    &language,
                 )
                 .await;
                 if res.len() > 0 {
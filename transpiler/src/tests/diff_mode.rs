@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+
+    use std::path::Path;
+
+    use crate::run_main;
+
+    #[tokio::test]
+    async fn test_exit_codes() {
+        let test_files = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("transpiler")
+            .join("compatibility")
+            .join("tests")
+            .join("compatibility_test_files");
+        let old = test_files.join("old").to_str().unwrap().to_string();
+        let new = test_files.join("new").to_str().unwrap().to_string();
+        let diff = |args: Vec<&String>| {
+            let mut ret = vec!["transpiler".to_string(), "diff".to_string()];
+            ret.extend(args.into_iter().cloned());
+            ret
+        };
+
+        assert_eq!(run_main(diff(vec![&old, &new])).await, 1);
+        assert_eq!(run_main(diff(vec![&old, &old])).await, 0);
+        assert_eq!(run_main(diff(vec![&old])).await, 2);
+        assert_eq!(
+            run_main(diff(vec![
+                &old,
+                &test_files.join("missing").to_str().unwrap().to_string()
+            ]))
+            .await,
+            2
+        );
+    }
+}
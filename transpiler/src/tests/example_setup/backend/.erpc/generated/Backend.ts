@@ -0,0 +1,68 @@
+import { ERPCError, ERPCServer, ServerOptions } from "@easy-rpc/node"
+import Frontend from "./Frontend"
+import api from "./Backend/api"
+
+/**
+    The endpoints of a browser role called on many sockets at once, calls resolve to the result of each socket by its id
+*/
+type Broadcast<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => Promise<infer R>
+        ? (...args: A) => Promise<Record<string, { ok: true, value: R } | { ok: false, error: ERPCError }>>
+        : T[K] extends object ? Broadcast<T[K]> : never
+}
+
+export default class Backend extends ERPCServer {
+    private _api = undefined as any
+    set api(value: api) {
+        this._api = value;
+        (value as any).setERPCServer(this)
+    }
+    get api() {
+        return this._api
+    }
+    /**
+        @param options The options to set for the easy-rpc object
+        @param callbacks Callbacks to register for this server
+    */
+    constructor(options: ServerOptions, callbacks?: {
+        api: api
+    }) {
+        super(options, "http-server", true, "Backend", "3b131c344a87998c")
+        if (callbacks?.api) {
+            this.api = callbacks.api
+        } else {
+            this.api = new api()
+        }
+    }
+
+    onConnection(callback: (target: Frontend) => void) {
+        // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+        // @ts-ignore
+        super.onSocketConnection((role, socket) => {
+            if (role === "Frontend") {
+                const ret = new Frontend({address: ""})
+                // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+                // @ts-ignore
+                ret.setERPCSocket(socket)
+                callback(ret)
+            }
+        })
+    }
+
+    /**
+        Calls the connected Frontend browsers at once, e.g. Frontend.all or Frontend.only(socketIds)
+    */
+    get Frontend() {
+        const broadcast = (socketIds?: string[]) => {
+            const ret = new Frontend({address: ""})
+            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+            // @ts-ignore
+            ret.setERPCBroadcast(this, "Frontend", socketIds)
+            return ret as unknown as Broadcast<Frontend>
+        }
+        return {
+            all: broadcast(),
+            only: (socketIds: string[]) => broadcast(socketIds),
+        }
+    }
+}
\ No newline at end of file
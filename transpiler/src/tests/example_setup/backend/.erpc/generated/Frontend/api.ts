@@ -7,8 +7,8 @@ export default class api {
         this.server = server
     }
 
-    test2(): Promise<void> {
-        return this.server.call("api/test2")
+    login2(newUser: string): Promise<"success"> {
+        return this.server.call("api/login2", [newUser])
     }
 
 }
\ No newline at end of file
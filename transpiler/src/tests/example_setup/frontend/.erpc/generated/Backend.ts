@@ -0,0 +1,13 @@
+import { ERPCTarget, TargetOptions } from "@easy-rpc/browser"
+import Frontend from "./Frontend"
+import api from "./Backend/api"
+
+export default class Backend extends ERPCTarget {
+    api = new api(this)
+    /**
+        @param options The options to set for the easy-rpc object
+    */
+    constructor(options: TargetOptions) {
+        super(options, "http-server", "3b131c344a87998c")
+    }
+}
\ No newline at end of file
@@ -1,2 +1,3 @@
+mod diff_mode;
 mod normal_mode;
 mod watch_mode;
\ No newline at end of file
@@ -0,0 +1,681 @@
+mod tests;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    fs::{read_dir, File},
+    path::Path,
+};
+
+use crate::error::{Diagnostic, DisplayableError};
+
+use super::{
+    generator::translator::is_middleware_class,
+    parser::{
+        input_reader::InputReader,
+        lexer::{literal::LiteralType, TokenReader},
+        parser::{
+            custom_type::CustomType,
+            endpoint::{Endpoint, Parameter},
+            erpc_type::{ArrayAmount, Custom, EnumType, Primitive, PrimitiveType, Type},
+            parse, ParseResult,
+        },
+    },
+};
+
+/**
+   A difference between two versions of the sources which affects the endpoint with the identifier, e.g. api/users/find
+*/
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub identifier: String,
+    pub message: String,
+    /**
+       Clients generated from the old sources fail to call the endpoint of a server generated from the new ones
+    */
+    pub breaking: bool,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.identifier, self.message)
+    }
+}
+
+/**
+   Parses both versions of a sources directory and lists the changes of their endpoints, sorted by identifier.
+   A change is breaking when an already deployed client of the old sources can't call the new server anymore.
+*/
+pub fn compare_sources(
+    old_directory: &Path,
+    new_directory: &Path,
+) -> Result<Vec<Change>, Vec<DisplayableError>> {
+    let old = read_sources(old_directory)?;
+    let new = read_sources(new_directory)?;
+    Ok(compare_files(&old, &new))
+}
+
+/**
+   Parses all .erpc files of the directory by their path relative to it without the extension, e.g. api/users
+*/
+fn read_sources(directory: &Path) -> Result<BTreeMap<String, ParseResult>, Vec<DisplayableError>> {
+    fn read_recursively(
+        directory: &Path,
+        relative_path: &str,
+        files: &mut BTreeMap<String, ParseResult>,
+        errors: &mut Vec<DisplayableError>,
+    ) {
+        let entries = match read_dir(directory.join(relative_path)) {
+            Ok(v) => v,
+            Err(err) => {
+                errors.push(
+                    format!(
+                        "Could not read dir '{}': {err}",
+                        directory
+                            .join(relative_path)
+                            .to_str()
+                            .unwrap_or("<could not unwrap path>")
+                    )
+                    .into(),
+                );
+                return;
+            }
+        };
+
+        for entry in entries {
+            let path = match entry {
+                Ok(v) => v.path(),
+                Err(err) => {
+                    errors.push(format!("Could not read dir entry: {err}").into());
+                    continue;
+                }
+            };
+            let file_name = match path.file_name().and_then(|v| v.to_str()) {
+                Some(v) => v,
+                None => {
+                    errors.push(
+                        format!(
+                            "File name is not valid UTF-8 for {}",
+                            path.to_str().unwrap_or("<could not unwrap path>")
+                        )
+                        .into(),
+                    );
+                    continue;
+                }
+            };
+
+            if path.is_dir() {
+                read_recursively(
+                    directory,
+                    &format!("{relative_path}{file_name}/"),
+                    files,
+                    errors,
+                );
+                continue;
+            }
+
+            let class_name = match file_name.strip_suffix(".erpc") {
+                Some(v) => v,
+                None => continue,
+            };
+            // middleware is not called by clients
+            if is_middleware_class(class_name, relative_path) {
+                continue;
+            }
+
+            let mut reader = match TokenReader::new(InputReader::new(match File::open(&path) {
+                Ok(v) => v,
+                Err(err) => {
+                    errors.push(
+                        format!(
+                            "Could not open file {}: {err}",
+                            path.to_str().unwrap_or("<could not unwrap path>")
+                        )
+                        .into(),
+                    );
+                    continue;
+                }
+            })) {
+                Ok(v) => v,
+                Err(err) => {
+                    errors.push(
+                        format!(
+                            "Input reader error occurred at {}: {err}",
+                            path.to_str().unwrap_or("<could not unwrap path>")
+                        )
+                        .into(),
+                    );
+                    continue;
+                }
+            };
+
+            match parse(&mut reader) {
+                Ok(v) => {
+                    files.insert(format!("{relative_path}{class_name}"), v);
+                }
+                Err(err) => errors.push(DisplayableError::Diagnostic(Diagnostic {
+                    source: path.clone(),
+                    range: err.range,
+                    message: err.message,
+                })),
+            };
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    let mut errors = vec![];
+    read_recursively(directory, "", &mut files, &mut errors);
+
+    if errors.is_empty() {
+        Ok(files)
+    } else {
+        Err(errors)
+    }
+}
+
+fn compare_files(
+    old: &BTreeMap<String, ParseResult>,
+    new: &BTreeMap<String, ParseResult>,
+) -> Vec<Change> {
+    let mut endpoints = BTreeMap::<String, (Option<_>, Option<_>)>::new();
+    for (class_path, file) in old {
+        for endpoint in &file.endpoints {
+            endpoints
+                .entry(format!("{class_path}/{}", endpoint.identifier))
+                .or_default()
+                .0 = Some((endpoint, &file.custom_types));
+        }
+    }
+    for (class_path, file) in new {
+        for endpoint in &file.endpoints {
+            endpoints
+                .entry(format!("{class_path}/{}", endpoint.identifier))
+                .or_default()
+                .1 = Some((endpoint, &file.custom_types));
+        }
+    }
+
+    let mut changes = vec![];
+    for (identifier, endpoint) in endpoints {
+        let (breaking, message) = match endpoint {
+            (Some((old, old_types)), Some((new, new_types))) => {
+                for (breaking, message) in compare_endpoints(old, old_types, new, new_types) {
+                    changes.push(Change {
+                        identifier: identifier.clone(),
+                        message,
+                        breaking,
+                    });
+                }
+                continue;
+            }
+            (Some(_), None) => (true, "the endpoint was removed".to_string()),
+            (None, Some(_)) => (false, "the endpoint was added".to_string()),
+            (None, None) => continue,
+        };
+        changes.push(Change {
+            identifier,
+            message,
+            breaking,
+        });
+    }
+    changes
+}
+
+/**
+   Whether the compared values are sent to the endpoint or returned by it.
+   Sent values come from old clients and have to be accepted by the new endpoint,
+   returned values come from the new endpoint and have to be accepted by old clients.
+*/
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Sent,
+    Returned,
+}
+
+/**
+   Compares the types of one endpoint, custom types are looked up in the source file of the respective version
+*/
+struct Comparison<'a> {
+    old_types: &'a Vec<CustomType>,
+    new_types: &'a Vec<CustomType>,
+    direction: Direction,
+    /**
+       The pairs of old and new custom types which are compared already, custom types may reference themselves
+    */
+    visited: HashSet<(String, String)>,
+    changes: Vec<(bool, String)>,
+}
+
+fn compare_endpoints(
+    old: &Endpoint,
+    old_types: &Vec<CustomType>,
+    new: &Endpoint,
+    new_types: &Vec<CustomType>,
+) -> Vec<(bool, String)> {
+    let mut comparison = Comparison {
+        old_types,
+        new_types,
+        direction: Direction::Sent,
+        visited: HashSet::new(),
+        changes: vec![],
+    };
+
+    if old.role != new.role {
+        comparison.breaking(format!(
+            "the endpoint moved from {} to {}",
+            old.role, new.role
+        ));
+    }
+    if old.named_parameters != new.named_parameters {
+        comparison.breaking(if new.named_parameters {
+            "the endpoint takes named parameters now".to_string()
+        } else {
+            "the endpoint takes a list of parameters now".to_string()
+        });
+    } else {
+        comparison.compare_parameters(&old.parameters, &new.parameters, new.named_parameters);
+    }
+
+    comparison.direction = Direction::Returned;
+    comparison.visited.clear();
+    if old.streaming != new.streaming {
+        comparison.breaking(if new.streaming {
+            "the endpoint returns a stream now".to_string()
+        } else {
+            "the endpoint does not return a stream anymore".to_string()
+        });
+    }
+    match (&old.return_type, &new.return_type) {
+        (Some(old), Some(new)) => comparison.compare_types(old, new, "return type"),
+        (None, Some(new)) => {
+            comparison.breaking(format!("the endpoint returns {} now", describe_type(new)))
+        }
+        (Some(_), None) => {
+            comparison.breaking("the endpoint does not return a value anymore".to_string())
+        }
+        (None, None) => {}
+    }
+    match (&old.error_type, &new.error_type) {
+        (Some(old), Some(new)) => comparison.compare_types(old, new, "error type"),
+        (None, Some(new)) => {
+            comparison.breaking(format!("the endpoint throws {} now", describe_type(new)))
+        }
+        (Some(_), None) => comparison.compatible("the endpoint does not throw anymore".to_string()),
+        (None, None) => {}
+    }
+
+    comparison.changes
+}
+
+impl<'a> Comparison<'a> {
+    fn breaking(&mut self, message: String) {
+        self.changes.push((true, message));
+    }
+
+    fn compatible(&mut self, message: String) {
+        self.changes.push((false, message));
+    }
+
+    /**
+       Positional parameters are matched by their position, so renaming them does not break callers.
+       The server rejects calls with unknown parameters, so removing one breaks old clients.
+    */
+    fn compare_parameters(&mut self, old: &[Parameter], new: &[Parameter], named: bool) {
+        for (i, new_parameter) in new.iter().enumerate() {
+            let old_parameter = if named {
+                old.iter()
+                    .find(|v| v.identifier == new_parameter.identifier)
+            } else {
+                old.get(i)
+            };
+            let path = format!("parameter {}", new_parameter.identifier);
+
+            let old_parameter = match old_parameter {
+                Some(v) => v,
+                None => {
+                    if new_parameter.optional {
+                        self.compatible(format!("{path} was added"));
+                    } else {
+                        self.breaking(format!("{path} was added and is required"));
+                    }
+                    continue;
+                }
+            };
+
+            if old_parameter.identifier != new_parameter.identifier {
+                self.compatible(format!(
+                    "parameter {} was renamed to {}",
+                    old_parameter.identifier, new_parameter.identifier
+                ));
+            }
+            self.compare_optional(old_parameter.optional, new_parameter.optional, &path);
+            self.compare_types(
+                &old_parameter.parameter_type,
+                &new_parameter.parameter_type,
+                &path,
+            );
+        }
+
+        for (i, old_parameter) in old.iter().enumerate() {
+            let removed = if named {
+                !new.iter().any(|v| v.identifier == old_parameter.identifier)
+            } else {
+                i >= new.len()
+            };
+            if removed {
+                self.breaking(format!(
+                    "parameter {} was removed",
+                    old_parameter.identifier
+                ));
+            }
+        }
+    }
+
+    fn compare_optional(&mut self, old: bool, new: bool, path: &str) {
+        match (old, new, self.direction) {
+            (true, false, Direction::Sent) => self.breaking(format!("{path} is required now")),
+            (true, false, Direction::Returned) => {
+                self.compatible(format!("{path} is always set now"))
+            }
+            (false, true, Direction::Sent) => self.compatible(format!("{path} is optional now")),
+            (false, true, Direction::Returned) => {
+                self.breaking(format!("{path} may be missing now"))
+            }
+            _ => {}
+        }
+    }
+
+    fn compare_types(&mut self, old: &Type, new: &Type, path: &str) {
+        let old_variants = variants(old);
+        let new_variants = variants(new);
+
+        // a plain type is compared in detail, so changes of custom types point to the changed field
+        if old_variants.len() == 1 && new_variants.len() == 1 {
+            self.compare_variants(&old_variants[0], &new_variants[0], path);
+            return;
+        }
+
+        let (sent, received) = match self.direction {
+            Direction::Sent => (&old_variants, &new_variants),
+            Direction::Returned => (&new_variants, &old_variants),
+        };
+        let mut unaccepted = vec![];
+        for sent_variant in sent {
+            if !received
+                .iter()
+                .any(|received_variant| self.accepts(sent_variant, received_variant))
+            {
+                unaccepted.push(describe_variant(sent_variant));
+            }
+        }
+
+        if !unaccepted.is_empty() {
+            self.breaking(match self.direction {
+                Direction::Sent => {
+                    format!("{path} does not accept {} anymore", unaccepted.join(" | "))
+                }
+                Direction::Returned => format!("{path} can be {} now", unaccepted.join(" | ")),
+            });
+        } else if describe_type(old) != describe_type(new) {
+            self.compatible(format!(
+                "{path} changed from {} to {}",
+                describe_type(old),
+                describe_type(new)
+            ));
+        }
+    }
+
+    /**
+       Whether every value of the sent variant is accepted by the received one, without reporting anything
+    */
+    fn accepts(&self, sent: &Variant, received: &Variant) -> bool {
+        let mut comparison = Comparison {
+            old_types: self.old_types,
+            new_types: self.new_types,
+            direction: self.direction,
+            visited: self.visited.clone(),
+            changes: vec![],
+        };
+        match self.direction {
+            Direction::Sent => comparison.compare_variants(sent, received, ""),
+            Direction::Returned => comparison.compare_variants(received, sent, ""),
+        }
+        !comparison.changes.iter().any(|(breaking, _)| *breaking)
+    }
+
+    fn compare_variants(&mut self, old: &Variant, new: &Variant, path: &str) {
+        let (sent, received) = match self.direction {
+            Direction::Sent => (old, new),
+            Direction::Returned => (new, old),
+        };
+
+        let compatible = match (sent, received) {
+            (Variant::Custom(sent_custom), Variant::Custom(received_custom)) => {
+                if !accepts_array(&sent_custom.array_amount, &received_custom.array_amount) {
+                    false
+                } else {
+                    let (old_custom, new_custom) = match self.direction {
+                        Direction::Sent => (sent_custom, received_custom),
+                        Direction::Returned => (received_custom, sent_custom),
+                    };
+                    self.compare_custom_types(&old_custom.identifier, &new_custom.identifier, path);
+                    true
+                }
+            }
+            (Variant::Primitive(sent), Variant::Primitive(received)) => {
+                accepts_array(&sent.array_amount, &received.array_amount)
+                    && accepts_primitive(&sent.primitive_type, &received.primitive_type)
+            }
+            (Variant::Literal(sent), Variant::Primitive(received)) => {
+                matches!(received.array_amount, ArrayAmount::NoArray)
+                    && accepts_literal(sent, &received.primitive_type)
+            }
+            (Variant::Literal(_), Variant::Literal(_)) => {
+                describe_variant(sent) == describe_variant(received)
+            }
+            _ => false,
+        };
+
+        let (old_description, new_description) = (describe_variant(old), describe_variant(new));
+        if !compatible {
+            self.breaking(format!(
+                "{path} changed from {old_description} to {new_description}"
+            ));
+        } else if old_description != new_description {
+            self.compatible(format!(
+                "{path} changed from {old_description} to {new_description}"
+            ));
+        }
+    }
+
+    /**
+       Unknown fields are ignored when values are parsed, so only fields the receiver requires have to be sent
+    */
+    fn compare_custom_types(&mut self, old_identifier: &str, new_identifier: &str, path: &str) {
+        if !self
+            .visited
+            .insert((old_identifier.to_string(), new_identifier.to_string()))
+        {
+            return;
+        }
+
+        let (old_fields, new_fields) = match (
+            self.old_types
+                .iter()
+                .find(|v| v.identifier == old_identifier),
+            self.new_types
+                .iter()
+                .find(|v| v.identifier == new_identifier),
+        ) {
+            (Some(old), Some(new)) => (&old.fields, &new.fields),
+            // the sources are validated before they are used, unknown types can only be compared by name
+            _ => {
+                if old_identifier != new_identifier {
+                    self.breaking(format!(
+                        "{path} changed from {old_identifier} to {new_identifier}"
+                    ));
+                }
+                return;
+            }
+        };
+
+        for new_field in new_fields {
+            let field_path = format!("{path}.{}", new_field.identifier);
+            match old_fields
+                .iter()
+                .find(|v| v.identifier == new_field.identifier)
+            {
+                Some(old_field) => {
+                    self.compare_optional(old_field.optional, new_field.optional, &field_path);
+                    self.compare_types(&old_field.field_type, &new_field.field_type, &field_path);
+                }
+                None => {
+                    if self.direction == Direction::Sent && !new_field.optional {
+                        self.breaking(format!("{field_path} was added and is required"));
+                    } else {
+                        self.compatible(format!("{field_path} was added"));
+                    }
+                }
+            }
+        }
+
+        for old_field in old_fields {
+            if new_fields
+                .iter()
+                .any(|v| v.identifier == old_field.identifier)
+            {
+                continue;
+            }
+            let field_path = format!("{path}.{}", old_field.identifier);
+            if self.direction == Direction::Returned && !old_field.optional {
+                self.breaking(format!("{field_path} was removed"));
+            } else {
+                self.compatible(format!("{field_path} was removed"));
+            }
+        }
+    }
+}
+
+/**
+   A type which is not an enum, or a single value of an enum
+*/
+enum Variant<'a> {
+    Primitive(&'a Primitive),
+    Custom(&'a Custom),
+    Literal(&'a LiteralType),
+}
+
+fn variants(erpc_type: &Type) -> Vec<Variant<'_>> {
+    match erpc_type {
+        Type::Primitive(v) => vec![Variant::Primitive(v)],
+        Type::Custom(v) => vec![Variant::Custom(v)],
+        Type::Enum(v) => v
+            .values
+            .iter()
+            .map(|value| match value {
+                EnumType::Primitive(v) => Variant::Primitive(v),
+                EnumType::Custom(v) => Variant::Custom(v),
+                EnumType::Literal(v) => Variant::Literal(v),
+            })
+            .collect(),
+    }
+}
+
+fn accepts_array(sent: &ArrayAmount, received: &ArrayAmount) -> bool {
+    match (sent, received) {
+        (ArrayAmount::NoArray, ArrayAmount::NoArray) => true,
+        (
+            ArrayAmount::NoLengthSpecified | ArrayAmount::LengthSpecified(_),
+            ArrayAmount::NoLengthSpecified,
+        ) => true,
+        (ArrayAmount::LengthSpecified(sent), ArrayAmount::LengthSpecified(received)) => {
+            sent == received
+        }
+        _ => false,
+    }
+}
+
+/**
+   Integers fit into wider integers and every number fits into a float
+*/
+fn accepts_primitive(sent: &PrimitiveType, received: &PrimitiveType) -> bool {
+    fn integer_width(primitive_type: &PrimitiveType) -> Option<u8> {
+        match primitive_type {
+            PrimitiveType::Int8 => Some(8),
+            PrimitiveType::Int16 => Some(16),
+            PrimitiveType::Int32 => Some(32),
+            PrimitiveType::Int64 => Some(64),
+            _ => None,
+        }
+    }
+
+    match (sent, received) {
+        (PrimitiveType::Boolean, PrimitiveType::Boolean)
+        | (PrimitiveType::String, PrimitiveType::String)
+        | (PrimitiveType::Float32, PrimitiveType::Float32 | PrimitiveType::Float64)
+        | (PrimitiveType::Float64, PrimitiveType::Float64) => true,
+        (sent, PrimitiveType::Float32 | PrimitiveType::Float64) => integer_width(sent).is_some(),
+        (sent, received) => match (integer_width(sent), integer_width(received)) {
+            (Some(sent), Some(received)) => sent <= received,
+            _ => false,
+        },
+    }
+}
+
+fn accepts_literal(sent: &LiteralType, received: &PrimitiveType) -> bool {
+    match (sent, received) {
+        (LiteralType::Boolean(_), PrimitiveType::Boolean) => true,
+        (LiteralType::String(_), PrimitiveType::String) => true,
+        (LiteralType::Float(_), PrimitiveType::Float32 | PrimitiveType::Float64) => true,
+        (LiteralType::Integer(_), PrimitiveType::Float32 | PrimitiveType::Float64) => true,
+        (LiteralType::Integer(v), PrimitiveType::Int8) => i8::try_from(*v).is_ok(),
+        (LiteralType::Integer(v), PrimitiveType::Int16) => i16::try_from(*v).is_ok(),
+        (LiteralType::Integer(_), PrimitiveType::Int32 | PrimitiveType::Int64) => true,
+        _ => false,
+    }
+}
+
+/**
+   Describes the type the way it is written in the sources, e.g. int32[] or "a" | "b"
+*/
+fn describe_type(erpc_type: &Type) -> String {
+    variants(erpc_type)
+        .iter()
+        .map(describe_variant)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn describe_variant(variant: &Variant) -> String {
+    let (name, array_amount) = match variant {
+        Variant::Primitive(v) => (
+            match v.primitive_type {
+                PrimitiveType::Boolean => "boolean",
+                PrimitiveType::Int8 => "int8",
+                PrimitiveType::Int16 => "int16",
+                PrimitiveType::Int32 => "int32",
+                PrimitiveType::Int64 => "int64",
+                PrimitiveType::Float32 => "float32",
+                PrimitiveType::Float64 => "float64",
+                PrimitiveType::String => "string",
+            }
+            .to_string(),
+            &v.array_amount,
+        ),
+        Variant::Custom(v) => (v.identifier.clone(), &v.array_amount),
+        Variant::Literal(v) => {
+            return match v {
+                LiteralType::Boolean(v) => v.to_string(),
+                LiteralType::String(v) => format!("\"{v}\""),
+                LiteralType::Float(v) => v.to_string(),
+                LiteralType::Integer(v) => v.to_string(),
+            }
+        }
+    };
+
+    match array_amount {
+        ArrayAmount::NoArray => name,
+        ArrayAmount::NoLengthSpecified => format!("{name}[]"),
+        ArrayAmount::LengthSpecified(length) => format!("{name}[{length}]"),
+    }
+}
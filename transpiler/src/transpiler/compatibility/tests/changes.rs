@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path};
+
+    use crate::transpiler::{
+        compatibility::{compare_files, compare_sources, Change},
+        parser::{
+            input_reader::InputReader,
+            lexer::TokenReader,
+            parser::{parse, ParseResult},
+        },
+    };
+
+    fn sources(source: &str) -> BTreeMap<String, ParseResult> {
+        let mut reader = TokenReader::new(InputReader::new(source.as_bytes())).unwrap();
+        BTreeMap::from([("api".to_string(), parse(&mut reader).unwrap())])
+    }
+
+    fn compare(old: &str, new: &str) -> Vec<(bool, String)> {
+        compare_files(&sources(old), &sources(new))
+            .into_iter()
+            .map(|change| (change.breaking, change.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_sources() {
+        let test_files = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("transpiler")
+            .join("compatibility")
+            .join("tests")
+            .join("compatibility_test_files");
+
+        let changes = compare_sources(&test_files.join("old"), &test_files.join("new")).unwrap();
+
+        let change = |identifier: &str, message: &str, breaking: bool| Change {
+            identifier: identifier.to_string(),
+            message: message.to_string(),
+            breaking,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change("api/users/find", "parameter limit is required now", true),
+                change(
+                    "api/users/find",
+                    "parameter limit changed from int32 to int64",
+                    false
+                ),
+                change("api/users/find", "parameter filter was added", false),
+                change("api/users/find", "return type.age is always set now", false),
+                change("api/users/find", "return type.email was added", false),
+                change("api/users/ping", "the endpoint was added", false),
+                change("api/users/remove", "the endpoint was removed", true),
+                change(
+                    "api/users/rename",
+                    "parameter user.age is required now",
+                    true
+                ),
+                change("api/users/rename", "parameter user.email was added", false),
+                change(
+                    "api/users/rename",
+                    "parameter name was renamed to newName",
+                    false
+                ),
+                change(
+                    "api/users/setMode",
+                    "parameter mode does not accept \"run\" anymore",
+                    true
+                ),
+                change("api/users/status", "return type can be \"away\" now", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_types_and_unions() {
+        let old = "type Node {\n    name string\n    next? Node[]\n}\nServer walk(node Node, steps int8 | string) int8 | string\n";
+        let new = "type Node {\n    name string\n    next? Node[]\n    weight? float32\n}\nServer walk(node Node, steps int16 | string | boolean) int8\n";
+
+        assert_eq!(
+            compare(old, new),
+            vec![
+                (false, "api/walk: parameter node.weight was added".to_string()),
+                (
+                    false,
+                    "api/walk: parameter steps changed from int8 | string to int16 | string | boolean"
+                        .to_string()
+                ),
+                (
+                    false,
+                    "api/walk: return type changed from int8 | string to int8".to_string()
+                ),
+            ]
+        );
+
+        // the other way around the unions break callers, unknown fields are ignored
+        assert_eq!(
+            compare(new, old),
+            vec![
+                (
+                    false,
+                    "api/walk: parameter node.weight was removed".to_string()
+                ),
+                (
+                    true,
+                    "api/walk: parameter steps does not accept int16 | boolean anymore".to_string()
+                ),
+                (true, "api/walk: return type can be string now".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let named = "Server create({name string, age? int16})\n";
+
+        assert_eq!(
+            compare(named, "Server create({age? int16, name string})\n"),
+            vec![]
+        );
+        assert_eq!(
+            compare(named, "Server create({name string})\n"),
+            vec![(true, "api/create: parameter age was removed".to_string())]
+        );
+        assert_eq!(
+            compare(named, "Server create(name string, age? int16)\n"),
+            vec![(
+                true,
+                "api/create: the endpoint takes a list of parameters now".to_string()
+            )]
+        );
+        assert_eq!(
+            compare(
+                "Server create(names string[2])\n",
+                "Server create(names string[])\n"
+            ),
+            vec![(
+                false,
+                "api/create: parameter names changed from string[2] to string[]".to_string()
+            )]
+        );
+        assert_eq!(
+            compare(
+                "Server create(names string[])\n",
+                "Server create(names string[2])\n"
+            ),
+            vec![(
+                true,
+                "api/create: parameter names changed from string[] to string[2]".to_string()
+            )]
+        );
+    }
+}
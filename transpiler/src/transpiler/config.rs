@@ -11,11 +11,24 @@ pub struct Role {
     pub documentation: Option<String>,
 }
 
+/**
+   The language the code for a project is generated in
+*/
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    TypeScript,
+    Rust,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub sources: Vec<String>,
     pub role: String,
+    #[serde(default)]
+    pub language: Language,
 }
 
 pub fn parse_roles<T: Read>(input: T) -> Result<Vec<Role>, serde_json::Error> {
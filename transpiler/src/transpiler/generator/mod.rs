@@ -7,7 +7,10 @@ use std::{
 
 use crate::error::{Diagnostic, DisplayableError};
 
-use self::translator::Translator;
+use self::translator::{
+    schema::{endpoint_signature, schema_hash},
+    Translator,
+};
 
 use super::{
     config::Role,
@@ -42,6 +45,7 @@ pub fn generate_for_directory<T: Translator>(
     available_roles: &Vec<Role>,
     available_middleware: &Vec<Endpoint>,
 ) -> Vec<DisplayableError> {
+    let mut signatures_per_role = HashMap::new();
     let result = generate_for_directory_recursively::<T>(
         source_directory,
         output_directory,
@@ -49,6 +53,7 @@ pub fn generate_for_directory<T: Translator>(
         &selected_role_name,
         &available_roles,
         available_middleware,
+        &mut signatures_per_role,
     );
 
     let mut errors = result.1;
@@ -67,20 +72,17 @@ pub fn generate_for_directory<T: Translator>(
         })
         .collect();
 
-    let source = if available_roles
-        .iter()
-        .find(|x| x.name == selected_role_name)
-        .unwrap()
-        .role_type
-        .contains(&"browser".to_string())
-    {
-        "@easy-rpc/browser"
-    } else {
-        "@easy-rpc/node" // currently only supports node
-    };
+    let source = T::library_source(
+        available_roles
+            .iter()
+            .find(|x| x.name == selected_role_name)
+            .unwrap(),
+    );
 
     for role in available_roles {
         let imports = classes_per_role.get(&role.name);
+        // the clients of a role and its server are generated from the same sources, so they embed the same hash
+        let hash = schema_hash(signatures_per_role.get(&role.name).unwrap_or(&vec![]));
 
         let generated = match imports {
             Some(imports) => T::generate_client(
@@ -88,14 +90,16 @@ pub fn generate_for_directory<T: Translator>(
                 &imports,
                 &role,
                 socket_enabled_browser_roles,
-                source,
+                &source,
+                &hash,
             ),
             None => T::generate_client(
                 role.name != selected_role_name,
                 &vec![],
                 &role,
                 socket_enabled_browser_roles,
-                source,
+                &source,
+                &hash,
             ),
         };
 
@@ -162,6 +166,7 @@ pub fn generate_for_directory<T: Translator>(
 /**
    Internal recursive function to process a directory of erpc sources. Input/Output directory and selected role stay the same.
    The relative path specifies at which level relative of the root input dir this function should run.
+   The signatures of all endpoints are collected per role to build the schema hash of the role.
    Returns which classes were generated for what role.
 */
 fn generate_for_directory_recursively<T: Translator>(
@@ -171,6 +176,7 @@ fn generate_for_directory_recursively<T: Translator>(
     selected_role: &str,
     all_roles: &Vec<Role>,
     available_middleware: &Vec<Endpoint>,
+    signatures_per_role: &mut HashMap<String, Vec<String>>,
 ) -> (HashMap<String, Vec<String>>, Vec<DisplayableError>) {
     // tracks which classes per role were generated on the current dir level
     let mut generated_classnames_per_role: HashMap<String, Vec<String>> = HashMap::new();
@@ -271,6 +277,7 @@ fn generate_for_directory_recursively<T: Translator>(
                 selected_role,
                 all_roles,
                 available_middleware,
+                signatures_per_role,
             );
 
             let generated_classes_per_role = result.0;
@@ -363,6 +370,7 @@ fn generate_for_directory_recursively<T: Translator>(
                 available_middleware,
             )
             .into_iter()
+            .chain(T::validate(&result.endpoints, &result.custom_types))
             {
                 validation_error_occurred = true;
                 errors.push(DisplayableError::Diagnostic(Diagnostic {
@@ -375,6 +383,21 @@ fn generate_for_directory_recursively<T: Translator>(
                 continue;
             }
 
+            // middleware is not called by clients, so it does not change their schema
+            if !translator::is_middleware_class(file_name, relative_path) {
+                for endpoint in &result.endpoints {
+                    let signature = endpoint_signature(
+                        &format!("{relative_path}{file_name}/{}", endpoint.identifier),
+                        endpoint,
+                        &result.custom_types,
+                    );
+                    signatures_per_role
+                        .entry(endpoint.role.clone())
+                        .or_default()
+                        .push(signature.to_string());
+                }
+            }
+
             // generate class strings per role
             let mut generated_class_content_per_role = generate_classes_per_role::<T>(
                 file_name,
@@ -388,7 +411,7 @@ fn generate_for_directory_recursively<T: Translator>(
             );
 
             // if the middleware source file at root level is processed, we only need to generate callback handlers to register the middleware on
-            if relative_path.is_empty() && file_name == "middleware" {
+            if translator::is_middleware_class(file_name, relative_path) {
                 generated_class_content_per_role.retain(|key, _| key == selected_role);
             }
 
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
 
-    use std::{fs, path::Path};
+    use std::{collections::HashMap, fs, path::Path};
 
     use crate::{
         transpiler::{
@@ -46,6 +46,7 @@ mod tests {
                 },
             ],
             &vec![],
+            &mut HashMap::new(),
         );
 
         assert_eq!(result.1.len(), 0);
@@ -1,8 +1,17 @@
-import { ERPCServer, ServerOptions } from "@easy-rpc/node"
+import { ERPCError, ERPCServer, ServerOptions } from "@easy-rpc/node"
 import Client from "./Client"
 import api from "./Server/api"
 import auth from "./Server/auth"
 
+/**
+    The endpoints of a browser role called on many sockets at once, calls resolve to the result of each socket by its id
+*/
+type Broadcast<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => Promise<infer R>
+        ? (...args: A) => Promise<Record<string, { ok: true, value: R } | { ok: false, error: ERPCError }>>
+        : T[K] extends object ? Broadcast<T[K]> : never
+}
+
 /**This is some docs*/
 export default class Server extends ERPCServer {
     private _api = undefined as any
@@ -29,7 +38,7 @@ export default class Server extends ERPCServer {
         api: api
         auth: auth
     }) {
-        super(options, "http-server", true, "Server")
+        super(options, "http-server", true, "Server", "b11c2d4ea7efa5ae")
         if (callbacks?.api) {
             this.api = callbacks.api
         } else {
@@ -55,4 +64,21 @@ export default class Server extends ERPCServer {
             }
         })
     }
+
+    /**
+        Calls the connected Client browsers at once, e.g. Client.all or Client.only(socketIds)
+    */
+    get Client() {
+        const broadcast = (socketIds?: string[]) => {
+            const ret = new Client({address: ""})
+            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+            // @ts-ignore
+            ret.setERPCBroadcast(this, "Client", socketIds)
+            return ret as unknown as Broadcast<Client>
+        }
+        return {
+            all: broadcast(),
+            only: (socketIds: string[]) => broadcast(socketIds),
+        }
+    }
 }
\ No newline at end of file
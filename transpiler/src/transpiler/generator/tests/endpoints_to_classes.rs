@@ -21,6 +21,9 @@ mod tests {
                     identifier: "MySuperCoolEndpoint".to_string(),
                     role: "Server".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                     middleware_identifiers: vec![],
                 },
@@ -30,6 +33,9 @@ mod tests {
                     identifier: "MySuperCoolEndpoint2".to_string(),
                     role: "Client".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                     middleware_identifiers: vec![],
                 },
@@ -39,6 +45,9 @@ mod tests {
                     identifier: "MySuperCoolEndpoint3".to_string(),
                     role: "Client".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                     middleware_identifiers: vec![],
                 },
@@ -92,7 +101,7 @@ export default class TestClass {
     private _MySuperCoolEndpoint: () => Promise<void> = undefined as any
     set MySuperCoolEndpoint(value: () => Promise<void>) {
         this._MySuperCoolEndpoint = value
-        this.server?.registerERPCHandler(value, \"test/test2/TestClass/MySuperCoolEndpoint\")
+        this.server?.registerERPCHandler(value, \"test/test2/TestClass/MySuperCoolEndpoint\", false, [], {\"parameters\":[],\"types\":{}})
     }
     get MySuperCoolEndpoint() {
         return this._MySuperCoolEndpoint
@@ -1,2 +1,2 @@
+mod class_generation;
 mod endpoints_to_classes;
-mod class_generation;
\ No newline at end of file
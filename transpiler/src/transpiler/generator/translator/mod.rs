@@ -1,9 +1,27 @@
 use crate::transpiler::{
-    parser::parser::{custom_type::CustomType, endpoint::Endpoint}, config::Role,
+    config::Role,
+    parser::parser::{custom_type::CustomType, endpoint::Endpoint},
+    validator::ValidationError,
 };
 
+pub mod rust;
+pub mod schema;
+mod tests;
 pub mod typescript;
 
+/**
+   The name of the source file at root level which declares middleware instead of endpoints
+*/
+pub const MIDDLEWARE_CLASS_NAME: &str = "middleware";
+
+/**
+   Checks if the class is generated from the middleware source file. Its endpoints are registered as middleware
+   by their plain identifier instead of as handlers.
+*/
+pub fn is_middleware_class(class_name: &str, relative_path: &str) -> bool {
+    relative_path.is_empty() && class_name == MIDDLEWARE_CLASS_NAME
+}
+
 /**
    Translates various easy-rpc elements into code strings of another language
 */
@@ -40,8 +58,22 @@ pub trait Translator {
     */
     fn file_suffix() -> String;
 
+    /**
+       Returns the library the generated code imports the easy-rpc runtime from.
+
+       The role is the role selected through the config.json
+    */
+    fn library_source(role: &Role) -> String;
+
+    /**
+       Checks the endpoints and custom types of a source file for limits of the generated language, in addition to the checks of the validator
+    */
+    fn validate(endpoints: &[Endpoint], custom_types: &[CustomType]) -> Vec<ValidationError>;
+
     /**
        Generate the client class actually used by the user
+
+       schema_hash is the hash of the endpoints of the role, it is sent with calls to detect clients and servers generated from different sources
     */
     fn generate_client(
         foreign: bool,
@@ -49,5 +81,6 @@ pub trait Translator {
         role: &Role,
         socket_enabled_browser_roles: &Vec<String>,
         library_source: &str,
+        schema_hash: &str,
     ) -> String;
 }
@@ -0,0 +1,96 @@
+use crate::transpiler::{
+    generator::translator::is_middleware_class,
+    parser::parser::{custom_type::CustomType, endpoint::Endpoint},
+};
+
+use super::{
+    endpoint::{endpoint_to_function, endpoint_type_definitions, middleware_to_function},
+    escape_identifier,
+    structure::custom_type_to_struct,
+    FILE_HEADER, RUNTIME_CRATE,
+};
+
+pub fn generate_class(
+    class_name: &str,
+    relative_path: &str,
+    endpoints: &Vec<Endpoint>,
+    foreign: bool,
+    class_imports: &Vec<String>,
+    custom_types: &Vec<CustomType>,
+) -> String {
+    let mut ret = String::new();
+
+    ret.push_str(FILE_HEADER);
+    for imp in class_imports {
+        ret.push_str(&format!("pub mod {};\n", escape_identifier(imp)));
+    }
+    ret.push('\n');
+
+    for t in custom_types {
+        ret.push_str(&custom_type_to_struct(t));
+        ret.push('\n');
+    }
+
+    for endpoint in endpoints {
+        let definitions = endpoint_type_definitions(endpoint);
+        if !definitions.is_empty() {
+            ret.push_str(&definitions);
+            ret.push('\n');
+        }
+    }
+
+    let class_identifier = escape_identifier(class_name);
+
+    // foreign classes call endpoints on a target, callback classes register handlers on a server
+    let (field, field_type) = if foreign {
+        ("target", "Target")
+    } else {
+        ("server", "Server")
+    };
+
+    ret.push_str(&format!(
+        "pub struct {class_identifier} {{\n    {field}: {RUNTIME_CRATE}::{field_type},\n"
+    ));
+    for imp in class_imports {
+        ret.push_str(&format!(
+            "    pub {imp}: {imp}::{imp},\n",
+            imp = escape_identifier(imp)
+        ));
+    }
+    ret.push_str("}\n\n");
+
+    ret.push_str(&format!(
+        "impl {class_identifier} {{
+    pub fn new({field}: {RUNTIME_CRATE}::{field_type}) -> Self {{
+        Self {{
+"
+    ));
+    for imp in class_imports {
+        ret.push_str(&format!(
+            "            {imp}: {imp}::{imp}::new({field}.clone()),\n",
+            imp = escape_identifier(imp)
+        ));
+    }
+    ret.push_str(&format!("            {field},\n        }}\n    }}\n\n"));
+
+    // endpoints of the middleware source file are registered as middleware
+    let middleware = is_middleware_class(class_name, relative_path);
+
+    for endpoint in endpoints {
+        if middleware {
+            ret.push_str(&middleware_to_function(endpoint));
+            continue;
+        }
+
+        ret.push_str(&endpoint_to_function(
+            endpoint,
+            foreign,
+            &format!("{relative_path}{class_name}/{}", endpoint.identifier),
+            custom_types,
+        ));
+    }
+
+    ret.push_str("}\n");
+
+    ret
+}
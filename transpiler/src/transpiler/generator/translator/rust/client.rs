@@ -0,0 +1,274 @@
+use crate::transpiler::config::Role;
+
+use super::{escape_identifier, FILE_HEADER};
+
+pub fn generate_client(
+    foreign: bool,
+    class_imports: &Vec<String>,
+    role: &Role,
+    socket_enabled_browser_roles: &Vec<String>,
+    library_source: &str,
+    schema_hash: &str,
+) -> String {
+    if foreign {
+        generate_target(class_imports, role, library_source, schema_hash)
+    } else {
+        generate_server(
+            class_imports,
+            role,
+            socket_enabled_browser_roles,
+            library_source,
+            schema_hash,
+        )
+    }
+}
+
+/**
+   Generates the module declarations, the struct with one field per class and the constructor body which creates the classes
+*/
+fn generate_struct(
+    class_imports: &Vec<String>,
+    role: &Role,
+    field: &str,
+    field_type: &str,
+    constructor_parameters: &str,
+    constructor_body: &str,
+) -> String {
+    let mut ret = String::new();
+
+    ret.push_str(FILE_HEADER);
+    for imp in class_imports {
+        ret.push_str(&format!("pub mod {};\n", escape_identifier(imp)));
+    }
+    ret.push('\n');
+
+    if let Some(doc) = &role.documentation {
+        ret.push_str(&format!("/**{doc}*/\n"));
+    }
+
+    ret.push_str(&format!(
+        "pub struct {class_name} {{\n    {field}: {field_type},\n",
+        class_name = role.name
+    ));
+    for imp in class_imports {
+        ret.push_str(&format!(
+            "    pub {imp}: {imp}::{imp},\n",
+            imp = escape_identifier(imp)
+        ));
+    }
+    ret.push_str("}\n\n");
+
+    ret.push_str(&format!(
+        "impl {class_name} {{
+    /**
+        Creates the {class_name} with all of its classes
+    */
+    pub fn new({constructor_parameters}) -> Self {{
+{constructor_body}        Self {{
+",
+        class_name = role.name
+    ));
+    for imp in class_imports {
+        ret.push_str(&format!(
+            "            {imp}: {imp}::{imp}::new({field}.clone()),\n",
+            imp = escape_identifier(imp)
+        ));
+    }
+    ret.push_str(&format!("            {field},\n        }}\n    }}\n"));
+
+    ret
+}
+
+fn generate_server(
+    class_imports: &Vec<String>,
+    role: &Role,
+    socket_enabled_browser_roles: &Vec<String>,
+    library_source: &str,
+    schema_hash: &str,
+) -> String {
+    // see the typescript translator for when websockets are enabled
+    let enable_websockets = (!socket_enabled_browser_roles.is_empty()
+        && role.role_type.contains(&"http-server".to_string()))
+        || (socket_enabled_browser_roles.contains(&role.name)
+            && role.role_type.contains(&"browser".to_string()));
+
+    let mut constructor_body = format!(
+        "        let server = {library_source}::Server::new(options, {enable_websockets});\n"
+    );
+    // browsers don't accept calls from clients, see the typescript translator
+    if role.role_type.contains(&"http-server".to_string()) {
+        constructor_body.push_str(&format!(
+            "        server.set_schema_hash(\"{schema_hash}\");\n"
+        ));
+    }
+
+    let mut ret = generate_struct(
+        class_imports,
+        role,
+        "server",
+        &format!("{library_source}::Server"),
+        &format!("options: {library_source}::ServerOptions"),
+        &constructor_body,
+    );
+
+    ret.push_str(
+        "
+    /**
+        Starts the server as configured
+    */
+    pub async fn run(&self) {
+        self.server.run().await.await
+    }
+
+    /**
+        Stops the server
+    */
+    pub fn stop(&self) -> Result<(), String> {
+        self.server.stop()
+    }
+",
+    );
+
+    if enable_websockets && !role.role_type.contains(&"browser".to_string()) {
+        ret.push_str(&format!(
+            "
+    /**
+        Calls the callback for every browser which connects via websocket
+    */
+    pub fn on_connection<F>(&self, callback: F)
+    where
+        F: Fn({class_name}Connection) + Send + 'static,
+    {{
+        let socket_broadcaster = self.server.get_socket_broadcaster().clone();
+        tokio::spawn(async move {{
+            while let Ok(socket) = socket_broadcaster.recv_async().await {{
+                match socket.role.as_str() {{
+",
+            class_name = role.name
+        ));
+
+        for browser_role in socket_enabled_browser_roles {
+            ret.push_str(&format!(
+                "                    \"{browser_role}\" => {{
+                        let mut target = super::{browser_role}::{browser_role}::new(String::new());
+                        target.set_socket(socket);
+                        callback({class_name}Connection::{browser_role}(target));
+                    }}
+",
+                class_name = role.name
+            ));
+        }
+
+        ret.push_str(
+            "                    _ => {}
+                }
+            }
+        });
+    }
+",
+        );
+    }
+
+    ret.push_str("}\n");
+
+    if enable_websockets && !role.role_type.contains(&"browser".to_string()) {
+        ret.push_str(&format!(
+            "
+/**
+    A browser which connected to the {class_name} via websocket
+*/
+pub enum {class_name}Connection {{
+",
+            class_name = role.name
+        ));
+        for browser_role in socket_enabled_browser_roles {
+            ret.push_str(&format!(
+                "    {browser_role}(super::{browser_role}::{browser_role}),\n"
+            ));
+        }
+        ret.push_str("}\n");
+    }
+
+    ret
+}
+
+fn generate_target(
+    class_imports: &Vec<String>,
+    role: &Role,
+    library_source: &str,
+    schema_hash: &str,
+) -> String {
+    let target_type = if role.role_type.contains(&"browser".to_string()) {
+        "Browser"
+    } else {
+        "HttpServer"
+    };
+
+    let mut constructor_body = format!(
+        "        let target = {library_source}::Target::new(address, {library_source}::erpc::target::TargetType::{target_type});\n"
+    );
+    if target_type == "HttpServer" {
+        constructor_body.push_str(&format!(
+            "        target.set_schema_hash(Some(\"{schema_hash}\".to_string()));\n"
+        ));
+    }
+
+    let mut ret = generate_struct(
+        class_imports,
+        role,
+        "target",
+        &format!("{library_source}::Target"),
+        "address: String",
+        &constructor_body,
+    );
+
+    ret.push_str(&format!(
+        "
+    /**
+        Sets the timeout for all calls to the {class_name}. None waits forever
+    */
+    pub fn set_timeout(&self, timeout: Option<std::time::Duration>) {{
+        self.target.set_timeout(timeout)
+    }}
+",
+        class_name = role.name
+    ));
+
+    if target_type == "HttpServer" {
+        ret.push_str(&format!(
+            "
+    /**
+        Creates the {class_name} for a server running in the same process, calls are dispatched to its handlers without http
+    */
+    pub fn new_loopback(server: &{library_source}::Server) -> Self {{
+        let target = {library_source}::Target::new_loopback(server);
+        Self {{
+",
+            class_name = role.name
+        ));
+        for imp in class_imports {
+            ret.push_str(&format!(
+                "            {imp}: {imp}::{imp}::new(target.clone()),\n",
+                imp = escape_identifier(imp)
+            ));
+        }
+        ret.push_str("            target,\n        }\n    }\n");
+    }
+
+    if target_type == "Browser" {
+        ret.push_str(&format!(
+            "
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It sets the socket the browser is connected with.
+    */
+    pub fn set_socket(&mut self, socket: {library_source}::Socket) {{
+        self.target.set_socket(socket)
+    }}
+"
+        ));
+    }
+
+    ret.push_str("}\n");
+
+    ret
+}
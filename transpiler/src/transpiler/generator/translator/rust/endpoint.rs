@@ -0,0 +1,457 @@
+use crate::transpiler::{
+    generator::translator::schema::endpoint_schema,
+    parser::parser::{custom_type::CustomType, endpoint::Endpoint},
+};
+
+use super::{
+    capitalize, escape_identifier, serde_derive, stringify_field_type,
+    stringify_optional_field_type, type_to_definition, RUNTIME_CRATE,
+};
+
+/**
+   Translates an endpoint to a method for the target language.
+   The foreign parameter indicates if the generated code should be for calling and endpoint
+   on another machine or to provide logic for handling a call on this machine.
+   The url must be a unique identifier for determining this endpoint.
+   The custom types are needed to generate the schema the parameters of incoming calls are checked against.
+*/
+pub fn endpoint_to_function(
+    endpoint: &Endpoint,
+    foreign: bool,
+    url: &str,
+    custom_types: &[CustomType],
+) -> String {
+    if foreign {
+        make_foreign_endpoint(endpoint, url)
+    } else {
+        make_callback_endpoint(endpoint, url, custom_types)
+    }
+}
+
+/**
+   Generates the definitions of all enum types which are used as parameter or return type of the endpoint
+*/
+pub fn endpoint_type_definitions(endpoint: &Endpoint) -> String {
+    let mut ret = String::new();
+
+    if endpoint.named_parameters {
+        ret.push_str(&parameters_struct(endpoint));
+    }
+
+    for parameter in &endpoint.parameters {
+        ret.push_str(&type_to_definition(
+            &parameter.parameter_type,
+            &parameter_name_hint(endpoint, &parameter.identifier),
+        ));
+    }
+
+    if let Some(return_type) = &endpoint.return_type {
+        ret.push_str(&type_to_definition(
+            return_type,
+            &return_name_hint(endpoint),
+        ));
+    }
+
+    if let Some(error_type) = &endpoint.error_type {
+        ret.push_str(&type_to_definition(error_type, &error_name_hint(endpoint)));
+    }
+
+    ret
+}
+
+/**
+   Endpoints with named parameters take a single struct, it is sent as an object keyed by parameter name
+*/
+fn parameters_struct(endpoint: &Endpoint) -> String {
+    let mut ret = String::new();
+
+    ret.push_str(&serde_derive("Debug, Clone"));
+    ret.push_str(&format!(
+        "pub struct {} {{\n",
+        parameters_struct_name(endpoint)
+    ));
+
+    for parameter in &endpoint.parameters {
+        if parameter.optional {
+            ret.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+        }
+
+        ret.push_str(&format!(
+            "    pub {}: {},\n",
+            escape_identifier(&parameter.identifier),
+            stringify_optional_field_type(
+                &parameter.parameter_type,
+                parameter.optional,
+                &parameter_name_hint(endpoint, &parameter.identifier)
+            )
+        ));
+    }
+
+    ret.push_str("}\n");
+    ret
+}
+
+fn parameters_struct_name(endpoint: &Endpoint) -> String {
+    format!("{}Parameters", capitalize(&endpoint.identifier))
+}
+
+/**
+   The types of the parameters the handler of an endpoint takes, endpoints with named parameters take their struct
+*/
+fn stringify_handler_parameters(endpoint: &Endpoint) -> Vec<String> {
+    if endpoint.named_parameters {
+        return vec![parameters_struct_name(endpoint)];
+    }
+
+    endpoint
+        .parameters
+        .iter()
+        .map(|parameter| {
+            stringify_optional_field_type(
+                &parameter.parameter_type,
+                parameter.optional,
+                &parameter_name_hint(endpoint, &parameter.identifier),
+            )
+        })
+        .collect()
+}
+
+/**
+   The parameters of the function calling an endpoint, each starting with a comma since they follow &self
+*/
+fn stringify_foreign_parameters(endpoint: &Endpoint) -> String {
+    if endpoint.named_parameters {
+        return format!(", parameters: {}", parameters_struct_name(endpoint));
+    }
+
+    endpoint
+        .parameters
+        .iter()
+        .map(|parameter| {
+            format!(
+                ", {}: {}",
+                escape_identifier(&parameter.identifier),
+                stringify_optional_field_type(
+                    &parameter.parameter_type,
+                    parameter.optional,
+                    &parameter_name_hint(endpoint, &parameter.identifier)
+                )
+            )
+        })
+        .collect()
+}
+
+/**
+   The expression building the parameters of the request calling an endpoint
+*/
+fn stringify_request_parameters(endpoint: &Endpoint) -> String {
+    if endpoint.named_parameters {
+        return format!("{RUNTIME_CRATE}::erpc::protocol::Parameters::named(&parameters)?");
+    }
+
+    let values = endpoint
+        .parameters
+        .iter()
+        .map(|parameter| {
+            format!(
+                "{RUNTIME_CRATE}::serde_json::to_value({})?",
+                escape_identifier(&parameter.identifier)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!("vec![{values}].into()")
+}
+
+fn parameter_name_hint(endpoint: &Endpoint, parameter_identifier: &str) -> String {
+    format!(
+        "{}{}",
+        capitalize(&endpoint.identifier),
+        capitalize(parameter_identifier)
+    )
+}
+
+fn return_name_hint(endpoint: &Endpoint) -> String {
+    format!("{}Return", capitalize(&endpoint.identifier))
+}
+
+fn error_name_hint(endpoint: &Endpoint) -> String {
+    format!("{}Error", capitalize(&endpoint.identifier))
+}
+
+fn stringify_return_type(endpoint: &Endpoint) -> String {
+    match &endpoint.return_type {
+        Some(return_type) => stringify_field_type(return_type, &return_name_hint(endpoint)),
+        None => "()".to_string(),
+    }
+}
+
+/**
+   Stringifies the type the handler of an endpoint returns, endpoints which declare an error type return a Result
+*/
+fn stringify_handler_return_type(endpoint: &Endpoint) -> String {
+    match &endpoint.error_type {
+        Some(error_type) => format!(
+            "Result<{}, {}>",
+            stringify_return_type(endpoint),
+            stringify_field_type(error_type, &error_name_hint(endpoint))
+        ),
+        None => stringify_return_type(endpoint),
+    }
+}
+
+/**
+   What the handler of an endpoint has to look like, the types are stringified like in the generated code
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandlerSignature {
+    pub parameters: Vec<String>,
+    /**
+       What the future of the handler resolves to, the item type for streaming endpoints
+    */
+    pub output: String,
+    pub streaming: bool,
+    /**
+       Set for endpoints which declare an error type, their handlers return a Result
+    */
+    pub fallible: bool,
+    pub middleware: Vec<String>,
+    /**
+       The schema the parameters are checked against as json, it covers what the parameter types can't express
+    */
+    pub schema: String,
+}
+
+pub fn handler_signature(endpoint: &Endpoint, custom_types: &[CustomType]) -> HandlerSignature {
+    HandlerSignature {
+        parameters: stringify_handler_parameters(endpoint),
+        output: if endpoint.streaming {
+            stringify_return_type(endpoint)
+        } else {
+            stringify_handler_return_type(endpoint)
+        },
+        streaming: endpoint.streaming,
+        fallible: endpoint.error_type.is_some(),
+        middleware: endpoint.middleware_identifiers.clone(),
+        schema: endpoint_schema(endpoint, custom_types).to_string(),
+    }
+}
+
+fn make_foreign_endpoint(endpoint: &Endpoint, url: &str) -> String {
+    if endpoint.streaming {
+        return make_foreign_stream_endpoint(endpoint, url);
+    }
+
+    let mut ret = String::new();
+
+    if let Some(val) = &endpoint.documentation {
+        ret.push_str(&format!("    /**{val}*/\n"));
+    }
+
+    ret.push_str(&format!(
+        "    pub async fn {identifier}(&self",
+        identifier = escape_identifier(&endpoint.identifier)
+    ));
+
+    ret.push_str(&stringify_foreign_parameters(endpoint));
+
+    ret.push_str(&format!(
+        ") -> Result<{}, {RUNTIME_CRATE}::erpc::protocol::SendableError> {{
+        let response = self
+            .target
+            .call({RUNTIME_CRATE}::erpc::protocol::Request {{
+                identifier: \"{url}\".to_string(),
+                parameters: {},
+            }})
+            .await;
+
+",
+        stringify_handler_return_type(endpoint),
+        stringify_request_parameters(endpoint)
+    ));
+
+    if endpoint.error_type.is_some() {
+        ret.push_str(&format!(
+            "        match response.body {{
+            Err({RUNTIME_CRATE}::erpc::protocol::SendableError::Application {{ error }}) => {{
+                Ok(Err({RUNTIME_CRATE}::serde_json::from_value(error)?))
+            }}
+            body => Ok(Ok({RUNTIME_CRATE}::serde_json::from_value(body?)?)),
+        }}
+    }}
+
+"
+        ));
+    } else {
+        ret.push_str(&format!(
+            "        Ok({RUNTIME_CRATE}::serde_json::from_value(response.body?)?)
+    }}
+
+"
+        ));
+    }
+
+    ret
+}
+
+/**
+   Streams are requested without awaiting anything, the items are deserialized while they are consumed
+*/
+fn make_foreign_stream_endpoint(endpoint: &Endpoint, url: &str) -> String {
+    let mut ret = String::new();
+
+    if let Some(val) = &endpoint.documentation {
+        ret.push_str(&format!("    /**{val}*/\n"));
+    }
+
+    ret.push_str(&format!(
+        "    pub fn {identifier}(&self",
+        identifier = escape_identifier(&endpoint.identifier)
+    ));
+
+    ret.push_str(&stringify_foreign_parameters(endpoint));
+
+    ret.push_str(&format!(
+        ") -> Result<{RUNTIME_CRATE}::erpc::stream::TypedStream<{}>, {RUNTIME_CRATE}::erpc::protocol::SendableError> {{
+        let items = self.target.call_stream({RUNTIME_CRATE}::erpc::protocol::Request {{
+            identifier: \"{url}\".to_string(),
+            parameters: {},
+        }})?;
+
+        Ok(items.typed())
+    }}
+
+",
+        stringify_return_type(endpoint),
+        stringify_request_parameters(endpoint)
+    ));
+
+    ret
+}
+
+fn make_callback_endpoint(endpoint: &Endpoint, url: &str, custom_types: &[CustomType]) -> String {
+    let mut ret = String::new();
+
+    if let Some(val) = &endpoint.documentation {
+        ret.push_str(&format!("    /**{val}*/\n"));
+    }
+
+    let handler_parameters = stringify_handler_parameters(endpoint);
+    let params_string = handler_parameters.join(", ");
+
+    // the handler parameters are deserialized as tuple, single element tuples need a trailing comma
+    let params_tuple = if handler_parameters.len() == 1 {
+        format!("({params_string},)")
+    } else {
+        format!("({params_string})")
+    };
+
+    // endpoints with an error type send the errors returned by the handler as application errors
+    let register = match &endpoint.error_type {
+        Some(error_type) => format!(
+            "register_fallible_handler::<H, {params_tuple}, {}, {}>",
+            stringify_return_type(endpoint),
+            stringify_field_type(error_type, &error_name_hint(endpoint))
+        ),
+        None if endpoint.streaming => format!(
+            "register_stream_handler::<H, {params_tuple}, {}>",
+            stringify_return_type(endpoint)
+        ),
+        None => format!("register_handler::<H, {params_tuple}>"),
+    };
+
+    // the runtime runs the declared middleware in order before the handler
+    let middleware = if endpoint.middleware_identifiers.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "        self.server.set_endpoint_middleware(\"{url}\", vec![{}]);\n",
+            endpoint
+                .middleware_identifiers
+                .iter()
+                .map(|identifier| format!("\"{identifier}\".to_string()"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    };
+
+    // the parameter types don't cover everything the sources declare, e.g. the length of arrays, so calls are checked against the schema as well
+    let schema = raw_string_literal(&endpoint_schema(endpoint, custom_types).to_string());
+
+    // stream handlers resolve to a stream of the items instead of the result
+    let (generics, output, stream_bound) = if endpoint.streaming {
+        (
+            "H, F, S",
+            "S".to_string(),
+            format!(
+                "        S: {RUNTIME_CRATE}::erpc::stream::Stream<Item = {}> + Send + 'static,\n",
+                stringify_return_type(endpoint)
+            ),
+        )
+    } else {
+        (
+            "H, F",
+            stringify_handler_return_type(endpoint),
+            String::new(),
+        )
+    };
+
+    ret.push_str(&format!(
+        "    pub fn {identifier}<{generics}>(&mut self, handler: H)
+    where
+        H: Fn({params_string}) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = {output}> + Send + 'static,
+{stream_bound}    {{
+        self.server
+            .{register}(handler, \"{url}\");
+        self.server.set_endpoint_schema(
+            \"{url}\",
+            {RUNTIME_CRATE}::serde_json::from_str({schema}).expect(\"the generated schema is valid\"),
+        );
+{middleware}    }}
+
+",
+        identifier = escape_identifier(&endpoint.identifier),
+    ));
+
+    ret
+}
+
+/**
+   Translates an endpoint of the middleware source file to a method which registers the middleware on the server
+*/
+pub fn middleware_to_function(endpoint: &Endpoint) -> String {
+    let mut ret = String::new();
+
+    if let Some(val) = &endpoint.documentation {
+        ret.push_str(&format!("    /**{val}*/\n"));
+    }
+
+    ret.push_str(&format!(
+        "    pub fn {identifier}<M, F>(&mut self, middleware: M)
+    where
+        M: Fn({RUNTIME_CRATE}::MiddlewareCall) -> F + Send + Sync + 'static,
+        F: std::future::Future<Output = Result<{RUNTIME_CRATE}::MiddlewareCall, {RUNTIME_CRATE}::erpc::protocol::SendableError>> + Send + 'static,
+    {{
+        self.server.register_middleware(middleware, \"{id}\");
+    }}
+
+",
+        identifier = escape_identifier(&endpoint.identifier),
+        id = endpoint.identifier,
+    ));
+
+    ret
+}
+
+/**
+   Wraps the value in a raw string literal with enough hashes that the value can't end it
+*/
+fn raw_string_literal(value: &str) -> String {
+    let mut hashes = String::from("#");
+    while value.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}\"{value}\"{hashes}")
+}
@@ -0,0 +1,295 @@
+use crate::transpiler::{
+    config::Role,
+    parser::{
+        lexer::literal::LiteralType,
+        parser::{
+            custom_type::CustomType,
+            endpoint::Endpoint,
+            erpc_type::{ArrayAmount, Enum, EnumType, Primitive, PrimitiveType, Type},
+        },
+    },
+    validator::{validate_rust, ValidationError},
+};
+
+use self::{class::generate_class, client::generate_client};
+
+use super::Translator;
+
+mod class;
+mod client;
+mod endpoint;
+mod structure;
+mod tests;
+
+pub use endpoint::{handler_signature, HandlerSignature};
+
+/**
+   Generates rust code which uses the http-server crate as runtime
+*/
+pub struct RustTranslator;
+
+impl Translator for RustTranslator {
+    fn generate_class(
+        class_name: &str,
+        relative_path: &str,
+        endpoints: &Vec<Endpoint>,
+        foreign: bool,
+        class_imports: &Vec<String>,
+        custom_types: &Vec<CustomType>,
+    ) -> String {
+        generate_class(
+            class_name,
+            relative_path,
+            endpoints,
+            foreign,
+            class_imports,
+            custom_types,
+        )
+    }
+
+    fn file_suffix() -> String {
+        String::from("rs")
+    }
+
+    fn library_source(_role: &Role) -> String {
+        // browser roles are rejected before generating, therefore the server runtime is always used
+        String::from(RUNTIME_CRATE)
+    }
+
+    fn validate(endpoints: &[Endpoint], custom_types: &[CustomType]) -> Vec<ValidationError> {
+        validate_rust(endpoints, custom_types)
+    }
+
+    fn generate_client(
+        foreign: bool,
+        class_imports: &Vec<String>,
+        role: &Role,
+        socket_enabled_browser_roles: &Vec<String>,
+        library_source: &str,
+        schema_hash: &str,
+    ) -> String {
+        generate_client(
+            foreign,
+            class_imports,
+            role,
+            socket_enabled_browser_roles,
+            library_source,
+            schema_hash,
+        )
+    }
+}
+
+/**
+   The crate which provides the runtime for the generated code
+*/
+const RUNTIME_CRATE: &str = "http_server";
+
+/**
+   Inner attributes every generated file starts with. Identifiers are taken from the .erpc sources as they are,
+   which collides with the rust naming conventions.
+*/
+const FILE_HEADER: &str =
+    "#![allow(non_snake_case, non_camel_case_types, dead_code, unused_imports)]\n";
+
+const RUST_KEYWORDS: [&str; 34] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/**
+   Escapes identifiers which are reserved in rust by turning them into raw identifiers.
+   Keywords which can't be raw identifiers, like self, are rejected by validate_rust instead.
+*/
+fn escape_identifier(identifier: &str) -> String {
+    if RUST_KEYWORDS.contains(&identifier) {
+        format!("r#{identifier}")
+    } else {
+        identifier.to_string()
+    }
+}
+
+/**
+   The derive of generated types. Serde is used through the runtime crate, so projects only need to depend on the runtime.
+*/
+fn serde_derive(traits: &str) -> String {
+    format!(
+        "#[derive({traits}, {RUNTIME_CRATE}::serde::Serialize, {RUNTIME_CRATE}::serde::Deserialize)]\n#[serde(crate = \"{RUNTIME_CRATE}::serde\")]\n"
+    )
+}
+
+/**
+   Uppercases the first char of a string, used to build type names out of identifiers
+*/
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/**
+   Stringifies a type. Since rust has no anonymous union types, enums are referenced by the name_hint.
+   The matching definition needs to be generated with enum_to_definition.
+*/
+fn stringify_field_type(field_type: &Type, name_hint: &str) -> String {
+    match field_type {
+        Type::Primitive(primitive) => stringify_primitive(primitive),
+        Type::Enum(_) => name_hint.to_string(),
+        Type::Custom(custom) => wrap_array(custom.identifier.to_string(), &custom.array_amount),
+    }
+}
+
+fn stringify_primitive(primitive: &Primitive) -> String {
+    wrap_array(
+        stringify_primitive_type(&primitive.primitive_type).to_string(),
+        &primitive.array_amount,
+    )
+}
+
+fn stringify_primitive_type(primitive_type: &PrimitiveType) -> &'static str {
+    match primitive_type {
+        PrimitiveType::Boolean => "bool",
+        PrimitiveType::Int8 => "i8",
+        PrimitiveType::Int16 => "i16",
+        PrimitiveType::Int32 => "i32",
+        PrimitiveType::Int64 => "i64",
+        PrimitiveType::Float32 => "f32",
+        PrimitiveType::Float64 => "f64",
+        PrimitiveType::String => "String",
+    }
+}
+
+fn wrap_array(type_string: String, array_amount: &ArrayAmount) -> String {
+    match array_amount {
+        ArrayAmount::NoArray => type_string,
+        // fixed size arrays are not used since serde only supports them up to a length of 32
+        ArrayAmount::NoLengthSpecified => format!("Vec<{type_string}>"),
+        ArrayAmount::LengthSpecified(_) => format!("Vec<{type_string}>"),
+    }
+}
+
+/**
+   Stringifies a type which might be optional
+*/
+fn stringify_optional_field_type(field_type: &Type, optional: bool, name_hint: &str) -> String {
+    let type_string = stringify_field_type(field_type, name_hint);
+    if optional {
+        format!("Option<{type_string}>")
+    } else {
+        type_string
+    }
+}
+
+/**
+   Returns the definition of an enum if the type requires one, otherwise an empty string
+*/
+fn type_to_definition(field_type: &Type, name: &str) -> String {
+    match field_type {
+        Type::Enum(en) => enum_to_definition(en, name),
+        _ => String::new(),
+    }
+}
+
+/**
+   Generates a named rust enum for an erpc enum type.
+   Enums which only consist of string literals are generated as plain enums with renamed variants,
+   all other enums are generated as untagged enums where each possible kind of value is a variant.
+*/
+fn enum_to_definition(en: &Enum, name: &str) -> String {
+    let mut ret = String::new();
+
+    let only_string_literals = en
+        .values
+        .iter()
+        .all(|value| matches!(value, EnumType::Literal(LiteralType::String(_))));
+
+    if only_string_literals {
+        ret.push_str(&format!(
+            "{}pub enum {name} {{\n",
+            serde_derive("Debug, Clone, PartialEq")
+        ));
+
+        let mut used_variants: Vec<String> = vec![];
+        for value in &en.values {
+            let literal = match value {
+                EnumType::Literal(LiteralType::String(literal)) => literal,
+                _ => continue,
+            };
+
+            let mut variant = literal_to_variant_name(literal);
+            if used_variants.contains(&variant) {
+                variant = format!("{variant}{}", used_variants.len());
+            }
+            used_variants.push(variant.clone());
+
+            ret.push_str(&format!(
+                "    #[serde(rename = \"{literal}\")]\n    {variant},\n"
+            ));
+        }
+
+        ret.push_str("}\n");
+        return ret;
+    }
+
+    ret.push_str(&format!(
+        "{}#[serde(untagged)]\npub enum {name} {{\n",
+        serde_derive("Debug, Clone")
+    ));
+
+    let mut used_variants: Vec<String> = vec![];
+    for value in &en.values {
+        let (variant, type_string) = match value {
+            EnumType::Primitive(primitive) => {
+                let mut variant = capitalize(&format!("{:?}", primitive.primitive_type));
+                if !matches!(primitive.array_amount, ArrayAmount::NoArray) {
+                    variant.push_str("Array");
+                }
+                (variant, stringify_primitive(primitive))
+            }
+            EnumType::Custom(custom) => {
+                let mut variant = capitalize(&custom.identifier);
+                if !matches!(custom.array_amount, ArrayAmount::NoArray) {
+                    variant.push_str("Array");
+                }
+                (
+                    variant,
+                    wrap_array(custom.identifier.to_string(), &custom.array_amount),
+                )
+            }
+            EnumType::Literal(literal) => match literal {
+                LiteralType::Boolean(_) => ("Boolean".to_string(), "bool".to_string()),
+                LiteralType::String(_) => ("String".to_string(), "String".to_string()),
+                LiteralType::Float(_) => ("Float".to_string(), "f64".to_string()),
+                LiteralType::Integer(_) => ("Integer".to_string(), "i64".to_string()),
+            },
+        };
+
+        if used_variants.contains(&variant) {
+            continue;
+        }
+        used_variants.push(variant.clone());
+
+        ret.push_str(&format!("    {variant}({type_string}),\n"));
+    }
+
+    ret.push_str("}\n");
+    ret
+}
+
+/**
+   Builds a valid enum variant name out of a string literal
+*/
+fn literal_to_variant_name(literal: &str) -> String {
+    let mut variant = String::new();
+    for part in literal.split(|c: char| !c.is_ascii_alphanumeric()) {
+        variant.push_str(&capitalize(part));
+    }
+
+    match variant.chars().next() {
+        Some(first) if first.is_ascii_digit() => format!("Value{variant}"),
+        Some(_) => variant,
+        None => "Empty".to_string(),
+    }
+}
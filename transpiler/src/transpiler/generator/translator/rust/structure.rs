@@ -0,0 +1,61 @@
+use crate::transpiler::parser::parser::{
+    custom_type::CustomType,
+    erpc_type::{ArrayAmount, Type},
+};
+
+use super::{
+    capitalize, escape_identifier, serde_derive, stringify_field_type, type_to_definition,
+};
+
+/**
+   Translates a custom type into a serde struct. Enum fields get their own type which is named after the struct and the field.
+*/
+pub fn custom_type_to_struct(custom_type: &CustomType) -> String {
+    let mut builder = String::new();
+    let mut enum_definitions = String::new();
+
+    if let Some(doc) = &custom_type.documentation {
+        builder.push_str(&format!("/**{doc}*/\n"));
+    }
+
+    builder.push_str(&serde_derive("Debug, Clone"));
+    builder.push_str(&format!("pub struct {} {{\n", custom_type.identifier));
+
+    for field in &custom_type.fields {
+        if let Some(doc) = &field.documentation {
+            builder.push_str(&format!("    /**{doc}*/\n"));
+        }
+
+        let name_hint = format!(
+            "{}{}",
+            custom_type.identifier,
+            capitalize(&field.identifier)
+        );
+        enum_definitions.push_str(&type_to_definition(&field.field_type, &name_hint));
+
+        let mut type_string = stringify_field_type(&field.field_type, &name_hint);
+
+        // directly recursive fields would lead to a type of infinite size
+        if let Type::Custom(custom) = &field.field_type {
+            if custom.identifier == custom_type.identifier
+                && matches!(custom.array_amount, ArrayAmount::NoArray)
+            {
+                type_string = format!("Box<{type_string}>");
+            }
+        }
+
+        if field.optional {
+            builder.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            type_string = format!("Option<{type_string}>");
+        }
+
+        builder.push_str(&format!(
+            "    pub {}: {type_string},\n",
+            escape_identifier(&field.identifier)
+        ));
+    }
+
+    builder.push_str("}\n");
+    builder.push_str(&enum_definitions);
+    builder
+}
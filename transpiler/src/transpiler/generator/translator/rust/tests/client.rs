@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod tests {
+    use crate::transpiler::{config::Role, generator::translator::rust::client::generate_client};
+
+    #[test]
+    fn test_success_foreign() {
+        let result = generate_client(
+            true,
+            &vec!["api".to_string(), "tracks".to_string()],
+            &Role {
+                documentation: Some("Example docs".to_string()),
+                name: "Client".to_string(),
+                role_type: "browser".to_string(),
+            },
+            &vec!["Client".to_string()],
+            "http_server",
+            "3f9a0c1e5b7d2486",
+        );
+        assert_eq!(
+            result,
+            "#![allow(non_snake_case, non_camel_case_types, dead_code, unused_imports)]
+pub mod api;
+pub mod tracks;
+
+/**Example docs*/
+pub struct Client {
+    target: http_server::Target,
+    pub api: api::api,
+    pub tracks: tracks::tracks,
+}
+
+impl Client {
+    /**
+        Creates the Client with all of its classes
+    */
+    pub fn new(address: String) -> Self {
+        let target = http_server::Target::new(address, http_server::erpc::target::TargetType::Browser);
+        Self {
+            api: api::api::new(target.clone()),
+            tracks: tracks::tracks::new(target.clone()),
+            target,
+        }
+    }
+
+    /**
+        Sets the timeout for all calls to the Client. None waits forever
+    */
+    pub fn set_timeout(&self, timeout: Option<std::time::Duration>) {
+        self.target.set_timeout(timeout)
+    }
+
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It sets the socket the browser is connected with.
+    */
+    pub fn set_socket(&mut self, socket: http_server::Socket) {
+        self.target.set_socket(socket)
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_success_foreign_server() {
+        let result = generate_client(
+            true,
+            &vec!["api".to_string()],
+            &Role {
+                documentation: None,
+                name: "Backend".to_string(),
+                role_type: "http-server".to_string(),
+            },
+            &vec![],
+            "http_server",
+            "3f9a0c1e5b7d2486",
+        );
+        assert_eq!(
+            result,
+            "#![allow(non_snake_case, non_camel_case_types, dead_code, unused_imports)]
+pub mod api;
+
+pub struct Backend {
+    target: http_server::Target,
+    pub api: api::api,
+}
+
+impl Backend {
+    /**
+        Creates the Backend with all of its classes
+    */
+    pub fn new(address: String) -> Self {
+        let target = http_server::Target::new(address, http_server::erpc::target::TargetType::HttpServer);
+        target.set_schema_hash(Some(\"3f9a0c1e5b7d2486\".to_string()));
+        Self {
+            api: api::api::new(target.clone()),
+            target,
+        }
+    }
+
+    /**
+        Sets the timeout for all calls to the Backend. None waits forever
+    */
+    pub fn set_timeout(&self, timeout: Option<std::time::Duration>) {
+        self.target.set_timeout(timeout)
+    }
+
+    /**
+        Creates the Backend for a server running in the same process, calls are dispatched to its handlers without http
+    */
+    pub fn new_loopback(server: &http_server::Server) -> Self {
+        let target = http_server::Target::new_loopback(server);
+        Self {
+            api: api::api::new(target.clone()),
+            target,
+        }
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_success_callback() {
+        let result = generate_client(
+            false,
+            &vec!["api".to_string(), "tracks".to_string()],
+            &Role {
+                documentation: Some("Example docs".to_string()),
+                name: "Server".to_string(),
+                role_type: "http-server".to_string(),
+            },
+            &vec!["Client".to_string()],
+            "http_server",
+            "3f9a0c1e5b7d2486",
+        );
+        assert_eq!(
+            result,
+            "#![allow(non_snake_case, non_camel_case_types, dead_code, unused_imports)]
+pub mod api;
+pub mod tracks;
+
+/**Example docs*/
+pub struct Server {
+    server: http_server::Server,
+    pub api: api::api,
+    pub tracks: tracks::tracks,
+}
+
+impl Server {
+    /**
+        Creates the Server with all of its classes
+    */
+    pub fn new(options: http_server::ServerOptions) -> Self {
+        let server = http_server::Server::new(options, true);
+        server.set_schema_hash(\"3f9a0c1e5b7d2486\");
+        Self {
+            api: api::api::new(server.clone()),
+            tracks: tracks::tracks::new(server.clone()),
+            server,
+        }
+    }
+
+    /**
+        Starts the server as configured
+    */
+    pub async fn run(&self) {
+        self.server.run().await.await
+    }
+
+    /**
+        Stops the server
+    */
+    pub fn stop(&self) -> Result<(), String> {
+        self.server.stop()
+    }
+
+    /**
+        Calls the callback for every browser which connects via websocket
+    */
+    pub fn on_connection<F>(&self, callback: F)
+    where
+        F: Fn(ServerConnection) + Send + 'static,
+    {
+        let socket_broadcaster = self.server.get_socket_broadcaster().clone();
+        tokio::spawn(async move {
+            while let Ok(socket) = socket_broadcaster.recv_async().await {
+                match socket.role.as_str() {
+                    \"Client\" => {
+                        let mut target = super::Client::Client::new(String::new());
+                        target.set_socket(socket);
+                        callback(ServerConnection::Client(target));
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+}
+
+/**
+    A browser which connected to the Server via websocket
+*/
+pub enum ServerConnection {
+    Client(super::Client::Client),
+}
+"
+        );
+    }
+}
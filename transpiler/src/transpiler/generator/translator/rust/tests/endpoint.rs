@@ -0,0 +1,407 @@
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Range;
+
+    use crate::transpiler::{
+        generator::translator::rust::endpoint::{
+            endpoint_to_function, endpoint_type_definitions, middleware_to_function,
+        },
+        parser::{
+            lexer::literal::LiteralType,
+            parser::{
+                endpoint::{Endpoint, Parameter},
+                erpc_type::{ArrayAmount, Custom, Enum, EnumType, Primitive, PrimitiveType, Type},
+            },
+        },
+    };
+
+    fn endpoint() -> Endpoint {
+        Endpoint {
+            middleware_identifiers: vec![],
+            documentation: Some("some docs".to_string()),
+            range: Range::default(),
+            identifier: "MySuperCoolEndpoint".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: Some(Type::Enum(Enum {
+                values: vec![
+                    EnumType::Literal(LiteralType::String("success".to_string())),
+                    EnumType::Literal(LiteralType::String("not found".to_string())),
+                ],
+            })),
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![
+                Parameter {
+                    identifier: "p1".to_string(),
+                    optional: true,
+                    parameter_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::NoLengthSpecified,
+                        primitive_type: PrimitiveType::String,
+                    }),
+                },
+                Parameter {
+                    identifier: "type".to_string(),
+                    optional: false,
+                    parameter_type: Type::Enum(Enum {
+                        values: vec![
+                            EnumType::Primitive(Primitive {
+                                array_amount: ArrayAmount::NoArray,
+                                primitive_type: PrimitiveType::Int8,
+                            }),
+                            EnumType::Literal(LiteralType::String("none".to_string())),
+                        ],
+                    }),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_success_foreign() {
+        let result = endpoint_to_function(&endpoint(), true, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    /**some docs*/
+    pub async fn MySuperCoolEndpoint(&self, p1: Option<Vec<String>>, r#type: MySuperCoolEndpointType) -> Result<MySuperCoolEndpointReturn, http_server::erpc::protocol::SendableError> {
+        let response = self
+            .target
+            .call(http_server::erpc::protocol::Request {
+                identifier: \"ABC123\".to_string(),
+                parameters: vec![http_server::serde_json::to_value(p1)?, http_server::serde_json::to_value(r#type)?].into(),
+            })
+            .await;
+
+        Ok(http_server::serde_json::from_value(response.body?)?)
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_success_callback() {
+        let result = endpoint_to_function(&endpoint(), false, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    /**some docs*/
+    pub fn MySuperCoolEndpoint<H, F>(&mut self, handler: H)
+    where
+        H: Fn(Option<Vec<String>>, MySuperCoolEndpointType) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = MySuperCoolEndpointReturn> + Send + 'static,
+    {
+        self.server
+            .register_handler::<H, (Option<Vec<String>>, MySuperCoolEndpointType)>(handler, \"ABC123\");
+        self.server.set_endpoint_schema(
+            \"ABC123\",
+            http_server::serde_json::from_str(r#\"{\"parameters\":[{\"name\":\"p1\",\"optional\":true,\"schema\":{\"items\":{\"kind\":\"string\"},\"kind\":\"array\"}},{\"name\":\"type\",\"optional\":false,\"schema\":{\"kind\":\"union\",\"variants\":[{\"kind\":\"integer\",\"max\":127,\"min\":-128},{\"kind\":\"literal\",\"value\":\"none\"}]}}],\"types\":{}}\"#).expect(\"the generated schema is valid\"),
+        );
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_stream_foreign() {
+        let mut ep = endpoint();
+        ep.streaming = true;
+
+        let result = endpoint_to_function(&ep, true, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    /**some docs*/
+    pub fn MySuperCoolEndpoint(&self, p1: Option<Vec<String>>, r#type: MySuperCoolEndpointType) -> Result<http_server::erpc::stream::TypedStream<MySuperCoolEndpointReturn>, http_server::erpc::protocol::SendableError> {
+        let items = self.target.call_stream(http_server::erpc::protocol::Request {
+            identifier: \"ABC123\".to_string(),
+            parameters: vec![http_server::serde_json::to_value(p1)?, http_server::serde_json::to_value(r#type)?].into(),
+        })?;
+
+        Ok(items.typed())
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_stream_callback() {
+        let mut ep = endpoint();
+        ep.streaming = true;
+
+        let result = endpoint_to_function(&ep, false, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    /**some docs*/
+    pub fn MySuperCoolEndpoint<H, F, S>(&mut self, handler: H)
+    where
+        H: Fn(Option<Vec<String>>, MySuperCoolEndpointType) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = S> + Send + 'static,
+        S: http_server::erpc::stream::Stream<Item = MySuperCoolEndpointReturn> + Send + 'static,
+    {
+        self.server
+            .register_stream_handler::<H, (Option<Vec<String>>, MySuperCoolEndpointType), MySuperCoolEndpointReturn>(handler, \"ABC123\");
+        self.server.set_endpoint_schema(
+            \"ABC123\",
+            http_server::serde_json::from_str(r#\"{\"parameters\":[{\"name\":\"p1\",\"optional\":true,\"schema\":{\"items\":{\"kind\":\"string\"},\"kind\":\"array\"}},{\"name\":\"type\",\"optional\":false,\"schema\":{\"kind\":\"union\",\"variants\":[{\"kind\":\"integer\",\"max\":127,\"min\":-128},{\"kind\":\"literal\",\"value\":\"none\"}]}}],\"types\":{}}\"#).expect(\"the generated schema is valid\"),
+        );
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_type_definitions() {
+        let result = endpoint_type_definitions(&endpoint());
+        assert_eq!(
+            result,
+            "#[derive(Debug, Clone, http_server::serde::Serialize, http_server::serde::Deserialize)]
+#[serde(crate = \"http_server::serde\")]
+#[serde(untagged)]
+pub enum MySuperCoolEndpointType {
+    Int8(i8),
+    String(String),
+}
+#[derive(Debug, Clone, PartialEq, http_server::serde::Serialize, http_server::serde::Deserialize)]
+#[serde(crate = \"http_server::serde\")]
+pub enum MySuperCoolEndpointReturn {
+    #[serde(rename = \"success\")]
+    Success,
+    #[serde(rename = \"not found\")]
+    NotFound,
+}
+"
+        );
+    }
+
+    fn throwing_endpoint() -> Endpoint {
+        Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "login".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "Session".to_string(),
+            })),
+            streaming: false,
+            named_parameters: false,
+            error_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "LoginError".to_string(),
+            })),
+            parameters: vec![Parameter {
+                identifier: "user".to_string(),
+                optional: false,
+                parameter_type: Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                }),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_error_type_foreign() {
+        let result = endpoint_to_function(&throwing_endpoint(), true, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    pub async fn login(&self, user: String) -> Result<Result<Session, LoginError>, http_server::erpc::protocol::SendableError> {
+        let response = self
+            .target
+            .call(http_server::erpc::protocol::Request {
+                identifier: \"ABC123\".to_string(),
+                parameters: vec![http_server::serde_json::to_value(user)?].into(),
+            })
+            .await;
+
+        match response.body {
+            Err(http_server::erpc::protocol::SendableError::Application { error }) => {
+                Ok(Err(http_server::serde_json::from_value(error)?))
+            }
+            body => Ok(Ok(http_server::serde_json::from_value(body?)?)),
+        }
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_error_type_callback() {
+        let result = endpoint_to_function(&throwing_endpoint(), false, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    pub fn login<H, F>(&mut self, handler: H)
+    where
+        H: Fn(String) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = Result<Session, LoginError>> + Send + 'static,
+    {
+        self.server
+            .register_fallible_handler::<H, (String,), Session, LoginError>(handler, \"ABC123\");
+        self.server.set_endpoint_schema(
+            \"ABC123\",
+            http_server::serde_json::from_str(r#\"{\"parameters\":[{\"name\":\"user\",\"optional\":false,\"schema\":{\"kind\":\"string\"}}],\"types\":{}}\"#).expect(\"the generated schema is valid\"),
+        );
+    }
+
+"
+        );
+    }
+
+    fn named_endpoint() -> Endpoint {
+        Endpoint {
+            named_parameters: true,
+            ..throwing_endpoint()
+        }
+    }
+
+    #[test]
+    fn test_named_parameters_type_definitions() {
+        let mut endpoint = named_endpoint();
+        endpoint.parameters.push(Parameter {
+            identifier: "type".to_string(),
+            optional: true,
+            parameter_type: Type::Primitive(Primitive {
+                array_amount: ArrayAmount::NoArray,
+                primitive_type: PrimitiveType::Boolean,
+            }),
+        });
+
+        let result = endpoint_type_definitions(&endpoint);
+        assert_eq!(
+            result,
+            "#[derive(Debug, Clone, http_server::serde::Serialize, http_server::serde::Deserialize)]
+#[serde(crate = \"http_server::serde\")]
+pub struct LoginParameters {
+    pub user: String,
+    #[serde(default, skip_serializing_if = \"Option::is_none\")]
+    pub r#type: Option<bool>,
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_named_parameters_foreign() {
+        let result = endpoint_to_function(&named_endpoint(), true, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    pub async fn login(&self, parameters: LoginParameters) -> Result<Result<Session, LoginError>, http_server::erpc::protocol::SendableError> {
+        let response = self
+            .target
+            .call(http_server::erpc::protocol::Request {
+                identifier: \"ABC123\".to_string(),
+                parameters: http_server::erpc::protocol::Parameters::named(&parameters)?,
+            })
+            .await;
+
+        match response.body {
+            Err(http_server::erpc::protocol::SendableError::Application { error }) => {
+                Ok(Err(http_server::serde_json::from_value(error)?))
+            }
+            body => Ok(Ok(http_server::serde_json::from_value(body?)?)),
+        }
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_named_parameters_callback() {
+        let result = endpoint_to_function(&named_endpoint(), false, "ABC123", &[]);
+        assert_eq!(
+            result,
+            "    pub fn login<H, F>(&mut self, handler: H)
+    where
+        H: Fn(LoginParameters) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = Result<Session, LoginError>> + Send + 'static,
+    {
+        self.server
+            .register_fallible_handler::<H, (LoginParameters,), Session, LoginError>(handler, \"ABC123\");
+        self.server.set_endpoint_schema(
+            \"ABC123\",
+            http_server::serde_json::from_str(r#\"{\"named\":true,\"parameters\":[{\"name\":\"user\",\"optional\":false,\"schema\":{\"kind\":\"string\"}}],\"types\":{}}\"#).expect(\"the generated schema is valid\"),
+        );
+    }
+
+"
+        );
+    }
+
+    #[test]
+    fn test_array_length_schema() {
+        let mut ep = endpoint();
+        ep.parameters[0].parameter_type = Type::Primitive(Primitive {
+            array_amount: ArrayAmount::LengthSpecified(3),
+            primitive_type: PrimitiveType::String,
+        });
+
+        // the handler gets a Vec, so only the schema checks the length
+        let result = endpoint_to_function(&ep, false, "ABC123", &[]);
+        assert!(result.contains("Fn(Option<Vec<String>>, MySuperCoolEndpointType)"));
+        assert!(result.contains(r#""kind":"array","length":3"#));
+    }
+
+    #[test]
+    fn test_raw_string_schema() {
+        let mut ep = endpoint();
+        ep.parameters[1].parameter_type = Type::Enum(Enum {
+            values: vec![EnumType::Literal(LiteralType::String("\"#".to_string()))],
+        });
+
+        // the literal would end a raw string with a single hash
+        let result = endpoint_to_function(&ep, false, "ABC123", &[]);
+        assert!(result.contains("http_server::serde_json::from_str(r##\""));
+        assert!(result.contains("}\"##).expect"));
+    }
+
+    #[test]
+    fn test_middleware_callback() {
+        let mut ep = throwing_endpoint();
+        ep.error_type = None;
+        ep.middleware_identifiers = vec!["auth".to_string()];
+
+        let result = endpoint_to_function(&ep, false, "api/login", &[]);
+
+        assert_eq!(
+            result,
+            "    pub fn login<H, F>(&mut self, handler: H)
+    where
+        H: Fn(String) -> F + Send + Sync + Clone + 'static,
+        F: std::future::Future<Output = Session> + Send + 'static,
+    {
+        self.server
+            .register_handler::<H, (String,)>(handler, \"api/login\");
+        self.server.set_endpoint_schema(
+            \"api/login\",
+            http_server::serde_json::from_str(r#\"{\"parameters\":[{\"name\":\"user\",\"optional\":false,\"schema\":{\"kind\":\"string\"}}],\"types\":{}}\"#).expect(\"the generated schema is valid\"),
+        );
+        self.server.set_endpoint_middleware(\"api/login\", vec![\"auth\".to_string()]);
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_middleware_function() {
+        let result = middleware_to_function(&throwing_endpoint());
+
+        assert_eq!(
+            result,
+            "    pub fn login<M, F>(&mut self, middleware: M)
+    where
+        M: Fn(http_server::MiddlewareCall) -> F + Send + Sync + 'static,
+        F: std::future::Future<Output = Result<http_server::MiddlewareCall, http_server::erpc::protocol::SendableError>> + Send + 'static,
+    {
+        self.server.register_middleware(middleware, \"login\");
+    }
+
+"
+        )
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Range;
+
+    use crate::transpiler::{
+        generator::translator::rust::structure::custom_type_to_struct,
+        parser::{
+            lexer::literal::LiteralType,
+            parser::{
+                custom_type::{CustomType, Field},
+                erpc_type::{ArrayAmount, Custom, Enum, EnumType, Primitive, PrimitiveType, Type},
+            },
+        },
+    };
+
+    #[test]
+    fn test_success() {
+        let result = custom_type_to_struct(&CustomType {
+            documentation: Some("some docs".to_string()),
+            range: Range::default(),
+            identifier: "User".to_string(),
+            fields: vec![
+                Field {
+                    documentation: Some("field docs".to_string()),
+                    identifier: "name".to_string(),
+                    optional: false,
+                    field_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::NoArray,
+                        primitive_type: PrimitiveType::String,
+                    }),
+                },
+                Field {
+                    documentation: None,
+                    identifier: "friend".to_string(),
+                    optional: true,
+                    field_type: Type::Custom(Custom {
+                        array_amount: ArrayAmount::NoArray,
+                        identifier: "User".to_string(),
+                    }),
+                },
+                Field {
+                    documentation: None,
+                    identifier: "scores".to_string(),
+                    optional: false,
+                    field_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::LengthSpecified(3),
+                        primitive_type: PrimitiveType::Float64,
+                    }),
+                },
+                Field {
+                    documentation: None,
+                    identifier: "state".to_string(),
+                    optional: false,
+                    field_type: Type::Enum(Enum {
+                        values: vec![
+                            EnumType::Literal(LiteralType::String("active".to_string())),
+                            EnumType::Literal(LiteralType::String("42".to_string())),
+                        ],
+                    }),
+                },
+            ],
+        });
+        assert_eq!(
+            result,
+            "/**some docs*/
+#[derive(Debug, Clone, http_server::serde::Serialize, http_server::serde::Deserialize)]
+#[serde(crate = \"http_server::serde\")]
+pub struct User {
+    /**field docs*/
+    pub name: String,
+    #[serde(default, skip_serializing_if = \"Option::is_none\")]
+    pub friend: Option<Box<User>>,
+    pub scores: Vec<f64>,
+    pub state: UserState,
+}
+#[derive(Debug, Clone, PartialEq, http_server::serde::Serialize, http_server::serde::Deserialize)]
+#[serde(crate = \"http_server::serde\")]
+pub enum UserState {
+    #[serde(rename = \"active\")]
+    Active,
+    #[serde(rename = \"42\")]
+    Value42,
+}
+"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use serde_json::{json, Map, Value};
+
+use crate::transpiler::parser::{
+    lexer::literal::LiteralType,
+    parser::{
+        custom_type::CustomType,
+        endpoint::Endpoint,
+        erpc_type::{ArrayAmount, EnumType, PrimitiveType, Type},
+    },
+};
+
+/**
+   Builds the schema the runtime checks the parameters of calls against before passing them to a handler.
+   All custom types the parameters use, directly or through other custom types, are added to the types of the schema.
+*/
+pub fn endpoint_schema(endpoint: &Endpoint, custom_types: &[CustomType]) -> Value {
+    // custom types which are referenced but not yet added to the types
+    let mut required = Vec::<String>::new();
+
+    let parameters: Vec<Value> = endpoint
+        .parameters
+        .iter()
+        .map(|parameter| {
+            field_schema(
+                &parameter.identifier,
+                parameter.optional,
+                &parameter.parameter_type,
+                &mut required,
+            )
+        })
+        .collect();
+
+    let types = custom_types_schema(required, custom_types);
+
+    let mut schema = json!({
+        "parameters": parameters,
+        "types": types,
+    });
+    if endpoint.named_parameters {
+        schema["named"] = Value::Bool(true);
+    }
+    schema
+}
+
+/**
+   Describes everything about the endpoint a caller relies on, unlike the schema this includes the return and error type.
+   Documentation and formatting of the source are left out, so only changes which affect callers change the signature.
+*/
+pub fn endpoint_signature(
+    identifier: &str,
+    endpoint: &Endpoint,
+    custom_types: &[CustomType],
+) -> Value {
+    let mut signature = endpoint_schema(endpoint, custom_types);
+    let mut required = Vec::<String>::new();
+
+    signature["identifier"] = json!(identifier);
+    signature["streaming"] = json!(endpoint.streaming);
+    signature["returns"] = match &endpoint.return_type {
+        Some(v) => type_schema(v, &mut required),
+        None => Value::Null,
+    };
+    signature["errors"] = match &endpoint.error_type {
+        Some(v) => type_schema(v, &mut required),
+        None => Value::Null,
+    };
+
+    // the custom types of the return and error type are added to the ones of the parameters
+    if let Value::Object(types) = &mut signature["types"] {
+        types.extend(custom_types_schema(required, custom_types));
+    }
+    signature
+}
+
+/**
+   Hashes the signatures of the endpoints of a role. The order of the signatures does not matter,
+   so moving endpoints between or within sources keeps the hash as long as their identifiers stay the same
+*/
+pub fn schema_hash(signatures: &[String]) -> String {
+    let mut signatures = signatures.iter().collect::<Vec<_>>();
+    signatures.sort();
+
+    // FNV-1a, the hashers of the standard library may change between rust versions
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for signature in signatures {
+        for byte in signature.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/**
+   The fields of the required custom types and of all custom types they use in turn, by the identifier of the type
+*/
+fn custom_types_schema(
+    mut required: Vec<String>,
+    custom_types: &[CustomType],
+) -> Map<String, Value> {
+    let mut types = Map::new();
+    while let Some(identifier) = required.pop() {
+        if types.contains_key(&identifier) {
+            continue;
+        }
+
+        // the validator makes sure all referenced types exist
+        let custom_type = match custom_types.iter().find(|t| t.identifier == identifier) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let fields: Vec<Value> = custom_type
+            .fields
+            .iter()
+            .map(|field| {
+                field_schema(
+                    &field.identifier,
+                    field.optional,
+                    &field.field_type,
+                    &mut required,
+                )
+            })
+            .collect();
+        types.insert(identifier, Value::Array(fields));
+    }
+    types
+}
+
+fn field_schema(
+    name: &str,
+    optional: bool,
+    field_type: &Type,
+    required: &mut Vec<String>,
+) -> Value {
+    json!({
+        "name": name,
+        "optional": optional,
+        "schema": type_schema(field_type, required),
+    })
+}
+
+fn type_schema(field_type: &Type, required: &mut Vec<String>) -> Value {
+    match field_type {
+        Type::Primitive(primitive) => with_array(
+            primitive_schema(&primitive.primitive_type),
+            &primitive.array_amount,
+        ),
+        Type::Enum(en) => {
+            let variants: Vec<Value> = en
+                .values
+                .iter()
+                .map(|value| match value {
+                    EnumType::Primitive(primitive) => with_array(
+                        primitive_schema(&primitive.primitive_type),
+                        &primitive.array_amount,
+                    ),
+                    EnumType::Custom(custom) => {
+                        required.push(custom.identifier.to_owned());
+                        with_array(custom_schema(&custom.identifier), &custom.array_amount)
+                    }
+                    EnumType::Literal(literal) => json!({
+                        "kind": "literal",
+                        "value": literal_value(literal),
+                    }),
+                })
+                .collect();
+
+            json!({
+                "kind": "union",
+                "variants": variants,
+            })
+        }
+        Type::Custom(custom) => {
+            required.push(custom.identifier.to_owned());
+            with_array(custom_schema(&custom.identifier), &custom.array_amount)
+        }
+    }
+}
+
+fn custom_schema(identifier: &str) -> Value {
+    json!({
+        "kind": "custom",
+        "name": identifier,
+    })
+}
+
+fn primitive_schema(primitive_type: &PrimitiveType) -> Value {
+    match primitive_type {
+        PrimitiveType::Boolean => json!({ "kind": "boolean" }),
+        PrimitiveType::Int8 => integer_schema(i8::MIN.into(), i8::MAX.into()),
+        PrimitiveType::Int16 => integer_schema(i16::MIN.into(), i16::MAX.into()),
+        PrimitiveType::Int32 => integer_schema(i32::MIN.into(), i32::MAX.into()),
+        PrimitiveType::Int64 => integer_schema(i64::MIN, i64::MAX),
+        PrimitiveType::Float32 => json!({ "kind": "float" }),
+        PrimitiveType::Float64 => json!({ "kind": "float" }),
+        PrimitiveType::String => json!({ "kind": "string" }),
+    }
+}
+
+fn integer_schema(min: i64, max: i64) -> Value {
+    json!({
+        "kind": "integer",
+        "min": min,
+        "max": max,
+    })
+}
+
+fn with_array(schema: Value, array_amount: &ArrayAmount) -> Value {
+    match array_amount {
+        ArrayAmount::NoArray => schema,
+        ArrayAmount::NoLengthSpecified => json!({
+            "kind": "array",
+            "items": schema,
+        }),
+        ArrayAmount::LengthSpecified(length) => json!({
+            "kind": "array",
+            "items": schema,
+            "length": length,
+        }),
+    }
+}
+
+fn literal_value(literal: &LiteralType) -> Value {
+    match literal {
+        LiteralType::Boolean(val) => json!(val),
+        LiteralType::String(val) => json!(val),
+        // converting through the string keeps the value as written, e.g. 0.1 instead of 0.10000000149011612
+        LiteralType::Float(val) => match val.to_string().parse::<f64>() {
+            Ok(v) => json!(v),
+            Err(_) => Value::Null,
+        },
+        LiteralType::Integer(val) => json!(val),
+    }
+}
@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tower_lsp::lsp_types::Range;
+
+    use crate::transpiler::{
+        generator::translator::schema::{endpoint_schema, endpoint_signature, schema_hash},
+        parser::{
+            lexer::literal::LiteralType,
+            parser::{
+                custom_type::{CustomType, Field},
+                endpoint::{Endpoint, Parameter},
+                erpc_type::{ArrayAmount, Custom, Enum, EnumType, Primitive, PrimitiveType, Type},
+            },
+        },
+    };
+
+    #[test]
+    fn test_endpoint_schema() {
+        let endpoint = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "move".to_string(),
+            role: "Server".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![
+                Parameter {
+                    identifier: "position".to_string(),
+                    optional: false,
+                    parameter_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::LengthSpecified(2),
+                        primitive_type: PrimitiveType::Int32,
+                    }),
+                },
+                Parameter {
+                    identifier: "mode".to_string(),
+                    optional: true,
+                    parameter_type: Type::Enum(Enum {
+                        values: vec![
+                            EnumType::Literal(LiteralType::String("walk".to_string())),
+                            EnumType::Literal(LiteralType::Float(0.1)),
+                            EnumType::Primitive(Primitive {
+                                array_amount: ArrayAmount::NoArray,
+                                primitive_type: PrimitiveType::Boolean,
+                            }),
+                        ],
+                    }),
+                },
+                Parameter {
+                    identifier: "path".to_string(),
+                    optional: false,
+                    parameter_type: Type::Custom(Custom {
+                        array_amount: ArrayAmount::NoArray,
+                        identifier: "Node".to_string(),
+                    }),
+                },
+            ],
+        };
+
+        // Node references itself and Unused is not referenced at all
+        let custom_types = vec![
+            CustomType {
+                range: Range::default(),
+                documentation: None,
+                identifier: "Node".to_string(),
+                fields: vec![
+                    Field {
+                        optional: false,
+                        identifier: "name".to_string(),
+                        field_type: Type::Primitive(Primitive {
+                            array_amount: ArrayAmount::NoArray,
+                            primitive_type: PrimitiveType::String,
+                        }),
+                        documentation: None,
+                    },
+                    Field {
+                        optional: true,
+                        identifier: "next".to_string(),
+                        field_type: Type::Custom(Custom {
+                            array_amount: ArrayAmount::NoLengthSpecified,
+                            identifier: "Node".to_string(),
+                        }),
+                        documentation: None,
+                    },
+                ],
+            },
+            CustomType {
+                range: Range::default(),
+                documentation: None,
+                identifier: "Unused".to_string(),
+                fields: vec![],
+            },
+        ];
+
+        assert_eq!(
+            endpoint_schema(&endpoint, &custom_types),
+            json!({
+                "parameters": [
+                    {
+                        "name": "position",
+                        "optional": false,
+                        "schema": {
+                            "kind": "array",
+                            "items": { "kind": "integer", "min": -2147483648i64, "max": 2147483647 },
+                            "length": 2
+                        }
+                    },
+                    {
+                        "name": "mode",
+                        "optional": true,
+                        "schema": {
+                            "kind": "union",
+                            "variants": [
+                                { "kind": "literal", "value": "walk" },
+                                { "kind": "literal", "value": 0.1 },
+                                { "kind": "boolean" }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "path",
+                        "optional": false,
+                        "schema": { "kind": "custom", "name": "Node" }
+                    }
+                ],
+                "types": {
+                    "Node": [
+                        { "name": "name", "optional": false, "schema": { "kind": "string" } },
+                        {
+                            "name": "next",
+                            "optional": true,
+                            "schema": {
+                                "kind": "array",
+                                "items": { "kind": "custom", "name": "Node" }
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_named_endpoint_schema() {
+        let endpoint = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "login".to_string(),
+            role: "Server".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: true,
+            error_type: None,
+            parameters: vec![Parameter {
+                identifier: "user".to_string(),
+                optional: false,
+                parameter_type: Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                }),
+            }],
+        };
+
+        assert_eq!(
+            endpoint_schema(&endpoint, &vec![]),
+            json!({
+                "parameters": [
+                    { "name": "user", "optional": false, "schema": { "kind": "string" } }
+                ],
+                "types": {},
+                "named": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_hash() {
+        let endpoint = |documentation: Option<&str>, return_type: PrimitiveType| Endpoint {
+            middleware_identifiers: vec![],
+            documentation: documentation.map(|v| v.to_string()),
+            range: Range::default(),
+            identifier: "count".to_string(),
+            role: "Server".to_string(),
+            return_type: Some(Type::Primitive(Primitive {
+                array_amount: ArrayAmount::NoArray,
+                primitive_type: return_type,
+            })),
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![],
+        };
+        let signature = |identifier: &str, endpoint: &Endpoint| {
+            endpoint_signature(identifier, endpoint, &vec![]).to_string()
+        };
+
+        let count = signature("api/count", &endpoint(None, PrimitiveType::Int32));
+        let other = signature("api/other", &endpoint(None, PrimitiveType::Int32));
+        let hash = schema_hash(&vec![count.clone(), other.clone()]);
+        assert_eq!(hash.len(), 16);
+
+        // the order of the endpoints and their documentation don't affect callers
+        assert_eq!(schema_hash(&vec![other.clone(), count.clone()]), hash);
+        assert_eq!(
+            schema_hash(&vec![
+                signature("api/count", &endpoint(Some("Counts"), PrimitiveType::Int32)),
+                other.clone()
+            ]),
+            hash
+        );
+
+        // the return type does
+        assert_ne!(
+            schema_hash(&vec![
+                signature("api/count", &endpoint(None, PrimitiveType::Int64)),
+                other.clone()
+            ]),
+            hash
+        );
+        assert_ne!(schema_hash(&vec![count]), hash);
+    }
+}
@@ -1,7 +1,15 @@
-use crate::transpiler::parser::parser::{custom_type::CustomType, endpoint::Endpoint};
+use crate::transpiler::{
+    generator::translator::is_middleware_class,
+    parser::parser::{custom_type::CustomType, endpoint::Endpoint},
+};
 
 use super::{
-    endpoint::endpoint_to_function, interface::custom_type_to_interface, stringify_field_type,
+    endpoint::{
+        endpoint_to_function, middleware_to_function, stringify_parameters,
+        MIDDLEWARE_FUNCTION_TYPE,
+    },
+    interface::custom_type_to_interface,
+    stringify_endpoint_function_return_type,
 };
 
 pub fn generate_class(
@@ -50,6 +58,29 @@ fn generate_callback_class(
         ret.push_str("\n");
     }
 
+    // endpoints of the middleware source file are registered as middleware
+    let middleware = is_middleware_class(class_name, relative_path);
+    if middleware {
+        ret.push_str(
+            "export interface ERPCMiddlewareCall {
+    identifier: string
+    /** An object keyed by parameter name for endpoints with named parameters */
+    parameters: any[] | Record<string, any>
+    context: Record<string, any>
+    /** Who made the call, auth is the value the authenticator returned */
+    caller: {
+        headers: Record<string, string>
+        remoteAddress?: string
+        socketId?: string
+        role?: string
+        auth: any
+    }
+}
+
+",
+        );
+    }
+
     ret.push_str(&format!("export default class {class_name} {{
     private server: any
     /**
@@ -74,34 +105,19 @@ fn generate_callback_class(
     ret.push_str("    }\n\n    constructor(callbacks?: {\n");
 
     for endpoint in endpoints {
-        ret.push_str(&format!("        {}: (", endpoint.identifier));
-
-        for i in 0..endpoint.parameters.len() {
-            ret.push_str(&endpoint.parameters[i].identifier);
-
-            if endpoint.parameters[i].optional {
-                ret.push_str("?");
-            }
-
-            ret.push_str(": ");
-            ret.push_str(&stringify_field_type(
-                &endpoint.parameters[i].parameter_type,
+        if middleware {
+            ret.push_str(&format!(
+                "        {}: {MIDDLEWARE_FUNCTION_TYPE}\n",
+                endpoint.identifier
             ));
-
-            if i < endpoint.parameters.len() - 1 {
-                ret.push_str(", ");
-            }
+            continue;
         }
 
-        ret.push_str(") => Promise<");
-        if endpoint.return_type.is_some() {
-            ret.push_str(&stringify_field_type(
-                endpoint.return_type.as_ref().unwrap(),
-            ));
-        } else {
-            ret.push_str("void");
-        }
-        ret.push_str(">\n");
+        ret.push_str(&format!("        {}: (", endpoint.identifier));
+        ret.push_str(&stringify_parameters(endpoint));
+        ret.push_str(") => ");
+        ret.push_str(&stringify_endpoint_function_return_type(endpoint));
+        ret.push('\n');
     }
 
     for imp in class_imports {
@@ -131,10 +147,16 @@ fn generate_callback_class(
     ret.push_str("    }\n\n");
 
     for endpoint in endpoints {
+        if middleware {
+            ret.push_str(&middleware_to_function(endpoint));
+            continue;
+        }
+
         ret.push_str(&endpoint_to_function(
             endpoint,
             false,
             &format!("{relative_path}{class_name}/{}", endpoint.identifier),
+            custom_types,
         ));
     }
 
@@ -167,7 +189,6 @@ fn generate_foreign_class(
     let mut ret = String::new();
 
     for imp in class_imports {
-        
         ret.push_str(&format!("import {imp} from \"./{class_name}/{imp}\"\n"));
     }
     ret.push_str("\n");
@@ -199,6 +220,7 @@ fn generate_foreign_class(
             endpoint,
             true,
             &format!("{}{}/{}", relative_path, class_name, endpoint.identifier),
+            custom_types,
         ))
     }
 
@@ -6,6 +6,7 @@ pub fn generate_client(
     role: &Role,
     socket_enabled_browser_roles: &Vec<String>,
     library_source: &str,
+    schema_hash: &str,
 ) -> String {
     if foreign {
         generate_target(
@@ -13,6 +14,7 @@ pub fn generate_client(
             role,
             socket_enabled_browser_roles,
             library_source,
+            schema_hash,
         )
     } else {
         generate_server(
@@ -20,6 +22,7 @@ pub fn generate_client(
             role,
             socket_enabled_browser_roles,
             library_source,
+            schema_hash,
         )
     }
 }
@@ -29,12 +32,29 @@ fn generate_server(
     role: &Role,
     socket_enabled_browser_roles: &Vec<String>,
     library_source: &str,
+    schema_hash: &str,
 ) -> String {
     let mut ret = String::new();
 
-    ret.push_str(&format!(
-        "import {{ ERPCServer, ServerOptions }} from \"{library_source}\"\n"
-    ));
+    // websockets can be enabled in two cases:
+    // 1: the client belongs to a server and there is at least one browser which has endpoints (socket_enabled_browser_roles.len() > 0)
+    // 2: the client belongs to a browser and the role has endpoints (socket_enabled_browser_roles.contains(&role.name))
+    let enable_websockets = (!socket_enabled_browser_roles.is_empty()
+        && role.role_type.contains(&"http-server".to_string()))
+        || (socket_enabled_browser_roles.contains(&role.name)
+            && role.role_type.contains(&"browser".to_string()));
+    // servers can call the browsers which connected to them, one at a time or all at once
+    let accepts_connections = enable_websockets && !role.role_type.contains(&"browser".to_string());
+
+    if accepts_connections {
+        ret.push_str(&format!(
+            "import {{ ERPCError, ERPCServer, ServerOptions }} from \"{library_source}\"\n"
+        ));
+    } else {
+        ret.push_str(&format!(
+            "import {{ ERPCServer, ServerOptions }} from \"{library_source}\"\n"
+        ));
+    }
 
     for browser_role in socket_enabled_browser_roles {
         if browser_role != &role.name {
@@ -44,14 +64,6 @@ fn generate_server(
         }
     }
 
-    // websockets can be enabled in two cases:
-    // 1: the client belongs to a server and there is at least one browser which has endpoints (socket_enabled_browser_roles.len() > 0)
-    // 2: the client belongs to a browser and the role has endpoints (socket_enabled_browser_roles.contains(&role.name))
-    let enable_websockets = (!socket_enabled_browser_roles.is_empty()
-        && role.role_type.contains(&"http-server".to_string()))
-        || (socket_enabled_browser_roles.contains(&role.name)
-            && role.role_type.contains(&"browser".to_string()));
-
     for imp in class_imports {
         ret.push_str(&format!(
             "import {imp} from \"./{rolename}/{imp}\"\n",
@@ -60,6 +72,21 @@ fn generate_server(
     }
     ret.push_str("\n");
 
+    if accepts_connections {
+        ret.push_str(
+            "/**
+    The endpoints of a browser role called on many sockets at once, calls resolve to the result of each socket by its id
+*/
+type Broadcast<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => Promise<infer R>
+        ? (...args: A) => Promise<Record<string, { ok: true, value: R } | { ok: false, error: ERPCError }>>
+        : T[K] extends object ? Broadcast<T[K]> : never
+}
+
+",
+        );
+    }
+
     match &role.documentation {
         Some(doc) => {
             ret.push_str(&format!("/**{doc}*/\n"));
@@ -101,9 +128,14 @@ fn generate_server(
     ret.push_str(&format!("    }}) {{\n        super(options, \"{}\"", role.role_type));
 
     ret.push_str(&format!(
-        ", {enable_websockets}, \"{role_name}\")\n",
+        ", {enable_websockets}, \"{role_name}\"",
         role_name = role.name
     ));
+    // browsers don't accept calls from clients, so only http servers compare the schema hash
+    if role.role_type.contains(&"http-server".to_string()) {
+        ret.push_str(&format!(", \"{schema_hash}\""));
+    }
+    ret.push_str(")\n");
 
     for imp in class_imports {
         ret.push_str(&format!(
@@ -114,7 +146,7 @@ fn generate_server(
     ret.push_str("    }\n");
 
     // browsers are not able to accept web socket connections, therefore we dont need to add the onConnection method to ws enabled browsers
-    if enable_websockets && !role.role_type.contains(&"browser".to_string()) {
+    if accepts_connections {
         ret.push_str("\n    onConnection(callback: (target: ");
         for i in 0..socket_enabled_browser_roles.len() {
             ret.push_str(&socket_enabled_browser_roles[i]);
@@ -134,6 +166,29 @@ fn generate_server(
             ));
         }
         ret.push_str("\n        })\n    }");
+
+        for role in socket_enabled_browser_roles {
+            ret.push_str(&format!(
+                "
+
+    /**
+        Calls the connected {role} browsers at once, e.g. {role}.all or {role}.only(socketIds)
+    */
+    get {role}() {{
+        const broadcast = (socketIds?: string[]) => {{
+            const ret = new {role}({{address: \"\"}})
+            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+            // @ts-ignore
+            ret.setERPCBroadcast(this, \"{role}\", socketIds)
+            return ret as unknown as Broadcast<{role}>
+        }}
+        return {{
+            all: broadcast(),
+            only: (socketIds: string[]) => broadcast(socketIds),
+        }}
+    }}"
+            ));
+        }
     }
 
     ret.push_str("\n}");
@@ -146,6 +201,7 @@ fn generate_target(
     role: &Role,
     socket_enabled_browser_roles: &Vec<String>,
     library_source: &str,
+    schema_hash: &str,
 ) -> String {
     let mut ret = String::new();
 
@@ -196,7 +252,11 @@ fn generate_target(
     ret.push_str("        super(options, \"");
     ret.push_str(&role.role_type);
 
-    ret.push_str("\")\n    }\n}");
+    ret.push('"');
+    if role.role_type.contains(&"http-server".to_string()) {
+        ret.push_str(&format!(", \"{schema_hash}\""));
+    }
+    ret.push_str(")\n    }\n}");
 
     ret
 }
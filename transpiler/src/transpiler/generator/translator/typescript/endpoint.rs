@@ -1,18 +1,27 @@
-use crate::transpiler::parser::parser::endpoint::Endpoint;
+use crate::transpiler::{
+    generator::translator::schema::endpoint_schema,
+    parser::parser::{custom_type::CustomType, endpoint::Endpoint},
+};
 
-use super::stringify_field_type;
+use super::{stringify_endpoint_function_return_type, stringify_field_type};
 
 /**
    Translates an endpoint to a function for the target language.
    The foreign parameter indicates if the generated code should be for calling and endpoint
    on another machine or to provide logic for handling a call on this machine.
    The url must be a unique identifier for determining this endpoint.
+   The custom types are needed to generate the schema the parameters of incoming calls are checked against.
 */
-pub fn endpoint_to_function(endpoint: &Endpoint, foreign: bool, url: &str) -> String {
+pub fn endpoint_to_function(
+    endpoint: &Endpoint,
+    foreign: bool,
+    url: &str,
+    custom_types: &[CustomType],
+) -> String {
     if foreign {
         make_foreign_endpoint(endpoint, url)
     } else {
-        make_callback_endpoint(endpoint, url)
+        make_callback_endpoint(endpoint, url, custom_types)
     }
 }
 
@@ -30,40 +39,27 @@ fn make_foreign_endpoint(endpoint: &Endpoint, url: &str) -> String {
         "    {identifier}(",
         identifier = &endpoint.identifier
     ));
+    ret.push_str(&stringify_parameters(endpoint));
+    ret.push_str("): ");
+    ret.push_str(&stringify_endpoint_function_return_type(endpoint));
 
-    for i in 0..endpoint.parameters.len() {
-        ret.push_str(&endpoint.parameters[i].identifier);
-
-        if endpoint.parameters[i].optional {
-            ret.push_str("?");
-        }
-
-        ret.push_str(": ");
-        ret.push_str(&stringify_field_type(
-            &endpoint.parameters[i].parameter_type,
-        ));
-
-        if i < endpoint.parameters.len() - 1 {
-            ret.push_str(", ");
-        }
-    }
-
-    ret.push_str("): Promise<");
-
-    if endpoint.return_type.is_some() {
-        ret.push_str(&stringify_field_type(
-            endpoint.return_type.as_ref().unwrap(),
+    if endpoint.streaming {
+        // the runtime returns an async iterator, the wrapper makes it usable in for await loops
+        ret.push_str(&format!(
+            " {{
+        const stream = this.server.stream(\"{url}\""
         ));
     } else {
-        ret.push_str("void");
-    }
-
-    ret.push_str(&format!(
-        "> {{
+        ret.push_str(&format!(
+            " {{
         return this.server.call(\"{url}\""
-    ));
+        ));
+    }
 
-    if endpoint.parameters.len() > 0 {
+    if endpoint.named_parameters {
+        // named parameters are sent as the object the caller passed
+        ret.push_str(", parameters");
+    } else if !endpoint.parameters.is_empty() {
         ret.push_str(", [");
 
         for i in 0..endpoint.parameters.len() {
@@ -76,12 +72,24 @@ fn make_foreign_endpoint(endpoint: &Endpoint, url: &str) -> String {
         ret.push_str("]");
     }
 
-    ret.push_str(")\n    }\n\n");
+    // tells the runtime to resolve to a result object instead of throwing application errors
+    if endpoint.error_type.is_some() {
+        if endpoint.parameters.is_empty() {
+            ret.push_str(", []");
+        }
+        ret.push_str(", true");
+    }
+
+    ret.push_str(")\n");
+    if endpoint.streaming {
+        ret.push_str("        return { [Symbol.asyncIterator]: () => stream }\n");
+    }
+    ret.push_str("    }\n\n");
 
     ret
 }
 
-fn make_callback_endpoint(endpoint: &Endpoint, url: &str) -> String {
+fn make_callback_endpoint(endpoint: &Endpoint, url: &str, custom_types: &[CustomType]) -> String {
     let mut ret = String::new();
 
     if endpoint.documentation.is_some() {
@@ -93,49 +101,98 @@ fn make_callback_endpoint(endpoint: &Endpoint, url: &str) -> String {
 
     ret.push_str(&format!("    private _{}: (", endpoint.identifier));
 
-    let mut params_string = String::new();
-    for i in 0..endpoint.parameters.len() {
-        params_string.push_str(&endpoint.parameters[i].identifier);
+    let params_string = stringify_parameters(endpoint);
+    let return_type = stringify_endpoint_function_return_type(endpoint);
+    ret.push_str(&format!(
+        "{params_string}) => {return_type} = undefined as any\n    set {}(value: ({params_string}) => {return_type}",
+        endpoint.identifier
+    ));
 
-        if endpoint.parameters[i].optional {
-            params_string.push_str("?");
-        }
+    // handlers of endpoints with an error type return a result object, the runtime needs to know about that
+    let returns_result = endpoint.error_type.is_some();
 
-        params_string.push_str(": ");
-        params_string.push_str(&stringify_field_type(
-            &endpoint.parameters[i].parameter_type,
-        ));
+    // the runtime runs the declared middleware in order before the handler
+    let middleware = endpoint
+        .middleware_identifiers
+        .iter()
+        .map(|identifier| format!("\"{identifier}\""))
+        .collect::<Vec<String>>()
+        .join(", ");
 
-        if i < endpoint.parameters.len() - 1 {
-            params_string.push_str(", ");
-        }
-    }
+    // the runtime checks the parameters of calls against the schema before they reach the handler
+    let schema = endpoint_schema(endpoint, custom_types);
 
-    ret.push_str(&format!("{}) => Promise<", params_string));
-    if endpoint.return_type.is_some() {
-        ret.push_str(&stringify_field_type(
-            endpoint.return_type.as_ref().unwrap(),
-        ));
+    // streams are registered separately since the runtime pulls their items instead of awaiting a result
+    let register = if endpoint.streaming {
+        format!("registerERPCStreamHandler(value, \"{url}\", [{middleware}], {schema})")
     } else {
-        ret.push_str("void");
-    }
+        format!("registerERPCHandler(value, \"{url}\", {returns_result}, [{middleware}], {schema})")
+    };
 
     ret.push_str(&format!(
-        "> = undefined as any\n    set {}(value: ({}) => Promise<",
-        endpoint.identifier, params_string
+        ") {{
+        this._{id} = value
+        this.server?.{register}
+    }}
+    get {id}() {{
+        return this._{id}
+    }}
+
+",
+        id = endpoint.identifier
     ));
-    if endpoint.return_type.is_some() {
-        ret.push_str(&stringify_field_type(
-            endpoint.return_type.as_ref().unwrap(),
-        ));
+
+    ret
+}
+
+/**
+   The parameter list of the function of an endpoint.
+   Named parameters are taken as a single options object, e.g. (parameters: { user: string, password?: string })
+*/
+pub fn stringify_parameters(endpoint: &Endpoint) -> String {
+    let parameters = endpoint
+        .parameters
+        .iter()
+        .map(|parameter| {
+            format!(
+                "{}{}: {}",
+                parameter.identifier,
+                if parameter.optional { "?" } else { "" },
+                stringify_field_type(&parameter.parameter_type)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    if endpoint.named_parameters {
+        format!("parameters: {{ {parameters} }}")
     } else {
-        ret.push_str("void");
+        parameters
+    }
+}
+
+/**
+   The type of the functions implementing middleware. They get the call before it reaches the handler of the endpoint
+   and can return a changed call, returning nothing passes the call on unchanged and throwing rejects it.
+*/
+pub const MIDDLEWARE_FUNCTION_TYPE: &str =
+    "(call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>";
+
+/**
+   Translates an endpoint of the middleware source file to a setter which registers the middleware on the server
+*/
+pub fn middleware_to_function(endpoint: &Endpoint) -> String {
+    let mut ret = String::new();
+
+    if let Some(val) = &endpoint.documentation {
+        ret.push_str(&format!("/**{val}*/\n"));
     }
 
     ret.push_str(&format!(
-        ">) {{
+        "    private _{id}: {MIDDLEWARE_FUNCTION_TYPE} = undefined as any
+    set {id}(value: {MIDDLEWARE_FUNCTION_TYPE}) {{
         this._{id} = value
-        this.server?.registerERPCHandler(value, \"{url}\")
+        this.server?.registerERPCMiddleware(value, \"{id}\")
     }}
     get {id}() {{
         return this._{id}
@@ -8,6 +8,7 @@ use crate::transpiler::{
             erpc_type::{ArrayAmount, EnumType, Primitive, PrimitiveType, Type},
         },
     },
+    validator::ValidationError,
 };
 
 use self::{class::generate_class, client::generate_client};
@@ -45,12 +46,25 @@ impl Translator for TypeScriptTranslator {
         String::from("ts")
     }
 
+    fn library_source(role: &Role) -> String {
+        if role.role_type.contains(&"browser".to_string()) {
+            String::from("@easy-rpc/browser")
+        } else {
+            String::from("@easy-rpc/node") // currently only supports node
+        }
+    }
+
+    fn validate(_endpoints: &[Endpoint], _custom_types: &[CustomType]) -> Vec<ValidationError> {
+        vec![]
+    }
+
     fn generate_client(
         foreign: bool,
         class_imports: &Vec<String>,
         role: &Role,
         socket_enabled_browser_roles: &Vec<String>,
         library_source: &str,
+        schema_hash: &str,
     ) -> String {
         generate_client(
             foreign,
@@ -58,6 +72,7 @@ impl Translator for TypeScriptTranslator {
             role,
             socket_enabled_browser_roles,
             library_source,
+            schema_hash,
         )
     }
 }
@@ -111,6 +126,38 @@ fn stringify_field_type(field_type: &Type) -> String {
     }
 }
 
+/**
+   Stringifies the type an endpoint resolves to. Endpoints which declare an error type resolve to a result object.
+*/
+fn stringify_endpoint_return_type(endpoint: &Endpoint) -> String {
+    let return_type = match &endpoint.return_type {
+        Some(return_type) => stringify_field_type(return_type),
+        None => "void".to_string(),
+    };
+
+    match &endpoint.error_type {
+        Some(error_type) => format!(
+            "{{ ok: true, value: {return_type} }} | {{ ok: false, error: {} }}",
+            stringify_field_type(error_type)
+        ),
+        None => return_type,
+    }
+}
+
+/**
+   Stringifies what the function of an endpoint returns. Streaming endpoints return an async iterable of their items.
+*/
+fn stringify_endpoint_function_return_type(endpoint: &Endpoint) -> String {
+    if endpoint.streaming {
+        format!(
+            "AsyncIterable<{}>",
+            stringify_endpoint_return_type(endpoint)
+        )
+    } else {
+        format!("Promise<{}>", stringify_endpoint_return_type(endpoint))
+    }
+}
+
 fn stringify_primitive(primitive: &Primitive) -> String {
     let mut type_string = match primitive.primitive_type {
         PrimitiveType::Boolean => "boolean",
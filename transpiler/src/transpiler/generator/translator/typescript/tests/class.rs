@@ -24,6 +24,9 @@ mod tests {
                     array_amount: ArrayAmount::NoLengthSpecified,
                     primitive_type: PrimitiveType::String,
                 })),
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![
                     Parameter {
                         identifier: "p1".to_string(),
@@ -50,6 +53,9 @@ mod tests {
                 identifier: "MySuperCoolEndpoint2".to_string(),
                 role: "MyVeryNiceRole".to_string(),
                 return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![],
             },
         ];
@@ -134,7 +140,7 @@ export default class MyCoolClass {
     private _MySuperCoolEndpoint1: (p1?: string[], p2: number) => Promise<string[]> = undefined as any
     set MySuperCoolEndpoint1(value: (p1?: string[], p2: number) => Promise<string[]>) {
         this._MySuperCoolEndpoint1 = value
-        this.server?.registerERPCHandler(value, \"test/test2/MyCoolClass/MySuperCoolEndpoint1\")
+        this.server?.registerERPCHandler(value, \"test/test2/MyCoolClass/MySuperCoolEndpoint1\", false, [], {\"parameters\":[{\"name\":\"p1\",\"optional\":true,\"schema\":{\"items\":{\"kind\":\"string\"},\"kind\":\"array\"}},{\"name\":\"p2\",\"optional\":false,\"schema\":{\"kind\":\"integer\",\"max\":127,\"min\":-128}}],\"types\":{}})
     }
     get MySuperCoolEndpoint1() {
         return this._MySuperCoolEndpoint1
@@ -143,7 +149,7 @@ export default class MyCoolClass {
     private _MySuperCoolEndpoint2: () => Promise<void> = undefined as any
     set MySuperCoolEndpoint2(value: () => Promise<void>) {
         this._MySuperCoolEndpoint2 = value
-        this.server?.registerERPCHandler(value, \"test/test2/MyCoolClass/MySuperCoolEndpoint2\")
+        this.server?.registerERPCHandler(value, \"test/test2/MyCoolClass/MySuperCoolEndpoint2\", false, [], {\"parameters\":[],\"types\":{}})
     }
     get MySuperCoolEndpoint2() {
         return this._MySuperCoolEndpoint2
@@ -182,6 +188,9 @@ export default class MyCoolClass {
                     array_amount: ArrayAmount::NoLengthSpecified,
                     primitive_type: PrimitiveType::String,
                 })),
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![
                     Parameter {
                         identifier: "p1".to_string(),
@@ -208,6 +217,9 @@ export default class MyCoolClass {
                 identifier: "MySuperCoolEndpoint2".to_string(),
                 role: "MyVeryNiceRole".to_string(),
                 return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![],
             },
         ];
@@ -269,4 +281,76 @@ export default class MyCoolClass {
 }"
         );
     }
+
+    #[test]
+    fn test_middleware_class() {
+        let endpoints = vec![Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "auth".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![],
+        }];
+
+        let result = generate_class("middleware", "", &endpoints, false, &vec![], &vec![]);
+
+        assert_eq!(
+            result,
+            "
+export interface ERPCMiddlewareCall {
+    identifier: string
+    /** An object keyed by parameter name for endpoints with named parameters */
+    parameters: any[] | Record<string, any>
+    context: Record<string, any>
+    /** Who made the call, auth is the value the authenticator returned */
+    caller: {
+        headers: Record<string, string>
+        remoteAddress?: string
+        socketId?: string
+        role?: string
+        auth: any
+    }
+}
+
+export default class middleware {
+    private server: any
+    /**
+        This method is used by easy-rpc internally and is not intended for manual use. It can be used to set the server of the object.
+    */
+    private setERPCServer(server: any) {
+        this.server = server
+
+        // trigger the setters to set the handlers on the server object
+        if (this.auth) {
+            this.auth = this.auth
+        }
+    }
+
+    constructor(callbacks?: {
+        auth: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>
+    }) {
+        if (callbacks?.auth) {
+            this.auth = callbacks.auth
+        }
+
+    }
+
+    private _auth: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void> = undefined as any
+    set auth(value: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>) {
+        this._auth = value
+        this.server?.registerERPCMiddleware(value, \"auth\")
+    }
+    get auth() {
+        return this._auth
+    }
+
+
+}"
+        )
+    }
 }
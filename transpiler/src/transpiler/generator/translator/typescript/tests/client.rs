@@ -16,6 +16,7 @@ mod tests {
             },
             &vec!["Client".to_string()],
             "@easy-rpc/browser",
+            "3f9a0c1e5b7d2486",
         );
 
         assert_eq!(
@@ -50,15 +51,25 @@ export default class Client extends ERPCTarget {
             },
             &vec!["Client".to_string()],
             "@easy-rpc/node",
+            "3f9a0c1e5b7d2486",
         );
 
         assert_eq!(
             result,
-            "import { ERPCServer, ServerOptions } from \"@easy-rpc/node\"
+            "import { ERPCError, ERPCServer, ServerOptions } from \"@easy-rpc/node\"
 import Client from \"./Client\"
 import api from \"./Server/api\"
 import tracks from \"./Server/tracks\"
 
+/**
+    The endpoints of a browser role called on many sockets at once, calls resolve to the result of each socket by its id
+*/
+type Broadcast<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => Promise<infer R>
+        ? (...args: A) => Promise<Record<string, { ok: true, value: R } | { ok: false, error: ERPCError }>>
+        : T[K] extends object ? Broadcast<T[K]> : never
+}
+
 /**Example docs*/
 export default class Server extends ERPCServer {
     private _api = undefined as any
@@ -85,7 +96,7 @@ export default class Server extends ERPCServer {
         api: api
         tracks: tracks
     }) {
-        super(options, \"http-server\", true, \"Server\")
+        super(options, \"http-server\", true, \"Server\", \"3f9a0c1e5b7d2486\")
         if (callbacks?.api) {
             this.api = callbacks.api
         } else {
@@ -111,6 +122,23 @@ export default class Server extends ERPCServer {
             }
         })
     }
+
+    /**
+        Calls the connected Client browsers at once, e.g. Client.all or Client.only(socketIds)
+    */
+    get Client() {
+        const broadcast = (socketIds?: string[]) => {
+            const ret = new Client({address: \"\"})
+            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
+            // @ts-ignore
+            ret.setERPCBroadcast(this, \"Client\", socketIds)
+            return ret as unknown as Broadcast<Client>
+        }
+        return {
+            all: broadcast(),
+            only: (socketIds: string[]) => broadcast(socketIds),
+        }
+    }
 }"
         );
     }
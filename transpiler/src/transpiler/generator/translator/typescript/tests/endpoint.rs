@@ -3,10 +3,12 @@ mod tests {
     use tower_lsp::lsp_types::Range;
 
     use crate::transpiler::{
-        generator::translator::typescript::endpoint::endpoint_to_function,
+        generator::translator::typescript::endpoint::{
+            endpoint_to_function, middleware_to_function,
+        },
         parser::parser::{
             endpoint::{Endpoint, Parameter},
-            erpc_type::{ArrayAmount, Primitive, PrimitiveType, Type},
+            erpc_type::{ArrayAmount, Custom, Primitive, PrimitiveType, Type},
         },
     };
 
@@ -22,6 +24,9 @@ mod tests {
                 array_amount: ArrayAmount::NoLengthSpecified,
                 primitive_type: PrimitiveType::String,
             })),
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
             parameters: vec![
                 Parameter {
                     identifier: "p1".to_string(),
@@ -42,7 +47,7 @@ mod tests {
             ],
         };
 
-        let result = endpoint_to_function(&ep, true, "ABC123");
+        let result = endpoint_to_function(&ep, true, "ABC123", &vec![]);
 
         assert_eq!(
             result,
@@ -67,6 +72,9 @@ mod tests {
                 array_amount: ArrayAmount::NoLengthSpecified,
                 primitive_type: PrimitiveType::String,
             })),
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
             parameters: vec![
                 Parameter {
                     identifier: "p1".to_string(),
@@ -87,7 +95,7 @@ mod tests {
             ],
         };
 
-        let result = endpoint_to_function(&ep, false, "ABC123");
+        let result = endpoint_to_function(&ep, false, "ABC123", &vec![]);
 
         assert_eq!(
             result,
@@ -95,12 +103,289 @@ mod tests {
     private _MySuperCoolEndpoint: (p1?: string[], p2: number) => Promise<string[]> = undefined as any
     set MySuperCoolEndpoint(value: (p1?: string[], p2: number) => Promise<string[]>) {
         this._MySuperCoolEndpoint = value
-        this.server?.registerERPCHandler(value, \"ABC123\")
+        this.server?.registerERPCHandler(value, \"ABC123\", false, [], {\"parameters\":[{\"name\":\"p1\",\"optional\":true,\"schema\":{\"items\":{\"kind\":\"string\"},\"kind\":\"array\"}},{\"name\":\"p2\",\"optional\":false,\"schema\":{\"kind\":\"integer\",\"max\":127,\"min\":-128}}],\"types\":{}})
     }
     get MySuperCoolEndpoint() {
         return this._MySuperCoolEndpoint
     }
 
+"
+        )
+    }
+
+    #[test]
+    fn test_error_type_foreign() {
+        let ep = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "login".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "Session".to_string(),
+            })),
+            streaming: false,
+            named_parameters: false,
+            error_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "LoginError".to_string(),
+            })),
+            parameters: vec![],
+        };
+
+        let result = endpoint_to_function(&ep, true, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    login(): Promise<{ ok: true, value: Session } | { ok: false, error: LoginError }> {
+        return this.server.call(\"ABC123\", [], true)
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_error_type_callback() {
+        let ep = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "login".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: false,
+            error_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "LoginError".to_string(),
+            })),
+            parameters: vec![Parameter {
+                identifier: "user".to_string(),
+                optional: false,
+                parameter_type: Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                }),
+            }],
+        };
+
+        let result = endpoint_to_function(&ep, false, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    private _login: (user: string) => Promise<{ ok: true, value: void } | { ok: false, error: LoginError }> = undefined as any
+    set login(value: (user: string) => Promise<{ ok: true, value: void } | { ok: false, error: LoginError }>) {
+        this._login = value
+        this.server?.registerERPCHandler(value, \"ABC123\", true, [], {\"parameters\":[{\"name\":\"user\",\"optional\":false,\"schema\":{\"kind\":\"string\"}}],\"types\":{}})
+    }
+    get login() {
+        return this._login
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_middleware_callback() {
+        let ep = Endpoint {
+            middleware_identifiers: vec!["auth".to_string(), "log".to_string()],
+            documentation: None,
+            range: Range::default(),
+            identifier: "secret".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![],
+        };
+
+        let result = endpoint_to_function(&ep, false, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    private _secret: () => Promise<void> = undefined as any
+    set secret(value: () => Promise<void>) {
+        this._secret = value
+        this.server?.registerERPCHandler(value, \"ABC123\", false, [\"auth\", \"log\"], {\"parameters\":[],\"types\":{}})
+    }
+    get secret() {
+        return this._secret
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_middleware_function() {
+        let ep = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "auth".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![],
+        };
+
+        let result = middleware_to_function(&ep);
+
+        assert_eq!(
+            result,
+            "    private _auth: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void> = undefined as any
+    set auth(value: (call: ERPCMiddlewareCall) => Promise<ERPCMiddlewareCall | void>) {
+        this._auth = value
+        this.server?.registerERPCMiddleware(value, \"auth\")
+    }
+    get auth() {
+        return this._auth
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_stream_foreign() {
+        let ep = Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "ticks".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "Tick".to_string(),
+            })),
+            streaming: true,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![Parameter {
+                identifier: "symbol".to_string(),
+                optional: false,
+                parameter_type: Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                }),
+            }],
+        };
+
+        let result = endpoint_to_function(&ep, true, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    ticks(symbol: string): AsyncIterable<Tick> {
+        const stream = this.server.stream(\"ABC123\", [symbol])
+        return { [Symbol.asyncIterator]: () => stream }
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_stream_callback() {
+        let ep = Endpoint {
+            middleware_identifiers: vec!["auth".to_string()],
+            documentation: None,
+            range: Range::default(),
+            identifier: "ticks".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: Some(Type::Custom(Custom {
+                array_amount: ArrayAmount::NoArray,
+                identifier: "Tick".to_string(),
+            })),
+            streaming: true,
+            named_parameters: false,
+            error_type: None,
+            parameters: vec![],
+        };
+
+        let result = endpoint_to_function(&ep, false, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    private _ticks: () => AsyncIterable<Tick> = undefined as any
+    set ticks(value: () => AsyncIterable<Tick>) {
+        this._ticks = value
+        this.server?.registerERPCStreamHandler(value, \"ABC123\", [\"auth\"], {\"parameters\":[],\"types\":{}})
+    }
+    get ticks() {
+        return this._ticks
+    }
+
+"
+        )
+    }
+
+    fn named_endpoint() -> Endpoint {
+        Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "login".to_string(),
+            role: "MyVeryNiceRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters: true,
+            error_type: None,
+            parameters: vec![
+                Parameter {
+                    identifier: "user".to_string(),
+                    optional: false,
+                    parameter_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::NoArray,
+                        primitive_type: PrimitiveType::String,
+                    }),
+                },
+                Parameter {
+                    identifier: "remember".to_string(),
+                    optional: true,
+                    parameter_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::NoArray,
+                        primitive_type: PrimitiveType::Boolean,
+                    }),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_named_parameters_foreign() {
+        let result = endpoint_to_function(&named_endpoint(), true, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    login(parameters: { user: string, remember?: boolean }): Promise<void> {
+        return this.server.call(\"ABC123\", parameters)
+    }
+
+"
+        )
+    }
+
+    #[test]
+    fn test_named_parameters_callback() {
+        let result = endpoint_to_function(&named_endpoint(), false, "ABC123", &vec![]);
+
+        assert_eq!(
+            result,
+            "    private _login: (parameters: { user: string, remember?: boolean }) => Promise<void> = undefined as any
+    set login(value: (parameters: { user: string, remember?: boolean }) => Promise<void>) {
+        this._login = value
+        this.server?.registerERPCHandler(value, \"ABC123\", false, [], {\"named\":true,\"parameters\":[{\"name\":\"user\",\"optional\":false,\"schema\":{\"kind\":\"string\"}},{\"name\":\"remember\",\"optional\":true,\"schema\":{\"kind\":\"boolean\"}}],\"types\":{}})
+    }
+    get login() {
+        return this._login
+    }
+
 "
         )
     }
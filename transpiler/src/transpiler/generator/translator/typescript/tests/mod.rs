@@ -1,4 +1,4 @@
-mod interface;
-mod endpoint;
 mod class;
-mod client;
\ No newline at end of file
+mod client;
+mod endpoint;
+mod interface;
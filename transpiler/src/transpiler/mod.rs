@@ -1,3 +1,4 @@
+pub mod compatibility;
 pub mod config;
 mod generator;
 mod parser;
@@ -5,66 +6,77 @@ mod tests;
 
 pub mod validator;
 
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use crate::error::{Diagnostic, DisplayableError};
 
 use self::{
-    config::parse_roles,
-    generator::{generate_for_directory, translator::typescript::TypeScriptTranslator},
+    config::{parse_roles, Language, Role},
+    generator::{
+        generate_for_directory,
+        translator::{
+            is_middleware_class,
+            rust::{handler_signature, RustTranslator},
+            typescript::TypeScriptTranslator,
+        },
+    },
     parser::{
         input_reader::InputReader,
         lexer::TokenReader,
         parser::{endpoint::Endpoint, parse},
     },
-    validator::validate,
+    validator::{validate, validate_rust},
 };
 
-pub async fn run(
+pub use generator::translator::rust::HandlerSignature;
+
+/**
+   Reads the roles of the roles.json and the middleware of the middleware.erpc in the source directory.
+   The middleware is empty if there is no middleware.erpc
+*/
+fn read_roles_and_middleware(
     source_directory: &Path,
-    output: &Path,
-    selected_role_name: &str,
-) -> Vec<DisplayableError> {
-    // --- Config ---
+) -> Result<(Vec<Role>, Vec<Endpoint>), Vec<DisplayableError>> {
     let roles_json_path = source_directory.join("roles.json");
     if !roles_json_path.exists() {
-        return vec![format!(
+        return Err(vec![format!(
             "Could not find roles.json at {path_str}",
             path_str = roles_json_path
                 .as_os_str()
                 .to_str()
                 .unwrap_or("<Unable to unwrap path>")
         )
-        .into()];
+        .into()]);
     }
 
     let available_roles = match parse_roles(match File::open(roles_json_path.clone()) {
         Ok(v) => v,
         Err(err) => {
-            return vec![format!(
+            return Err(vec![format!(
                 "Could not open {path_str}: {err}",
                 path_str = roles_json_path
                     .to_str()
                     .unwrap_or("<Unable to unwrap path>")
             )
-            .into()];
+            .into()]);
         }
     }) {
         Ok(v) => v,
         Err(err) => {
-            return vec![format!(
+            return Err(vec![format!(
                 "Could not parse roles at {path_str}: {err}",
                 path_str = roles_json_path
                     .as_os_str()
                     .to_str()
                     .unwrap_or("<Unable to unwrap path>")
             )
-            .into()];
+            .into()]);
         }
     };
 
-    // --- Middleware ---
-
     let mut available_middleware = Vec::<Endpoint>::new();
     let middleware_erpc_path = source_directory.join("middleware.erpc");
     if middleware_erpc_path.exists() {
@@ -72,35 +84,35 @@ pub async fn run(
             match TokenReader::new(InputReader::new(match File::open(&middleware_erpc_path) {
                 Ok(v) => v,
                 Err(err) => {
-                    return vec![format!(
+                    return Err(vec![format!(
                         "Could not open file {}: {err}",
                         middleware_erpc_path
                             .to_str()
                             .unwrap_or("<could not unwrap path>")
                     )
-                    .into()]
+                    .into()])
                 }
             })) {
                 Ok(v) => v,
                 Err(err) => {
-                    return vec![format!(
+                    return Err(vec![format!(
                         "Input reader error occurred at {}: {err}",
                         middleware_erpc_path
                             .to_str()
                             .unwrap_or("<could not unwrap path>")
                     )
-                    .into()];
+                    .into()]);
                 }
             };
 
         let parse_result = match parse(&mut reader) {
             Ok(val) => val,
             Err(err) => {
-                return vec![DisplayableError::Diagnostic(Diagnostic {
+                return Err(vec![DisplayableError::Diagnostic(Diagnostic {
                     source: middleware_erpc_path,
                     range: err.range,
                     message: err.message,
-                })];
+                })]);
             }
         };
 
@@ -111,7 +123,7 @@ pub async fn run(
             &vec![],
         );
         if !validation_errors.is_empty() {
-            return validation_errors
+            return Err(validation_errors
                 .into_iter()
                 .map(|err| {
                     DisplayableError::Diagnostic(Diagnostic {
@@ -120,17 +132,182 @@ pub async fn run(
                         message: err.message,
                     })
                 })
-                .collect();
+                .collect());
         }
 
         available_middleware = parse_result.endpoints;
     }
 
-    generate_for_directory::<TypeScriptTranslator>(
-        source_directory,
-        output,
-        selected_role_name,
+    Ok((available_roles, available_middleware))
+}
+
+pub async fn run(
+    source_directory: &Path,
+    output: &Path,
+    selected_role_name: &str,
+    language: &Language,
+) -> Vec<DisplayableError> {
+    let (available_roles, available_middleware) = match read_roles_and_middleware(source_directory)
+    {
+        Ok(v) => v,
+        Err(errors) => return errors,
+    };
+
+    let roles_json_path = source_directory.join("roles.json");
+    match available_roles
+        .iter()
+        .find(|role| role.name == selected_role_name)
+    {
+        Some(role) => {
+            if *language == Language::Rust && role.role_type.contains(&"browser".to_string()) {
+                return vec![format!(
+                    "Role {selected_role_name} is a browser role, Rust code can only be generated for http-server roles"
+                )
+                .into()];
+            }
+        }
+        None => {
+            return vec![format!(
+                "Role {selected_role_name} is not configured in the roles.json at {path_str}",
+                path_str = roles_json_path
+                    .as_os_str()
+                    .to_str()
+                    .unwrap_or("<Unable to unwrap path>")
+            )
+            .into()];
+        }
+    };
+
+    match language {
+        Language::TypeScript => generate_for_directory::<TypeScriptTranslator>(
+            source_directory,
+            output,
+            selected_role_name,
+            &available_roles,
+            &available_middleware,
+        ),
+        Language::Rust => generate_for_directory::<RustTranslator>(
+            source_directory,
+            output,
+            selected_role_name,
+            &available_roles,
+            &available_middleware,
+        ),
+    }
+}
+
+/**
+   Looks up the endpoint with the identifier, e.g. api/ping, in the sources and describes the rust handler it needs.
+   Only endpoints of the selected role are handled by its server. Also returns the source file the endpoint is declared in.
+*/
+pub fn find_rust_handler(
+    source_directory: &Path,
+    selected_role_name: &str,
+    identifier: &str,
+) -> Result<(PathBuf, HandlerSignature), DisplayableError> {
+    // the identifier is the path of the source file relative to the source directory followed by the endpoint
+    let (class_path, endpoint_identifier) = match identifier.rsplit_once('/') {
+        Some(v) => v,
+        None => {
+            return Err(format!(
+                "{identifier} is not an endpoint identifier, expected the source file followed by the endpoint, e.g. api/ping"
+            )
+            .into())
+        }
+    };
+    let (relative_path, class_name) = match class_path.rsplit_once('/') {
+        Some((relative_path, class_name)) => (format!("{relative_path}/"), class_name),
+        None => (String::new(), class_path),
+    };
+    if is_middleware_class(class_name, &relative_path) {
+        return Err(format!("{identifier} is declared as middleware, not as endpoint").into());
+    }
+
+    let source = source_directory.join(format!("{class_path}.erpc"));
+    let mut reader = match TokenReader::new(InputReader::new(match File::open(&source) {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(format!(
+                "Could not open file {} for {identifier}: {err}",
+                source.to_str().unwrap_or("<could not unwrap path>")
+            )
+            .into())
+        }
+    })) {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(format!(
+                "Input reader error occurred at {}: {err}",
+                source.to_str().unwrap_or("<could not unwrap path>")
+            )
+            .into())
+        }
+    };
+
+    let parse_result = match parse(&mut reader) {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(DisplayableError::Diagnostic(Diagnostic {
+                source,
+                range: err.range,
+                message: err.message,
+            }))
+        }
+    };
+
+    let endpoint = match parse_result
+        .endpoints
+        .iter()
+        .find(|endpoint| endpoint.identifier == endpoint_identifier)
+    {
+        Some(v) => v,
+        None => {
+            return Err(format!(
+                "Could not find endpoint {endpoint_identifier} in {}",
+                source.to_str().unwrap_or("<could not unwrap path>")
+            )
+            .into())
+        }
+    };
+
+    if endpoint.role != selected_role_name {
+        return Err(format!(
+            "{identifier} is an endpoint of {}, it can't be handled by {selected_role_name}",
+            endpoint.role
+        )
+        .into());
+    }
+
+    // the handler has to fit the sources like generated code, e.g. streaming endpoints can't declare an error type
+    let (available_roles, available_middleware) = match read_roles_and_middleware(source_directory)
+    {
+        Ok(v) => v,
+        Err(errors) => {
+            return Err(match errors.into_iter().next() {
+                Some(v) => v,
+                None => format!("Could not read the roles of {identifier}").into(),
+            })
+        }
+    };
+    let validation_error = validate(
+        &parse_result.endpoints,
+        &parse_result.custom_types,
         &available_roles,
         &available_middleware,
     )
+    .into_iter()
+    .chain(validate_rust(
+        &parse_result.endpoints,
+        &parse_result.custom_types,
+    ))
+    .next();
+    if let Some(err) = validation_error {
+        return Err(DisplayableError::Diagnostic(Diagnostic {
+            source,
+            range: err.range,
+            message: err.message,
+        }));
+    }
+
+    Ok((source, handler_signature(endpoint, &parse_result.custom_types)))
 }
@@ -24,10 +24,33 @@ pub struct Endpoint {
     pub identifier: String,
     pub role: String,
     pub parameters: Vec<Parameter>,
+    /**
+       Whether the parameters are declared in curly brackets, e.g. login({user string}).
+       Such endpoints are called with an object keyed by parameter name instead of a list of parameters.
+    */
+    pub named_parameters: bool,
     pub return_type: Option<Type>,
+    /**
+       Whether the endpoint returns a stream of items of the return type instead of a single value
+    */
+    pub streaming: bool,
+    /**
+       The type of the application error declared with throws
+    */
+    pub error_type: Option<Type>,
     pub middleware_identifiers: Vec<String>,
 }
 
+/**
+   Separates the return type from the error type of an endpoint
+*/
+const THROWS: &str = "throws";
+
+/**
+   Marks the return type of an endpoint as the type of the items of a stream
+*/
+const STREAM: &str = "stream";
+
 impl Endpoint {
     pub fn parse_endpoint(reader: &mut TokenReader) -> Option<Result<Endpoint, ParseError>> {
         /*
@@ -118,6 +141,20 @@ impl Endpoint {
 
         let mut parameters: Vec<Parameter> = Vec::new();
 
+        // named parameters are wrapped in curly brackets
+        let named_parameters = match reader.peek(1) {
+            Some(v) => match &v[0] {
+                Token::Operator(operator) => {
+                    matches!(operator.operator_type, OperatorType::CurlyOpenBracket)
+                }
+                _ => false,
+            },
+            None => false,
+        };
+        if named_parameters {
+            reader.consume(1);
+        }
+
         loop {
             let peeked = reader.peek(1);
             // in valid cases this is either a parameter token or the closing bracket which at this point is not yet consumed
@@ -131,11 +168,51 @@ impl Endpoint {
             let peeked = &peeked.unwrap()[0];
 
             match peeked {
+                // named parameters can be spread over multiple lines
+                Token::LineBreak(_) if named_parameters => {
+                    reader.consume(1);
+                    continue;
+                }
                 Token::Operator(operator) => match operator.operator_type {
+                    OperatorType::CloseBracket if named_parameters => {
+                        return Some(Err(ParseError {
+                            range: operator.range,
+                            message: "Expected a closing curly bracket before the closing bracket"
+                                .to_string(),
+                        }))
+                    }
                     OperatorType::CloseBracket => {
                         reader.consume(1);
                         break;
                     }
+                    OperatorType::CurlyCloseBracket if named_parameters => {
+                        if parameters.is_empty() {
+                            return Some(Err(ParseError {
+                                range: operator.range,
+                                message:
+                                    "Expected named parameters instead of closing curly bracket"
+                                        .to_string(),
+                            }));
+                        }
+                        reader.consume(1);
+
+                        match reader.peek(1).map(|v| v[0].to_owned()) {
+                            Some(Token::Operator(operator))
+                                if matches!(operator.operator_type, OperatorType::CloseBracket) =>
+                            {
+                                reader.consume(1);
+                                break;
+                            }
+                            _ => {
+                                return Some(Err(ParseError {
+                                    range: reader.last_token_range,
+                                    message:
+                                        "Expected a closing bracket after the named parameters"
+                                            .to_string(),
+                                }))
+                            }
+                        }
+                    }
                     OperatorType::Comma => {
                         reader.consume(1);
                         let next = reader.peek(1);
@@ -171,12 +248,26 @@ impl Endpoint {
             parameters.push(endpoint.unwrap())
         }
 
+        // stream is not a keyword for the same reason as throws
+        let mut streaming = false;
+        if let Some(stream_token) = reader.peek(1) {
+            match stream_token[0].to_owned() {
+                Token::Identifier(identifier) if identifier.content == STREAM => {
+                    reader.consume(1);
+                    streaming = true;
+                }
+                _ => {}
+            }
+        }
+
         let mut return_type: Option<Type> = None;
         let return_token = reader.peek(1);
 
         if return_token.is_some() {
             match return_token.unwrap()[0].to_owned() {
                 Token::LineBreak(_) => {}
+                // an endpoint without return type can still declare an error type
+                Token::Identifier(identifier) if identifier.content == THROWS => {}
                 _ => {
                     let t = parse_field_type(reader);
                     if t.is_err() {
@@ -187,6 +278,28 @@ impl Endpoint {
             }
         }
 
+        if streaming && return_type.is_none() {
+            return Some(Err(ParseError {
+                range: reader.last_token_range,
+                message: "Expected the type of the stream items".to_string(),
+            }));
+        }
+
+        // throws is not a keyword, since the lexer would then split identifiers starting with it
+        let mut error_type: Option<Type> = None;
+        if let Some(throws_token) = reader.peek(1) {
+            match throws_token[0].to_owned() {
+                Token::Identifier(identifier) if identifier.content == THROWS => {
+                    reader.consume(1);
+                    error_type = match parse_field_type(reader) {
+                        Ok(v) => Some(v),
+                        Err(err) => return Some(Err(err)),
+                    };
+                }
+                _ => {}
+            }
+        }
+
         Some(Ok(Endpoint {
             documentation,
             range: Range {
@@ -195,7 +308,10 @@ impl Endpoint {
             },
             identifier,
             parameters,
+            named_parameters,
             return_type,
+            streaming,
+            error_type,
             role,
             middleware_identifiers,
         }))
@@ -861,4 +861,202 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_return_value_and_error_type() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend login(user string) Session throws LoginError".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert_eq!(result.range.end.character, 52);
+        assert_eq!(result.parameters.len(), 1);
+
+        match result.return_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "Session"),
+            _ => panic!("Should not match"),
+        }
+
+        match result.error_type.unwrap() {
+            Type::Custom(value) => {
+                assert_eq!(value.identifier, "LoginError");
+                assert!(matches!(value.array_amount, ArrayAmount::NoArray));
+            }
+            _ => panic!("Should not match"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_type_without_return_value() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend logout() throws LogoutError\nBackend other()".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(result.return_type.is_none());
+        match result.error_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "LogoutError"),
+            _ => panic!("Should not match"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_error_type() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend login(user string) throwsSomething".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        match result.return_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "throwsSomething"),
+            _ => panic!("Should not match"),
+        }
+        assert!(result.error_type.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_error_type() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend login(user string) Session throws".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap();
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend ticks(symbol string) stream Tick\nBackend other()".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(result.streaming);
+        assert_eq!(result.range.end.character, 40);
+        match result.return_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "Tick"),
+            _ => panic!("Should not match"),
+        }
+        assert!(result.error_type.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_stream() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend ticks(symbol string) streamingTick".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(!result.streaming);
+        match result.return_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "streamingTick"),
+            _ => panic!("Should not match"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_stream() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend ticks(symbol string) stream\nBackend other()".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap();
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_parameters() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend login({user string, password? string}) Session".as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(result.named_parameters);
+        assert_eq!(result.parameters.len(), 2);
+        assert_eq!(result.parameters[0].identifier, "user");
+        assert_eq!(result.parameters[1].identifier, "password");
+        assert!(result.parameters[1].optional);
+        match result.return_type.unwrap() {
+            Type::Custom(value) => assert_eq!(value.identifier, "Session"),
+            _ => panic!("Should not match"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_parameters_multiline() -> Result<(), InputReaderError> {
+        let mut reader = TokenReader::new(InputReader::new(
+            "Backend login({\n    user string,\n    password string\n})\nBackend other()"
+                .as_bytes(),
+        ))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(result.named_parameters);
+        assert_eq!(result.parameters.len(), 2);
+        assert!(result.return_type.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_positional_parameters_are_not_named() -> Result<(), InputReaderError> {
+        let mut reader =
+            TokenReader::new(InputReader::new("Backend login(user string)".as_bytes()))?;
+
+        let result = Endpoint::parse_endpoint(&mut reader).unwrap().unwrap();
+
+        assert!(!result.named_parameters);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_named_parameters() -> Result<(), InputReaderError> {
+        for (source, message) in [
+            (
+                "Backend login({user string)",
+                "Expected a closing curly bracket before the closing bracket",
+            ),
+            (
+                "Backend login({user string} Session",
+                "Expected a closing bracket after the named parameters",
+            ),
+            (
+                "Backend login({})",
+                "Expected named parameters instead of closing curly bracket",
+            ),
+        ] {
+            let mut reader = TokenReader::new(InputReader::new(source.as_bytes()))?;
+
+            let result = Endpoint::parse_endpoint(&mut reader).unwrap();
+            match result {
+                Err(err) => assert_eq!(err.message, message),
+                Ok(_) => panic!("Expected an error for {source}"),
+            }
+        }
+
+        Ok(())
+    }
 }
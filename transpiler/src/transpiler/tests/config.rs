@@ -2,7 +2,7 @@
 mod tests {
     use serde_json::Error;
 
-    use crate::transpiler::config::{parse_config, parse_roles};
+    use crate::transpiler::config::{parse_config, parse_roles, Language};
 
     #[test]
     fn test_roles() -> Result<(), Error> {
@@ -53,6 +53,24 @@ mod tests {
             result.sources,
             vec!["../../erpc-sources", "../../erpc-sources2"]
         );
+        assert_eq!(result.language, Language::TypeScript);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_language() -> Result<(), Error> {
+        let result = parse_config(
+            "{
+                \"sources\": [\"../../erpc-sources\"],
+                \"role\": \"backend\",
+                \"language\": \"rust\"
+              }"
+            .as_bytes(),
+        )?;
+
+        assert_eq!(result.role, "backend");
+        assert_eq!(result.language, Language::Rust);
 
         Ok(())
     }
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::transpiler::{find_rust_handler, HandlerSignature};
+
+    fn sources() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("transpiler")
+            .join("tests")
+            .join("handler_test_files")
+    }
+
+    #[test]
+    fn test_handler() {
+        let (source, signature) =
+            find_rust_handler(&sources(), "Server", "api/users/find").unwrap();
+
+        assert_eq!(source, sources().join("api/users.erpc"));
+        assert_eq!(
+            signature,
+            HandlerSignature {
+                parameters: vec!["String".to_string(), "Option<i32>".to_string()],
+                output: "Vec<User>".to_string(),
+                streaming: false,
+                fallible: false,
+                middleware: vec!["auth".to_string()],
+                schema: r#"{"parameters":[{"name":"name","optional":false,"schema":{"kind":"string"}},{"name":"limit","optional":true,"schema":{"kind":"integer","max":2147483647,"min":-2147483648}}],"types":{}}"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_fallible_and_stream_handlers() {
+        let (_, signature) = find_rust_handler(&sources(), "Server", "api/users/rename").unwrap();
+        assert_eq!(signature.parameters, vec!["User", "String"]);
+        assert_eq!(signature.output, "Result<RenameReturn, RenameError>");
+        assert!(signature.fallible);
+
+        let (_, signature) = find_rust_handler(&sources(), "Server", "api/users/watch").unwrap();
+        assert_eq!(signature.parameters, vec!["Vec<String>"]);
+        assert_eq!(signature.output, "User");
+        assert!(signature.streaming);
+    }
+
+    #[test]
+    fn test_named_parameters_handler() {
+        let (_, signature) = find_rust_handler(&sources(), "Server", "api/users/create").unwrap();
+        assert_eq!(signature.parameters, vec!["CreateParameters"]);
+        assert_eq!(signature.output, "User");
+    }
+
+    #[test]
+    fn test_fallible_stream_handler() {
+        let err = find_rust_handler(&sources(), "Server", "invalid/events/watch").unwrap_err();
+        assert!(
+            err.to_string().contains("can't declare an error type"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_identifiers() {
+        for identifier in [
+            "find",
+            "api/users/missing",
+            "api/missing/find",
+            "api/users/notify",
+            "middleware/auth",
+        ] {
+            assert!(
+                find_rust_handler(&sources(), "Server", identifier).is_err(),
+                "{identifier} should not be found"
+            );
+        }
+    }
+}
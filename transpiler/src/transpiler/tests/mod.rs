@@ -1 +1,2 @@
-mod config;
\ No newline at end of file
+mod config;
+mod handler;
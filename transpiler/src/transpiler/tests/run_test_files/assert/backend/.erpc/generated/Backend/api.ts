@@ -25,7 +25,7 @@ export default class api {
     private _test2: () => Promise<void> = undefined as any
     set test2(value: () => Promise<void>) {
         this._test2 = value
-        this.server?.registerERPCCallbackFunction(value, "api/test2")
+        this.server?.registerERPCHandler(value, "api/test2", false, [], {"parameters":[],"types":{}})
     }
     get test2() {
         return this._test2
@@ -7,8 +7,8 @@ export default class api {
         this.server = server
     }
 
-    test8(): Promise<void> {
-        return this.server.call("api/test8")
+    test1(): Promise<void> {
+        return this.server.call("api/test1")
     }
 
 }
\ No newline at end of file
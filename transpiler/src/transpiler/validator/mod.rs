@@ -9,10 +9,20 @@ use super::{
     parser::parser::{
         custom_type::CustomType,
         endpoint::Endpoint,
-        erpc_type::{EnumType, Type},
+        erpc_type::{ArrayAmount, EnumType, Type},
     },
 };
 
+/**
+   Rust handlers take at most this many parameters, endpoints with named parameters take a single struct instead
+*/
+const MAX_POSITIONAL_PARAMETERS: usize = 20;
+
+/**
+   Rust keywords which can't be escaped as raw identifiers, so they can't be generated at all
+*/
+const RESERVED_RUST_IDENTIFIERS: [&str; 4] = ["crate", "self", "Self", "super"];
+
 #[derive(Debug)]
 pub struct ValidationError {
     pub range: Range,
@@ -95,6 +105,70 @@ pub fn validate(
             None => {}
         }
 
+        // application errors are sent as objects, therefore only non array custom types are allowed
+        match &endpoint.error_type {
+            Some(Type::Custom(cstm)) => match cstm.array_amount {
+                ArrayAmount::NoArray => {
+                    required_types.push((cstm.identifier.to_owned(), endpoint.range))
+                }
+                _ => errors.push(ValidationError {
+                    range: endpoint.range,
+                    message: format!(
+                        "Error type of endpoint {} can't be an array",
+                        endpoint.identifier
+                    ),
+                }),
+            },
+            Some(_) => errors.push(ValidationError {
+                range: endpoint.range,
+                message: format!(
+                    "Error type of endpoint {} must be a custom type",
+                    endpoint.identifier
+                ),
+            }),
+            None => {}
+        }
+
+        // errors of a stream end it, so there is no single result which could carry an application error
+        if endpoint.streaming && endpoint.error_type.is_some() {
+            errors.push(ValidationError {
+                range: endpoint.range,
+                message: format!(
+                    "Endpoint {} can't declare an error type since it returns a stream",
+                    endpoint.identifier
+                ),
+            });
+        }
+
+        // named parameters are sent as the keys of an object, so every name can only be used once
+        let mut visited_parameters = HashSet::<&str>::new();
+        for param in &endpoint.parameters {
+            if !visited_parameters.insert(&param.identifier) {
+                errors.push(ValidationError {
+                    range: endpoint.range,
+                    message: format!(
+                        "Parameter {} is defined multiple times on endpoint {}",
+                        param.identifier, endpoint.identifier
+                    ),
+                });
+            }
+        }
+
+        // the browser runtime has no middleware support, running the endpoint without it could skip checks like authentication.
+        // This is a breaking change, such sources were accepted while middleware was not run anywhere
+        if !endpoint.middleware_identifiers.is_empty() {
+            match roles.iter().find(|val| val.name == endpoint.role) {
+                Some(role) if role.role_type.contains("browser") => errors.push(ValidationError {
+                    range: endpoint.range,
+                    message: format!(
+                        "Middleware is not supported on endpoint {} since role {} is a browser",
+                        endpoint.identifier, endpoint.role
+                    ),
+                }),
+                _ => {}
+            }
+        }
+
         let mut visited_middlewares = HashSet::<String>::new();
         for middleware in &endpoint.middleware_identifiers {
             if visited_middlewares.contains(middleware) {
@@ -203,3 +277,58 @@ pub fn validate(
 
     errors
 }
+
+/**
+   Checks the limits of the generated rust code, the other languages don't have them
+*/
+pub fn validate_rust(endpoints: &[Endpoint], custom_types: &[CustomType]) -> Vec<ValidationError> {
+    let mut errors: Vec<ValidationError> = endpoints
+        .iter()
+        .filter(|endpoint| {
+            !endpoint.named_parameters && endpoint.parameters.len() > MAX_POSITIONAL_PARAMETERS
+        })
+        .map(|endpoint| ValidationError {
+            range: endpoint.range,
+            message: format!(
+                "Endpoint {} has more than {MAX_POSITIONAL_PARAMETERS} parameters, declare them as named parameters like {}({{...}}) instead",
+                endpoint.identifier, endpoint.identifier
+            ),
+        })
+        .collect();
+
+    for endpoint in endpoints {
+        for identifier in std::iter::once(&endpoint.identifier).chain(
+            endpoint
+                .parameters
+                .iter()
+                .map(|parameter| &parameter.identifier),
+        ) {
+            if let Some(error) = reserved_rust_identifier(identifier, endpoint.range) {
+                errors.push(error);
+            }
+        }
+    }
+
+    for custom_type in custom_types {
+        for identifier in std::iter::once(&custom_type.identifier)
+            .chain(custom_type.fields.iter().map(|field| &field.identifier))
+        {
+            if let Some(error) = reserved_rust_identifier(identifier, custom_type.range) {
+                errors.push(error);
+            }
+        }
+    }
+
+    errors
+}
+
+fn reserved_rust_identifier(identifier: &str, range: Range) -> Option<ValidationError> {
+    if !RESERVED_RUST_IDENTIFIERS.contains(&identifier) {
+        return None;
+    }
+
+    Some(ValidationError {
+        range,
+        message: format!("{identifier} is reserved in rust and can not be used as identifier"),
+    })
+}
@@ -5,10 +5,11 @@ mod tests {
     use crate::transpiler::{
         config::Role,
         parser::parser::{
+            custom_type::{CustomType, Field},
             endpoint::{Endpoint, Parameter},
-            erpc_type::{ArrayAmount, Custom, Enum, EnumType, Type},
+            erpc_type::{ArrayAmount, Custom, Enum, EnumType, Primitive, PrimitiveType, Type},
         },
-        validator::validate,
+        validator::{validate, validate_rust},
     };
 
     #[test]
@@ -28,6 +29,9 @@ mod tests {
                     identifier: "SuperCoolEndpoint".to_string(),
                     role: "SomeRole1".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                 },
                 Endpoint {
@@ -46,6 +50,9 @@ mod tests {
                     identifier: "SuperCoolEndpoint".to_string(),
                     role: "SomeRole2".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                 },
                 Endpoint {
@@ -64,6 +71,9 @@ mod tests {
                     identifier: "SuperCoolEndpoint".to_string(),
                     role: "SomeRole1".to_string(),
                     return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
                     parameters: vec![],
                 },
             ],
@@ -111,6 +121,9 @@ mod tests {
                 identifier: "SuperCoolEndpoint".to_string(),
                 role: "SomeRole".to_string(),
                 return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![],
             }],
             &vec![],
@@ -150,6 +163,9 @@ mod tests {
                 identifier: "SuperCoolEndpoint".to_string(),
                 role: "SomeRole".to_string(),
                 return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![Parameter {
                     identifier: "something".to_string(),
                     optional: false,
@@ -196,6 +212,9 @@ mod tests {
                     array_amount: ArrayAmount::NoArray,
                     identifier: "SomeUnknownReturnType".to_string(),
                 })),
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![],
             }],
             &vec![],
@@ -238,6 +257,9 @@ mod tests {
                         identifier: "SomeUnknownType".to_string(),
                     })],
                 })),
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![],
             }],
             &vec![],
@@ -274,6 +296,9 @@ mod tests {
                 identifier: "SuperCoolEndpoint".to_string(),
                 role: "SomeRole".to_string(),
                 return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
                 parameters: vec![Parameter {
                     identifier: "someParam".to_string(),
                     optional: false,
@@ -302,4 +327,326 @@ mod tests {
         assert_eq!(result[0].range.end.character, 30);
         assert_eq!(result[0].range.end.line, 0);
     }
+
+    #[test]
+    fn test_unknown_error_type() {
+        let result = validate(
+            &vec![Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range {
+                    start: Position::default(),
+                    end: Position {
+                        line: 0,
+                        character: 30,
+                    },
+                },
+                identifier: "SuperCoolEndpoint".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: Some(Type::Custom(Custom {
+                    array_amount: ArrayAmount::NoArray,
+                    identifier: "SomeUnknownErrorType".to_string(),
+                })),
+                parameters: vec![],
+            }],
+            &vec![],
+            &vec![Role {
+                documentation: None,
+                name: "SomeRole".to_string(),
+                role_type: "browser".to_string(),
+            }],
+            &vec![],
+        );
+
+        assert_eq!(result.len(), 1);
+
+        assert_eq!(result[0].message, "Type SomeUnknownErrorType is unknown");
+        assert_eq!(result[0].range.end.character, 30);
+    }
+
+    #[test]
+    fn test_non_custom_error_type() {
+        let result = validate(
+            &vec![Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range {
+                    start: Position::default(),
+                    end: Position {
+                        line: 0,
+                        character: 30,
+                    },
+                },
+                identifier: "SuperCoolEndpoint".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: Some(Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                })),
+                parameters: vec![],
+            }],
+            &vec![],
+            &vec![Role {
+                documentation: None,
+                name: "SomeRole".to_string(),
+                role_type: "browser".to_string(),
+            }],
+            &vec![],
+        );
+
+        assert_eq!(result.len(), 1);
+
+        assert_eq!(
+            result[0].message,
+            "Error type of endpoint SuperCoolEndpoint must be a custom type"
+        );
+    }
+
+    #[test]
+    fn test_stream_with_error_type() {
+        let result = validate(
+            &vec![Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range::default(),
+                identifier: "ticks".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: Some(Type::Primitive(Primitive {
+                    array_amount: ArrayAmount::NoArray,
+                    primitive_type: PrimitiveType::String,
+                })),
+                streaming: true,
+                named_parameters: false,
+                error_type: Some(Type::Custom(Custom {
+                    array_amount: ArrayAmount::NoArray,
+                    identifier: "TickError".to_string(),
+                })),
+                parameters: vec![],
+            }],
+            &vec![CustomType {
+                documentation: None,
+                range: Range::default(),
+                identifier: "TickError".to_string(),
+                fields: vec![],
+            }],
+            &vec![Role {
+                documentation: None,
+                name: "SomeRole".to_string(),
+                role_type: "http-server".to_string(),
+            }],
+            &vec![],
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].message,
+            "Endpoint ticks can't declare an error type since it returns a stream"
+        );
+    }
+
+    #[test]
+    fn test_double_parameter() {
+        let parameter = || Parameter {
+            identifier: "user".to_string(),
+            optional: false,
+            parameter_type: Type::Primitive(Primitive {
+                array_amount: ArrayAmount::NoArray,
+                primitive_type: PrimitiveType::String,
+            }),
+        };
+        let result = validate(
+            &vec![Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range::default(),
+                identifier: "login".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: None,
+                streaming: false,
+                named_parameters: true,
+                error_type: None,
+                parameters: vec![parameter(), parameter()],
+            }],
+            &vec![],
+            &vec![Role {
+                documentation: None,
+                name: "SomeRole".to_string(),
+                role_type: "http-server".to_string(),
+            }],
+            &vec![],
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].message,
+            "Parameter user is defined multiple times on endpoint login"
+        );
+    }
+
+    #[test]
+    fn test_too_many_parameters() {
+        let endpoint = |named_parameters: bool| Endpoint {
+            middleware_identifiers: vec![],
+            documentation: None,
+            range: Range::default(),
+            identifier: "create".to_string(),
+            role: "SomeRole".to_string(),
+            return_type: None,
+            streaming: false,
+            named_parameters,
+            error_type: None,
+            parameters: (0..21)
+                .map(|i| Parameter {
+                    identifier: format!("p{i}"),
+                    optional: false,
+                    parameter_type: Type::Primitive(Primitive {
+                        array_amount: ArrayAmount::NoArray,
+                        primitive_type: PrimitiveType::String,
+                    }),
+                })
+                .collect(),
+        };
+        let roles = vec![Role {
+            documentation: None,
+            name: "SomeRole".to_string(),
+            role_type: "http-server".to_string(),
+        }];
+
+        // only rust handlers are limited
+        let result = validate(&vec![endpoint(false)], &vec![], &roles, &vec![]);
+        assert!(result.is_empty());
+
+        let result = validate_rust(&[endpoint(false)], &[]);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].message,
+            "Endpoint create has more than 20 parameters, declare them as named parameters like create({...}) instead"
+        );
+
+        let result = validate_rust(&[endpoint(true)], &[]);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_reserved_rust_identifiers() {
+        let string_type = || {
+            Type::Primitive(Primitive {
+                array_amount: ArrayAmount::NoArray,
+                primitive_type: PrimitiveType::String,
+            })
+        };
+        let result = validate_rust(
+            &[Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range::default(),
+                identifier: "super".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
+                parameters: vec![
+                    Parameter {
+                        identifier: "self".to_string(),
+                        optional: false,
+                        parameter_type: string_type(),
+                    },
+                    // other keywords are escaped as raw identifiers
+                    Parameter {
+                        identifier: "type".to_string(),
+                        optional: false,
+                        parameter_type: string_type(),
+                    },
+                ],
+            }],
+            &[CustomType {
+                documentation: None,
+                range: Range::default(),
+                identifier: "Self".to_string(),
+                fields: vec![Field {
+                    optional: false,
+                    identifier: "crate".to_string(),
+                    field_type: string_type(),
+                    documentation: None,
+                }],
+            }],
+        );
+
+        let messages: Vec<&str> = result.iter().map(|err| err.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "super is reserved in rust and can not be used as identifier",
+                "self is reserved in rust and can not be used as identifier",
+                "Self is reserved in rust and can not be used as identifier",
+                "crate is reserved in rust and can not be used as identifier",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_middleware_on_browser() {
+        let validate_with_role_type = |role_type: &str| {
+            let middleware = Endpoint {
+                middleware_identifiers: vec![],
+                documentation: None,
+                range: Range::default(),
+                identifier: "auth".to_string(),
+                role: "SomeRole".to_string(),
+                return_type: None,
+                streaming: false,
+                named_parameters: false,
+                error_type: None,
+                parameters: vec![],
+            };
+
+            validate(
+                &vec![Endpoint {
+                    middleware_identifiers: vec!["auth".to_string()],
+                    documentation: None,
+                    range: Range {
+                        start: Position::default(),
+                        end: Position {
+                            line: 1,
+                            character: 30,
+                        },
+                    },
+                    identifier: "SuperCoolEndpoint".to_string(),
+                    role: "SomeRole".to_string(),
+                    return_type: None,
+                    streaming: false,
+                    named_parameters: false,
+                    error_type: None,
+                    parameters: vec![],
+                }],
+                &vec![],
+                &vec![Role {
+                    documentation: None,
+                    name: "SomeRole".to_string(),
+                    role_type: role_type.to_string(),
+                }],
+                &vec![middleware],
+            )
+        };
+
+        // middleware on server endpoints stays valid like before middleware was run
+        assert!(validate_with_role_type("http-server").is_empty());
+
+        // this was accepted before as well, it is rejected since browsers can't run the middleware
+        let result = validate_with_role_type("browser");
+        assert_eq!(result.len(), 1);
+
+        assert_eq!(
+            result[0].message,
+            "Middleware is not supported on endpoint SuperCoolEndpoint since role SomeRole is a browser"
+        );
+        assert_eq!(result[0].range.end.line, 1);
+    }
 }
@@ -1,2 +1,2 @@
+mod endpoints;
 mod types;
-mod endpoints;
\ No newline at end of file